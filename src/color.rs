@@ -0,0 +1,171 @@
+/// A color with straight (non-premultiplied) alpha, each channel in 0..=1
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Rgba {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    pub a: f64,
+}
+
+impl Rgba {
+    pub fn new(r: f64, g: f64, b: f64, a: f64) -> Rgba {
+        return Rgba {
+            r: r,
+            g: g,
+            b: b,
+            a: a,
+        };
+    }
+
+    pub fn transparent() -> Rgba {
+        return Rgba::new(0.0, 0.0, 0.0, 0.0);
+    }
+
+    pub fn with_alpha(&self, a: f64) -> Rgba {
+        return Rgba { a: a, ..*self };
+    }
+
+    pub fn lerp(&self, other: &Rgba, t: f64) -> Rgba {
+        return Rgba {
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+            a: self.a + (other.a - self.a) * t,
+        };
+    }
+
+    /// Converts to cyan, magenta, yellow and key (black) ink amounts. Grays
+    /// use black ink only, so thin gray lines don't print as blurry mixes of
+    /// all four inks
+    pub fn to_cmyk(&self) -> (f64, f64, f64, f64) {
+        let (r, g, b) = (
+            self.r.max(0.0).min(1.0),
+            self.g.max(0.0).min(1.0),
+            self.b.max(0.0).min(1.0),
+        );
+        let k = 1.0 - r.max(g).max(b);
+        if k >= 1.0 {
+            return (0.0, 0.0, 0.0, 1.0);
+        }
+        return (
+            (1.0 - r - k) / (1.0 - k),
+            (1.0 - g - k) / (1.0 - k),
+            (1.0 - b - k) / (1.0 - k),
+            k,
+        );
+    }
+
+    /// Formats the color as `#rrggbb`, dropping alpha
+    pub fn to_hex(&self) -> String {
+        let channel = |value: f64| (value.max(0.0).min(1.0) * 255.0).round() as u8;
+        return format!(
+            "#{:02x}{:02x}{:02x}",
+            channel(self.r),
+            channel(self.g),
+            channel(self.b)
+        );
+    }
+}
+
+fn parse_hex_digits(digits: &str) -> Option<Vec<f64>> {
+    let expanded: String = if digits.len() == 3 || digits.len() == 4 {
+        digits.chars().flat_map(|c| vec![c, c]).collect()
+    } else {
+        digits.to_string()
+    };
+    if expanded.len() != 6 && expanded.len() != 8 {
+        return None;
+    }
+
+    let mut channels: Vec<f64> = vec![];
+    for i in (0..expanded.len()).step_by(2) {
+        let channel = u8::from_str_radix(expanded.get(i..i + 2)?, 16).ok()?;
+        channels.push(channel as f64 / 255.0);
+    }
+    return Some(channels);
+}
+
+fn parse_function_channels(arguments: &str) -> Option<Vec<f64>> {
+    let mut channels: Vec<f64> = vec![];
+    for (index, part) in arguments
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .enumerate()
+    {
+        let value = if part.ends_with('%') {
+            part[..part.len() - 1].parse::<f64>().ok()? / 100.0
+        } else if index < 3 {
+            part.parse::<f64>().ok()? / 255.0
+        } else {
+            part.parse::<f64>().ok()?
+        };
+        channels.push(value);
+    }
+    return Some(channels);
+}
+
+/// Parses a css color: `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`,
+/// `rgb(...)`, `rgba(...)` or one of a handful of common names.
+/// `none` and `transparent` parse to a fully transparent color.
+pub fn parse_color(color: &str) -> Option<Rgba> {
+    let color = color.trim().to_ascii_lowercase();
+
+    let channels: Vec<f64> = if color.starts_with('#') {
+        parse_hex_digits(&color[1..])?
+    } else if (color.starts_with("rgb(") || color.starts_with("rgba(")) && color.ends_with(')') {
+        let open = color.find('(')?;
+        parse_function_channels(&color[open + 1..color.len() - 1])?
+    } else {
+        match color.as_str() {
+            "none" | "transparent" => vec![0.0, 0.0, 0.0, 0.0],
+            "black" => vec![0.0, 0.0, 0.0],
+            "white" => vec![1.0, 1.0, 1.0],
+            "red" => vec![1.0, 0.0, 0.0],
+            "green" => vec![0.0, 128.0 / 255.0, 0.0],
+            "blue" => vec![0.0, 0.0, 1.0],
+            "yellow" => vec![1.0, 1.0, 0.0],
+            "gray" | "grey" => vec![128.0 / 255.0, 128.0 / 255.0, 128.0 / 255.0],
+            _ => return None,
+        }
+    };
+
+    return match channels.len() {
+        3 => Some(Rgba::new(channels[0], channels[1], channels[2], 1.0)),
+        4 => Some(Rgba::new(
+            channels[0],
+            channels[1],
+            channels[2],
+            channels[3],
+        )),
+        _ => None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{parse_color, Rgba};
+
+    #[test]
+    fn test_parse_colors() {
+        assert_eq!(parse_color("#fff"), Some(Rgba::new(1.0, 1.0, 1.0, 1.0)));
+        assert_eq!(parse_color("#FF000080").unwrap().to_hex(), "#ff0000");
+        assert_eq!(
+            parse_color("rgba(0, 255, 0, 0.5)"),
+            Some(Rgba::new(0.0, 1.0, 0.0, 0.5))
+        );
+        assert_eq!(parse_color("transparent").unwrap().a, 0.0);
+        assert_eq!(parse_color("#12"), None);
+        assert_eq!(parse_color("chartreuse-ish"), None);
+    }
+
+    #[test]
+    fn test_to_cmyk() {
+        let (c, m, y, k) = parse_color("#333333").unwrap().to_cmyk();
+        assert_eq!((c, m, y), (0.0, 0.0, 0.0));
+        assert!((k - 0.8).abs() < 1e-9);
+        assert_eq!(
+            parse_color("#ff0000").unwrap().to_cmyk(),
+            (0.0, 1.0, 1.0, 0.0)
+        );
+    }
+}
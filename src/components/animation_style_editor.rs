@@ -0,0 +1,109 @@
+use crate::components::float_field_set::FloatFieldSet;
+use crate::fig::animation::{AnimationStyle, Easing};
+use yew::{html, Callback, Component, ComponentLink, Html, Renderable, ShouldRender};
+
+pub struct AnimationStyleEditor {
+    pub animation_style: AnimationStyle,
+    pub on_updated: Callback<(AnimationStyle)>,
+}
+
+#[derive(Default, PartialEq, Clone)]
+pub struct AnimationStyleEditorProps {
+    pub animation_style: AnimationStyle,
+    // TODO I'm only wrapping this in option because Callback
+    // doesn't derive Default, but Option<Callback> does.
+    pub on_updated: Option<Callback<(AnimationStyle)>>,
+}
+
+pub enum AnimationStyleEditorMsg {
+    UpdateDelay(f64),
+    UpdateDuration(f64),
+    UseEasing(Easing),
+}
+
+impl Component for AnimationStyleEditor {
+    type Message = AnimationStyleEditorMsg;
+    type Properties = AnimationStyleEditorProps;
+
+    fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
+        AnimationStyleEditor {
+            animation_style: props.animation_style,
+            on_updated: match props.on_updated {
+                Some(x) => x,
+                None => panic!("on_updated must be specified"),
+            },
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let mut new_style = self.animation_style.clone();
+        match msg {
+            AnimationStyleEditorMsg::UpdateDelay(delay) => new_style.delay_s = delay,
+            AnimationStyleEditorMsg::UpdateDuration(duration) => new_style.duration_s = duration,
+            AnimationStyleEditorMsg::UseEasing(easing) => new_style.easing = easing,
+        };
+        self.on_updated.emit(new_style);
+
+        false // update given in onChange in parent state
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let should_render = props.animation_style != self.animation_style;
+        self.animation_style = props.animation_style;
+        self.on_updated = match props.on_updated {
+            Some(x) => x,
+            None => panic!("on_updated must be specified"),
+        };
+
+        return should_render;
+    }
+}
+
+impl Renderable<AnimationStyleEditor> for AnimationStyleEditor {
+    fn view(&self) -> Html<Self> {
+        let easing_options = vec![
+            (Easing::Linear, "Linear"),
+            (Easing::EaseIn, "Ease In"),
+            (Easing::EaseOut, "Ease Out"),
+            (Easing::EaseInOut, "Ease In Out"),
+        ]
+        .into_iter()
+        .map(|(easing, label)| {
+            let checked = easing == self.animation_style.easing;
+            html! {
+                <label>
+                    <input
+                        type="radio",
+                        checked={checked},
+                        onclick=move |_| AnimationStyleEditorMsg::UseEasing(easing.clone()),
+                        />
+                    {label}
+                </label>
+            }
+        });
+
+        return html! {
+            <form class="animation-style-editor fieldset",>
+                <span class="fields",>
+                    <FloatFieldSet:
+                        human_name="Delay (s)",
+                        input_name="animation_delay",
+                        value={self.animation_style.delay_s},
+                        max={10.0},
+                        on_input=|new_val| AnimationStyleEditorMsg::UpdateDelay(new_val),
+                    />
+                    <FloatFieldSet:
+                        human_name="Seconds per dot",
+                        input_name="animation_duration",
+                        value={self.animation_style.duration_s},
+                        max={2.0},
+                        on_input=|new_val| AnimationStyleEditorMsg::UpdateDuration(new_val),
+                    />
+                    <span class="animation-easing-options",>
+                        {for easing_options}
+                    </span>
+                </span>
+            </form>
+        };
+    }
+}
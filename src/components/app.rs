@@ -1,53 +1,174 @@
+use crate::components::background_style_editor::BackgroundStyleEditor;
+use crate::components::clipboard::listen_for_pasted_text;
+use crate::components::download::download_bytes;
 use crate::components::error_toast::ErrorToast;
+use crate::components::location::{get_location_hash, replace_location_hash};
 use crate::components::svg_view::{svg_data_url, svg_view};
 use crate::components::text_path_style_editor::TextPathStyleEditor;
 use crate::drawing_style::{DrawingColors, DrawingStyle};
-use crate::fig::diagram::Diagram;
-use crate::fig::dot::Dot;
+use crate::fig::animation::{AnimationMode, AnimationStyle};
+use crate::fig::background::{BackgroundFill, BackgroundShape, BackgroundStyle};
+use crate::fig::color_style::ColorStyle;
+use crate::fig::decoration::DecorationStyle;
+use crate::fig::diagram::{AccessibilityOptions, BoundingMode, Diagram};
+use crate::fig::dot::{Dot, DotOrientation, DotShape};
 use crate::fig::text_path::ArcStyle;
 use crate::fig::text_path::{TextPath, TextPathStyle};
-use crate::serializable_app_state::{get_state_from_document_string, DeserializedAppState};
+use crate::history::{EditKind, History};
+use crate::migrations::SCHEMA_VERSION;
+use crate::pdf::render::{PdfColorMode, PdfOptions};
+use crate::plot::gcode::GcodeOptions;
+use crate::plot::toolpath::PlotOptions;
+use crate::presets::{save_preset, PresetKind, PresetLibrary};
+use crate::raster::render::{RasterOptions, RasterSize};
+use crate::serializable_app_state::{
+    get_state_from_config_string, get_state_from_document_bytes, serialize, DeserializedAppState,
+    DocumentError, DocumentFormat, SerializableAppState,
+};
+use crate::svg::output::SvgOutputOptions;
+use crate::url_state::{decode_url_fragment, encode_url_fragment};
+use std::time::Duration;
+use stdweb::unstable::TryInto;
 use stdweb::web::{
     event::{
-        DataTransfer, DataTransferItem, DataTransferItemKind, IDragEvent, IEvent, LoadEndEvent,
+        DataTransfer, DataTransferItem, DataTransferItemKind, IDragEvent, IEvent, IKeyboardEvent,
+        KeyDownEvent,
     },
-    File, FileReader, FileReaderResult, IEventTarget,
+    html_element::{InputElement, TextAreaElement},
+    window, Date, File, IBlob, IEventTarget,
 };
+use yew::services::reader::{FileData, ReaderService, ReaderTask};
+use yew::services::storage::{Area, StorageService};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
 use yew::{
-    html, services::ConsoleService, Component, ComponentLink, Html, Renderable, ShouldRender,
+    html, services::ConsoleService, ChangeData, Component, ComponentLink, Html, Renderable,
+    ShouldRender,
 };
 
-static mut CURRENT_APP_REF: Option<&'static mut App> = None;
+/// localStorage key the current design is autosaved under
+const AUTOSAVE_KEY: &str = "radial-dots.autosave";
+
+/// localStorage key the preset library is kept under
+const PRESETS_KEY: &str = "radial-dots.presets";
+
+/// Quiet time after an edit before it is saved, so dragging a slider
+/// doesn't serialize the design on every step
+const AUTOSAVE_DELAY_MS: u64 = 500;
+
+/// Longest a document read may take. yew's reader never reports a failed
+/// read, so one that hasn't finished by then is reported as failed
+const DOCUMENT_READ_TIMEOUT_MS: u64 = 30_000;
+
+/// Most undo steps kept
+const HISTORY_LIMIT: usize = 100;
+
+/// Where presets are applied to and saved from
+#[derive(Debug, PartialEq, Clone)]
+pub enum PresetTarget {
+    Defaults,
+    Path(usize),
+}
 
 pub struct App {
     style: DrawingStyle,
     diagram: Diagram,
+    output_options: SvgOutputOptions,
+    animation_mode: AnimationMode,
+    raster_options: RasterOptions,
+    pdf_options: PdfOptions,
+    plot_options: PlotOptions,
+    gcode_options: GcodeOptions,
     error_toasts: Vec<ErrorToast>,
     link: ComponentLink<App>,
     console: ConsoleService,
+    storage: StorageService,
+    timeout: TimeoutService,
+    // dropping the task cancels it, so replacing it restarts the delay
+    autosave_task: Option<TimeoutTask>,
+    reader: ReaderService,
+    // dropping the task cancels the read
+    document_read_task: Option<ReaderTask>,
+    // counts reads started, so results of cancelled reads can be told apart
+    document_read_id: usize,
+    document_read_timeout_task: Option<TimeoutTask>,
+    history: History<(Diagram, DrawingStyle)>,
+    presets: PresetLibrary,
+    preset_target: PresetTarget,
+    preset_name: String,
+    // json pasted in to import, kept until it is imported
+    preset_import: String,
 }
 
 pub enum AppMsg {
     UpdateDefaultOneDotStyle(Option<Dot>),
     UpdateDefaultZeroDotStyle(Option<Dot>),
     UpdateDefaultArcStyle(Option<ArcStyle>),
+    UpdateDefaultDecorationStyle(Option<DecorationStyle>),
+    UpdateDefaultColorStyle(Option<ColorStyle>),
+    UpdateDefaultAnimationStyle(Option<AnimationStyle>),
 
     UpdatePathOneDotStyle(usize, Option<Dot>),
     UpdatePathZeroDotStyle(usize, Option<Dot>),
     UpdatePathArcStyle(usize, Option<ArcStyle>),
+    UpdatePathDecorationStyle(usize, Option<DecorationStyle>),
+    UpdatePathColorStyle(usize, Option<ColorStyle>),
+    UpdatePathAnimationStyle(usize, Option<AnimationStyle>),
     InitPathOneDotStyle(usize),
     InitPathZeroDotStyle(usize),
     InitPathArcStyle(usize),
+    InitPathDecorationStyle(usize),
+    InitPathColorStyle(usize),
+    InitPathAnimationStyle(usize),
 
     UpdateBackgroundColor(String),
+    UpdateBackground(BackgroundStyle),
     UpdateStrokeColor(String),
     UpdateDiagramText(String),
+    UpdateBoundingMode(BoundingMode),
+    UpdateAccessibility(Option<AccessibilityOptions>),
+    UpdateOutputOptions(SvgOutputOptions),
+    UpdateAnimationMode(AnimationMode),
+    DownloadAnimatedSvg,
+    UpdateRasterOptions(RasterOptions),
+    DownloadPng,
+    UpdatePdfOptions(PdfOptions),
+    DownloadPdf,
+    UpdatePlotOptions(PlotOptions),
+    UpdateGcodeOptions(GcodeOptions),
+    DownloadDxf,
+    DownloadHpgl,
+    DownloadGcode,
 
     TryDropDocument(DataTransfer),
-    ConsumeDroppedDocument(Result<DeserializedAppState, String>),
+    OpenDocumentFiles(Vec<File>),
+    PasteDocument(String),
+    DocumentRead(usize, DocumentFormat, FileData),
+    CancelDocumentRead,
+    DocumentReadTimedOut(usize),
+    ConsumeDocument(Result<DeserializedAppState, DocumentError>),
 
     DismissErrorToast(usize),
 
+    UpdatePresetTarget(PresetTarget),
+    UpdatePresetName(String),
+    ApplyZeroDotPreset(usize),
+    ApplyOneDotPreset(usize),
+    ApplyArcPreset(usize),
+    ApplyDrawingStylePreset(usize),
+    SaveZeroDotPreset,
+    SaveOneDotPreset,
+    SaveArcPreset,
+    SaveDrawingStylePreset,
+    RemovePreset(PresetKind, usize),
+    UpdatePresetImport(String),
+    ImportPresets,
+    ExportPresets,
+
+    Autosave,
+    ResetToDefaults,
+    Undo,
+    Redo,
+
     // All event handlers are required to return a message.
     //
     // In some cases, we don't want to generate a message and instead want to
@@ -59,69 +180,233 @@ pub enum AppMsg {
     DoNothing,
 }
 
-impl Component for App {
-    type Message = AppMsg;
-    type Properties = ();
+impl AppMsg {
+    /// How the message is recorded in the undo history, or `None` if it
+    /// doesn't change the design
+    fn get_edit_kind(&self) -> Option<EditKind> {
+        let field = |name: &str| Some(EditKind::Coalescing(name.to_string()));
+        let path_field = |name: &str, index: &usize| field(&format!("paths[{}].{}", index, name));
+        return match self {
+            // removing an override is a single click, even though it shares
+            // a message with the override's editor
+            AppMsg::UpdatePathOneDotStyle(_, None)
+            | AppMsg::UpdatePathZeroDotStyle(_, None)
+            | AppMsg::UpdatePathArcStyle(_, None)
+            | AppMsg::UpdatePathDecorationStyle(_, None)
+            | AppMsg::UpdatePathColorStyle(_, None)
+            | AppMsg::UpdatePathAnimationStyle(_, None) => Some(EditKind::Single),
 
-    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-        App {
-            style: DrawingStyle {
-                color: DrawingColors {
-                    stroke_color: "#333333".to_string(),
-                    background_color: "#EEEEEE".to_string(),
-                },
-                default_zero_dot_style: Dot {
-                    circle_radius: 1.0,
-                    ring_radius: 2.0,
-                    ring_stroke_width: 0.1,
-                },
-                default_one_dot_style: Dot {
-                    circle_radius: 0.5,
-                    ring_radius: 2.0,
-                    ring_stroke_width: 0.0,
+            AppMsg::UpdateDefaultOneDotStyle(_) => field("default_one_dot_style"),
+            AppMsg::UpdateDefaultZeroDotStyle(_) => field("default_zero_dot_style"),
+            AppMsg::UpdateDefaultArcStyle(_) => field("default_arc_style"),
+            AppMsg::UpdateDefaultDecorationStyle(_) => field("default_decoration_style"),
+            AppMsg::UpdateDefaultColorStyle(_) => field("default_color_style"),
+            AppMsg::UpdateDefaultAnimationStyle(_) => field("default_animation_style"),
+            AppMsg::UpdatePathOneDotStyle(index, _) => path_field("one_dot_style", index),
+            AppMsg::UpdatePathZeroDotStyle(index, _) => path_field("zero_dot_style", index),
+            AppMsg::UpdatePathArcStyle(index, _) => path_field("arc_style", index),
+            AppMsg::UpdatePathDecorationStyle(index, _) => path_field("decoration_style", index),
+            AppMsg::UpdatePathColorStyle(index, _) => path_field("color_style", index),
+            AppMsg::UpdatePathAnimationStyle(index, _) => path_field("animation_style", index),
+            AppMsg::UpdateBackgroundColor(_) => field("background_color"),
+            AppMsg::UpdateBackground(_) => field("background"),
+            AppMsg::UpdateStrokeColor(_) => field("stroke_color"),
+            AppMsg::UpdateDiagramText(_) => field("text"),
+            AppMsg::UpdateAccessibility(_) => field("accessibility"),
+
+            AppMsg::InitPathOneDotStyle(_)
+            | AppMsg::InitPathZeroDotStyle(_)
+            | AppMsg::InitPathArcStyle(_)
+            | AppMsg::InitPathDecorationStyle(_)
+            | AppMsg::InitPathColorStyle(_)
+            | AppMsg::InitPathAnimationStyle(_)
+            | AppMsg::UpdateBoundingMode(_)
+            | AppMsg::ConsumeDocument(Ok(_))
+            | AppMsg::ApplyZeroDotPreset(_)
+            | AppMsg::ApplyOneDotPreset(_)
+            | AppMsg::ApplyArcPreset(_)
+            | AppMsg::ApplyDrawingStylePreset(_)
+            | AppMsg::ResetToDefaults => Some(EditKind::Single),
+
+            _ => None,
+        };
+    }
+}
+
+/// Parses a size or rate typed into a field. None unless it is a finite
+/// number above 0
+fn parse_positive(text: &str) -> Option<f64> {
+    return match text.parse::<f64>() {
+        Ok(number) if number.is_finite() && number > 0.0 => Some(number),
+        _ => None,
+    };
+}
+
+/// Whether the key was pressed in a text field, which has its own undo
+fn is_in_text_field(e: &KeyDownEvent) -> bool {
+    return match e.target() {
+        Some(target) => {
+            let input: Result<InputElement, _> = target.clone().try_into();
+            let textarea: Result<TextAreaElement, _> = target.try_into();
+            input.is_ok() || textarea.is_ok()
+        }
+        None => false,
+    };
+}
+
+/// Sends undo and redo for ctrl+z, ctrl+shift+z and ctrl+y, or cmd on macs.
+/// Text fields keep the browser's own undo
+fn listen_for_history_shortcuts(link: &mut ComponentLink<App>) {
+    let undo = link.send_back(|_: ()| AppMsg::Undo);
+    let redo = link.send_back(|_: ()| AppMsg::Redo);
+    window().add_event_listener(move |e: KeyDownEvent| {
+        if !(e.ctrl_key() || e.meta_key()) || is_in_text_field(&e) {
+            return;
+        }
+        match (e.key().to_lowercase().as_str(), e.shift_key()) {
+            ("z", false) => {
+                e.prevent_default();
+                undo.emit(());
+            }
+            ("z", true) | ("y", _) => {
+                e.prevent_default();
+                redo.emit(());
+            }
+            _ => {}
+        }
+    });
+}
+
+fn get_document_format(file: &File) -> Option<DocumentFormat> {
+    let mime_type = file.mime().unwrap_or_default();
+    return DocumentFormat::from_file(&mime_type, &file.name());
+}
+
+/// Style the app starts with when there's nothing saved to restore
+fn get_default_style() -> DrawingStyle {
+    return DrawingStyle {
+        color: DrawingColors {
+            stroke_color: "#333333".to_string(),
+            background_color: "#EEEEEE".to_string(),
+        },
+        default_zero_dot_style: Dot {
+            circle_radius: 1.0,
+            ring_radius: 2.0,
+            ring_stroke_width: 0.1,
+            shape: DotShape::CircleAndRing,
+            orientation: DotOrientation::Upright,
+            rotation_degrees: 0.0,
+        },
+        default_one_dot_style: Dot {
+            circle_radius: 0.5,
+            ring_radius: 2.0,
+            ring_stroke_width: 0.0,
+            shape: DotShape::CircleAndRing,
+            orientation: DotOrientation::Upright,
+            rotation_degrees: 0.0,
+        },
+        default_arc_style: ArcStyle {
+            radius: 5.0,
+            arc_percentage: 1.0,
+            arc_offset_percentage: 0.0,
+        },
+        default_decoration_style: DecorationStyle::default(),
+        default_color_style: ColorStyle::default(),
+        background: BackgroundStyle::default(),
+        default_animation_style: AnimationStyle::default(),
+    };
+}
+
+fn get_default_diagram() -> Diagram {
+    return Diagram {
+        diagram_padding: 5.0,
+        bounding_mode: BoundingMode::Tight,
+        accessibility: Some(AccessibilityOptions::default()),
+        paths: vec![
+            TextPath {
+                text: "he".to_string(),
+                style: TextPathStyle {
+                    zero_dot_style: None,
+                    one_dot_style: None,
+                    arc_style: None,
+                    decoration_style: None,
+                    color_style: None,
+                    animation_style: None,
                 },
-                default_arc_style: ArcStyle {
-                    radius: 5.0,
-                    arc_percentage: 1.0,
-                    arc_offset_percentage: 0.0,
+            },
+            TextPath {
+                text: "ll".to_string(),
+                style: TextPathStyle {
+                    zero_dot_style: None,
+                    one_dot_style: None,
+                    arc_style: None,
+                    decoration_style: None,
+                    color_style: None,
+                    animation_style: None,
                 },
             },
-            diagram: Diagram {
-                diagram_padding: 5.0,
-                paths: vec![
-                    TextPath {
-                        text: "he".to_string(),
-                        style: TextPathStyle {
-                            zero_dot_style: None,
-                            one_dot_style: None,
-                            arc_style: None,
-                        },
-                    },
-                    TextPath {
-                        text: "ll".to_string(),
-                        style: TextPathStyle {
-                            zero_dot_style: None,
-                            one_dot_style: None,
-                            arc_style: None,
-                        },
-                    },
-                    TextPath {
-                        text: "o".to_string(),
-                        style: TextPathStyle {
-                            zero_dot_style: None,
-                            one_dot_style: None,
-                            arc_style: None,
-                        },
-                    },
-                ],
+            TextPath {
+                text: "o".to_string(),
+                style: TextPathStyle {
+                    zero_dot_style: None,
+                    one_dot_style: None,
+                    arc_style: None,
+                    decoration_style: None,
+                    color_style: None,
+                    animation_style: None,
+                },
             },
+        ],
+    };
+}
+
+impl Component for App {
+    type Message = AppMsg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
+        listen_for_history_shortcuts(&mut link);
+        listen_for_pasted_text(link.send_back(AppMsg::PasteDocument));
+        let mut app = App {
+            style: get_default_style(),
+            diagram: get_default_diagram(),
+            output_options: SvgOutputOptions::default(),
+            animation_mode: AnimationMode::Smil,
+            raster_options: RasterOptions::default(),
+            pdf_options: PdfOptions::default(),
+            plot_options: PlotOptions::default(),
+            gcode_options: GcodeOptions::default(),
             error_toasts: Vec::new(),
             link: link,
             console: ConsoleService::new(),
-        }
+            storage: StorageService::new(Area::Local),
+            timeout: TimeoutService::new(),
+            autosave_task: None,
+            reader: ReaderService::new(),
+            document_read_task: None,
+            document_read_id: 0,
+            document_read_timeout_task: None,
+            history: History::new(HISTORY_LIMIT),
+            presets: PresetLibrary::built_in(),
+            preset_target: PresetTarget::Defaults,
+            preset_name: String::new(),
+            preset_import: String::new(),
+        };
+        app.restore_presets();
+        // a shared link wins over whatever was being worked on last
+        app.restore_from_storage();
+        app.restore_from_location_hash();
+        return app;
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        if let Some(kind) = msg.get_edit_kind() {
+            self.history.record(
+                (self.diagram.clone(), self.style.clone()),
+                kind,
+                Date::now(),
+            );
+        }
         match msg {
             AppMsg::UpdateDefaultOneDotStyle(new_style) => match new_style {
                 Some(x) => self.style.default_one_dot_style = x,
@@ -135,6 +420,18 @@ impl Component for App {
                 Some(x) => self.style.default_arc_style = x,
                 None => panic!("default arc style should not be None"),
             },
+            AppMsg::UpdateDefaultDecorationStyle(new_style) => match new_style {
+                Some(x) => self.style.default_decoration_style = x,
+                None => panic!("default decoration style should not be None"),
+            },
+            AppMsg::UpdateDefaultColorStyle(new_style) => match new_style {
+                Some(x) => self.style.default_color_style = x,
+                None => panic!("default color style should not be None"),
+            },
+            AppMsg::UpdateDefaultAnimationStyle(new_style) => match new_style {
+                Some(x) => self.style.default_animation_style = x,
+                None => panic!("default animation style should not be None"),
+            },
 
             AppMsg::UpdatePathOneDotStyle(index, new_style) => {
                 self.diagram.paths[index].style.one_dot_style = new_style;
@@ -145,6 +442,15 @@ impl Component for App {
             AppMsg::UpdatePathArcStyle(index, new_style) => {
                 self.diagram.paths[index].style.arc_style = new_style;
             }
+            AppMsg::UpdatePathDecorationStyle(index, new_style) => {
+                self.diagram.paths[index].style.decoration_style = new_style;
+            }
+            AppMsg::UpdatePathColorStyle(index, new_style) => {
+                self.diagram.paths[index].style.color_style = new_style;
+            }
+            AppMsg::UpdatePathAnimationStyle(index, new_style) => {
+                self.diagram.paths[index].style.animation_style = new_style;
+            }
             AppMsg::InitPathOneDotStyle(index) => {
                 self.diagram.paths[index].style.one_dot_style =
                     Some(self.style.default_one_dot_style.clone())
@@ -157,10 +463,25 @@ impl Component for App {
                 self.diagram.paths[index].style.arc_style =
                     Some(self.style.default_arc_style.clone())
             }
+            AppMsg::InitPathDecorationStyle(index) => {
+                self.diagram.paths[index].style.decoration_style =
+                    Some(self.style.default_decoration_style.clone())
+            }
+            AppMsg::InitPathColorStyle(index) => {
+                self.diagram.paths[index].style.color_style =
+                    Some(self.style.default_color_style.clone())
+            }
+            AppMsg::InitPathAnimationStyle(index) => {
+                self.diagram.paths[index].style.animation_style =
+                    Some(self.style.default_animation_style.clone())
+            }
 
             AppMsg::UpdateBackgroundColor(new_color) => {
                 self.style.color.background_color = new_color;
             }
+            AppMsg::UpdateBackground(new_background) => {
+                self.style.background = new_background;
+            }
             AppMsg::UpdateStrokeColor(new_color) => {
                 self.style.color.stroke_color = new_color;
             }
@@ -170,145 +491,169 @@ impl Component for App {
                     if self.diagram.paths.len() > i {
                         new_text_paths.push(TextPath {
                             text: line.to_string(),
-                            style: TextPathStyle {
-                                zero_dot_style: match &self.diagram.paths[i].style.zero_dot_style {
-                                    Some(x) => Some(x.clone()),
-                                    None => None,
-                                },
-                                one_dot_style: match &self.diagram.paths[i].style.one_dot_style {
-                                    Some(x) => Some(x.clone()),
-                                    None => None,
-                                },
-                                arc_style: match &self.diagram.paths[i].style.arc_style {
-                                    Some(x) => Some(x.clone()),
-                                    None => None,
-                                },
-                            },
+                            style: self.diagram.paths[i].style.clone(),
                         })
                     } else {
                         new_text_paths.push(TextPath {
                             text: line.to_string(),
-                            style: TextPathStyle {
-                                zero_dot_style: None,
-                                one_dot_style: None,
-                                arc_style: None,
-                            },
+                            style: TextPathStyle::default(),
                         })
                     }
                 }
                 self.diagram.paths = new_text_paths;
             }
+            AppMsg::UpdateBoundingMode(bounding_mode) => {
+                self.diagram.bounding_mode = bounding_mode;
+            }
+            AppMsg::UpdateAccessibility(accessibility) => {
+                self.diagram.accessibility = accessibility;
+            }
+            AppMsg::UpdateOutputOptions(output_options) => {
+                self.output_options = output_options;
+            }
+            AppMsg::UpdateAnimationMode(animation_mode) => {
+                self.animation_mode = animation_mode;
+            }
+            AppMsg::DownloadAnimatedSvg => {
+                let svg = self.output_options.render(
+                    &self
+                        .diagram
+                        .as_animated_svg(&self.style, &self.animation_mode),
+                );
+                download_bytes(svg.as_bytes(), "image/svg+xml", "radial-dots-animated.svg");
+                return false;
+            }
+            AppMsg::UpdateRasterOptions(raster_options) => {
+                self.raster_options = raster_options;
+            }
+            AppMsg::DownloadPng => {
+                // rasterizing is slow enough that it only happens on request,
+                // rather than on every render like the svg link
+                let png = self.diagram.as_png(&self.style, &self.raster_options);
+                download_bytes(&png, "image/png", "radial-dots.png");
+                return false;
+            }
+            AppMsg::UpdatePdfOptions(pdf_options) => {
+                self.pdf_options = pdf_options;
+            }
+            AppMsg::DownloadPdf => {
+                let pdf = self.diagram.as_pdf(&self.style, &self.pdf_options);
+                download_bytes(&pdf, "application/pdf", "radial-dots.pdf");
+                return false;
+            }
+            AppMsg::UpdatePlotOptions(plot_options) => {
+                self.plot_options = plot_options;
+            }
+            AppMsg::UpdateGcodeOptions(gcode_options) => {
+                self.gcode_options = gcode_options;
+            }
+            AppMsg::DownloadDxf => {
+                let dxf = self.diagram.as_dxf(&self.style, &self.plot_options);
+                download_bytes(dxf.as_bytes(), "application/dxf", "radial-dots.dxf");
+                return false;
+            }
+            AppMsg::DownloadHpgl => {
+                let hpgl = self.diagram.as_hpgl(&self.style, &self.plot_options);
+                download_bytes(
+                    hpgl.as_bytes(),
+                    "application/vnd.hp-hpgl",
+                    "radial-dots.hpgl",
+                );
+                return false;
+            }
+            AppMsg::DownloadGcode => {
+                let gcode =
+                    self.diagram
+                        .as_gcode(&self.style, &self.plot_options, &self.gcode_options);
+                download_bytes(gcode.as_bytes(), "text/plain", "radial-dots.gcode");
+                return false;
+            }
             AppMsg::TryDropDocument(data_transfer) => {
                 self.console.log("TryDropDocument");
-                if data_transfer.items().len() != 1 {
-                    self.console.log("length not right");
-                    self.error_toasts.push(ErrorToast {
-                        title: String::from("Error in Drag/Drop"),
-                        body: format!(
-                            "Found {num} Data Transfer Items, expected 1",
-                            num = data_transfer.items().len()
-                        ),
-                    });
-                    return true;
-                }
-
-                let transfer_item: DataTransferItem = data_transfer.items().index(0).unwrap();
-
-                if transfer_item.ty() != "image/svg+xml" {
-                    self.error_toasts.push(ErrorToast {
-                        title: String::from("Error in Drag/Drop"),
-                        body: format!("Dropped document had wrong mimetype ({mime}), expected \"image/svg+xml\"", mime = transfer_item.ty()),
-                    });
-
-                    return true;
+                let items = data_transfer.items();
+                let mut files: Vec<File> = vec![];
+                for index in 0..items.len() {
+                    let item: DataTransferItem = items.index(index).unwrap();
+                    if item.kind() != DataTransferItemKind::File {
+                        continue;
+                    }
+                    if let Some(file) = item.get_as_file() {
+                        files.push(file);
+                    }
                 }
-
-                if transfer_item.kind() != DataTransferItemKind::File {
+                if files.is_empty() {
                     self.error_toasts.push(ErrorToast {
                         title: String::from("Error in Drag/Drop"),
-                        body: format!("Dropped item was not a file"),
+                        body: String::from("Dropped item was not a file"),
                     });
-
                     return true;
                 }
-
-                let file_option: Option<File> = transfer_item.get_as_file();
-                let file_blob: File = match file_option {
-                    Some(f) => f,
-                    None => {
-                        self.error_toasts.push(ErrorToast {
-                            title: String::from("Error in Drag/Drop"),
-                            body: String::from("Failed to unpack document"),
-                        });
-                        return true;
-                    }
-                };
-
-                let reader: FileReader = FileReader::new();
-                match reader.read_as_text(&file_blob) {
-                    Ok(_) => {}
-                    Err(_) => {
-                        self.error_toasts.push(ErrorToast {
-                            title: String::from("Error in Drag/Drop"),
-                            body: String::from("Failed to read document body"),
-                        });
-                        return true;
-                    }
+                return self.open_document_files(files);
+            }
+            AppMsg::OpenDocumentFiles(files) => {
+                return self.open_document_files(files);
+            }
+            AppMsg::DocumentRead(read_id, format, data) => {
+                // a read that was cancelled or replaced may still finish
+                if read_id != self.document_read_id {
+                    return false;
                 }
-
-                unsafe {
-                    let self_as_static: &'static mut App = std::mem::transmute(self);
-                    CURRENT_APP_REF = Some(self_as_static);
+                self.document_read_task = None;
+                self.document_read_timeout_task = None;
+                self.link
+                    .send_self(AppMsg::ConsumeDocument(get_state_from_document_bytes(
+                        &data.content,
+                        &format,
+                    )));
+                return true;
+            }
+            AppMsg::CancelDocumentRead => {
+                self.document_read_task = None;
+                self.document_read_timeout_task = None;
+                self.document_read_id += 1;
+                return true;
+            }
+            AppMsg::DocumentReadTimedOut(read_id) => {
+                if read_id != self.document_read_id {
+                    return false;
                 }
-
-                let reader_clone = reader.clone();
-                let reader_callback = move |_: LoadEndEvent| unsafe {
-                    let app_ref: &'static mut App = match &mut CURRENT_APP_REF {
-                        Some(x) => x,
-                        None => return,
-                    };
-
-                    let body_string: String = match reader.result() {
-                        Some(res) => match res {
-                            FileReaderResult::String(s) => s,
-                            FileReaderResult::ArrayBuffer(_) => {
-                                app_ref.link.send_self(AppMsg::ConsumeDroppedDocument(Err(
-                                    String::from(
-                                        "Got ArrayBuffer from FileReader. Expected String.",
-                                    ),
-                                )));
-                                return;
-                            }
-                        },
-                        None => {
-                            app_ref.link.send_self(AppMsg::ConsumeDroppedDocument(Err(
-                                String::from("Failed to get document body from reader body"),
-                            )));
-                            return;
-                        }
-                    };
-
-                    let maybe_state = get_state_from_document_string(&body_string);
-                    app_ref
-                        .link
-                        .send_self(AppMsg::ConsumeDroppedDocument(maybe_state));
+                self.document_read_task = None;
+                self.document_read_timeout_task = None;
+                self.document_read_id += 1;
+                self.link
+                    .send_self(AppMsg::ConsumeDocument(Err(DocumentError::Read(
+                        String::from("the file could not be read in time"),
+                    ))));
+                return true;
+            }
+            AppMsg::PasteDocument(text) => {
+                // pasting anything else is probably not meant as an import,
+                // so it isn't reported as a broken document
+                let format = if text.contains("<svg") {
+                    DocumentFormat::Svg
+                } else if text.trim_start().starts_with('{') {
+                    DocumentFormat::Json
+                } else {
+                    return false;
                 };
-
-                reader_clone.add_event_listener(reader_callback);
-
+                self.link
+                    .send_self(AppMsg::ConsumeDocument(get_state_from_document_bytes(
+                        text.as_bytes(),
+                        &format,
+                    )));
                 return false;
             }
-            AppMsg::ConsumeDroppedDocument(maybe_doc) => match maybe_doc {
+            AppMsg::ConsumeDocument(maybe_doc) => match maybe_doc {
                 Ok(doc) => {
-                    self.console.log("Consume Drop Document");
+                    self.console.log("Consume Document");
                     self.diagram = doc.diagram;
                     self.style = doc.style;
                 }
-                Err(err_message) => {
-                    self.console.log("Fail to consume dropped document");
+                Err(err) => {
+                    self.console.log("Fail to consume document");
                     self.error_toasts.push(ErrorToast {
-                        title: String::from("Error Parsing dropped document"),
-                        body: err_message,
+                        title: String::from("Error opening document"),
+                        body: err.to_string(),
                     });
                 }
             },
@@ -317,17 +662,150 @@ impl Component for App {
                     self.error_toasts.remove(idx);
                 }
             }
+            AppMsg::UpdatePresetTarget(target) => {
+                self.preset_target = target;
+                return true;
+            }
+            AppMsg::UpdatePresetName(name) => {
+                self.preset_name = name;
+                return true;
+            }
+            AppMsg::ApplyZeroDotPreset(index) => {
+                let dot = self.presets.dots[index].value.clone();
+                match self.get_target_path() {
+                    Some(path) => self.diagram.paths[path].style.zero_dot_style = Some(dot),
+                    None => self.style.default_zero_dot_style = dot,
+                }
+            }
+            AppMsg::ApplyOneDotPreset(index) => {
+                let dot = self.presets.dots[index].value.clone();
+                match self.get_target_path() {
+                    Some(path) => self.diagram.paths[path].style.one_dot_style = Some(dot),
+                    None => self.style.default_one_dot_style = dot,
+                }
+            }
+            AppMsg::ApplyArcPreset(index) => {
+                let arc = self.presets.arcs[index].value.clone();
+                match self.get_target_path() {
+                    Some(path) => self.diagram.paths[path].style.arc_style = Some(arc),
+                    None => self.style.default_arc_style = arc,
+                }
+            }
+            AppMsg::ApplyDrawingStylePreset(index) => {
+                // a drawing style covers the whole diagram, so it replaces
+                // the defaults whatever the target is
+                self.style = self.presets.drawing_styles[index].value.clone();
+            }
+            AppMsg::SaveZeroDotPreset => {
+                let dot = self.get_target_dot(false);
+                save_preset(&mut self.presets.dots, self.preset_name.trim(), dot);
+                self.store_presets();
+                return true;
+            }
+            AppMsg::SaveOneDotPreset => {
+                let dot = self.get_target_dot(true);
+                save_preset(&mut self.presets.dots, self.preset_name.trim(), dot);
+                self.store_presets();
+                return true;
+            }
+            AppMsg::SaveArcPreset => {
+                let arc = self.get_target_arc();
+                save_preset(&mut self.presets.arcs, self.preset_name.trim(), arc);
+                self.store_presets();
+                return true;
+            }
+            AppMsg::SaveDrawingStylePreset => {
+                let style = self.style.clone();
+                save_preset(
+                    &mut self.presets.drawing_styles,
+                    self.preset_name.trim(),
+                    style,
+                );
+                self.store_presets();
+                return true;
+            }
+            AppMsg::RemovePreset(kind, index) => {
+                self.presets.remove(&kind, index);
+                self.store_presets();
+                return true;
+            }
+            AppMsg::UpdatePresetImport(json) => {
+                self.preset_import = json;
+                return true;
+            }
+            AppMsg::ImportPresets => {
+                match self.presets.import(&self.preset_import) {
+                    Ok(_) => {
+                        self.preset_import = String::new();
+                        self.store_presets();
+                    }
+                    Err(message) => self.error_toasts.push(ErrorToast {
+                        title: String::from("Error importing presets"),
+                        body: message,
+                    }),
+                }
+                return true;
+            }
+            AppMsg::ExportPresets => {
+                let json = self.presets.export();
+                download_bytes(
+                    json.as_bytes(),
+                    "application/json",
+                    "radial-dots-presets.json",
+                );
+                return false;
+            }
+            AppMsg::Autosave => {
+                self.autosave_task = None;
+                self.storage
+                    .store(AUTOSAVE_KEY, Ok(serialize(&self.get_app_state())));
+                self.update_location_hash();
+                return false;
+            }
+            AppMsg::ResetToDefaults => {
+                self.diagram = get_default_diagram();
+                self.style = get_default_style();
+            }
+            AppMsg::Undo => match self
+                .history
+                .undo((self.diagram.clone(), self.style.clone()))
+            {
+                Some((diagram, style)) => {
+                    self.diagram = diagram;
+                    self.style = style;
+                }
+                None => return false,
+            },
+            AppMsg::Redo => match self
+                .history
+                .redo((self.diagram.clone(), self.style.clone()))
+            {
+                Some((diagram, style)) => {
+                    self.diagram = diagram;
+                    self.style = style;
+                }
+                None => return false,
+            },
             AppMsg::DoNothing => {
                 return false;
             }
         }
+        self.schedule_autosave();
         true
     }
 }
 
 impl Renderable<App> for App {
     fn view(&self) -> Html<Self> {
-        let background_style = format!("background-color: {}", self.style.color.background_color);
+        // the page only takes the background color when the drawing's
+        // background would fill it edge to edge anyway
+        let background_style = if self.style.background.fill == BackgroundFill::Solid
+            && self.style.background.shape == BackgroundShape::Rectangle
+        {
+            format!("background-color: {}", self.style.color.background_color)
+        } else {
+            String::new()
+        };
 
         let path_styles = self.diagram.paths.iter().enumerate().map(|(index, path)| {
             html! {
@@ -337,10 +815,16 @@ impl Renderable<App> for App {
                     on_zero_dot_updated=move |dot| AppMsg::UpdatePathZeroDotStyle(index, dot),
                     on_one_dot_updated=move |dot| AppMsg::UpdatePathOneDotStyle(index, dot),
                     on_arc_style_updated=move |arc| AppMsg::UpdatePathArcStyle(index, arc),
+                    on_decoration_style_updated=move |decoration| AppMsg::UpdatePathDecorationStyle(index, decoration),
+                    on_color_style_updated=move |color| AppMsg::UpdatePathColorStyle(index, color),
+                    on_animation_style_updated=move |animation| AppMsg::UpdatePathAnimationStyle(index, animation),
 
                     on_add_one_dot_override=move |_| AppMsg::InitPathOneDotStyle(index),
                     on_add_zero_dot_override=move |_| AppMsg::InitPathZeroDotStyle(index),
                     on_add_arc_style_override=move |_| AppMsg::InitPathArcStyle(index),
+                    on_add_decoration_style_override=move |_| AppMsg::InitPathDecorationStyle(index),
+                    on_add_color_style_override=move |_| AppMsg::InitPathColorStyle(index),
+                    on_add_animation_style_override=move |_| AppMsg::InitPathAnimationStyle(index),
                     can_remove={true},
                     />
             }
@@ -358,7 +842,72 @@ impl Renderable<App> for App {
             }
         });
 
-        let data_href: String = svg_data_url(&self.diagram, &self.style);
+        let data_href: String = svg_data_url(&self.diagram, &self.style, &self.output_options);
+        let toggled_inline_uses = SvgOutputOptions {
+            inline_uses: !self.output_options.inline_uses,
+            ..self.output_options.clone()
+        };
+        let toggled_minify = SvgOutputOptions {
+            minify: !self.output_options.minify,
+            ..self.output_options.clone()
+        };
+        let toggled_pretty = SvgOutputOptions {
+            pretty: !self.output_options.pretty,
+            ..self.output_options.clone()
+        };
+        let current_output_options = self.output_options.clone();
+        let toggled_animation_mode = match self.animation_mode {
+            AnimationMode::Smil => AnimationMode::Css,
+            AnimationMode::Css => AnimationMode::Smil,
+        };
+        let toggled_transparent_background = RasterOptions {
+            transparent_background: !self.raster_options.transparent_background,
+            ..self.raster_options.clone()
+        };
+        let toggled_print_size = RasterOptions {
+            size: match self.raster_options.size {
+                RasterSize::Pixels(_) => RasterSize::Physical {
+                    width_mm: 100.0,
+                    dpi: 300.0,
+                },
+                RasterSize::Physical { .. } => {
+                    RasterSize::Pixels(self.raster_options.get_pixel_width())
+                }
+            },
+            ..self.raster_options.clone()
+        };
+        let raster_size_fields = self.view_raster_size_fields();
+        let toggled_cmyk = PdfOptions {
+            color_mode: match self.pdf_options.color_mode {
+                PdfColorMode::Rgb => PdfColorMode::Cmyk,
+                PdfColorMode::Cmyk => PdfColorMode::Rgb,
+            },
+            ..self.pdf_options.clone()
+        };
+        let current_pdf_options = self.pdf_options.clone();
+        let current_plot_options = self.plot_options.clone();
+        let toggled_hatch_fills = PlotOptions {
+            hatch_spacing_mm: match self.plot_options.hatch_spacing_mm {
+                Some(_) => None,
+                None => PlotOptions::default().hatch_spacing_mm,
+            },
+            ..self.plot_options.clone()
+        };
+        let current_gcode_options = self.gcode_options.clone();
+        let preview_alt = format!(
+            "Radial dots encoding: {}",
+            self.diagram.get_description().replace('\n', " / ")
+        );
+        let toggled_accessibility = match self.diagram.accessibility {
+            Some(_) => None,
+            None => Some(AccessibilityOptions::default()),
+        };
+        let accessibility_fields = self.view_accessibility_fields();
+        let presets = self.view_presets();
+        let next_bounding_mode = match self.diagram.bounding_mode {
+            BoundingMode::Tight => BoundingMode::CenteredSquare,
+            BoundingMode::CenteredSquare => BoundingMode::Tight,
+        };
 
         return html! {
             <>
@@ -371,8 +920,37 @@ impl Renderable<App> for App {
                      ondragover=|e| {e.prevent_default(); AppMsg::DoNothing},
                      ondrop=|e| {e.prevent_default(); AppMsg::TryDropDocument(e.data_transfer().unwrap())},
                      >
-                    {svg_view(&self.diagram, &self.style)}
+                    {svg_view(&self.diagram, &self.style, &preview_alt)}
                     <div class="control-bar",>
+                        <section class="history-toolbar",>
+                            <label class="open-file-button", title="Open an svg, png or json file saved from this app, or paste svg or json text anywhere on the page",>
+                                {"Open…"}
+                                <input
+                                    type="file",
+                                    accept=".svg,.png,.json,image/svg+xml,image/png,application/json",
+                                    multiple="multiple",
+                                    onchange=|data| match data {
+                                        ChangeData::Files(files) => AppMsg::OpenDocumentFiles(files.iter().collect()),
+                                        _ => AppMsg::DoNothing,
+                                    },
+                                    />
+                            </label>
+                            {self.view_document_read_status()}
+                            <button
+                                disabled={!self.history.can_undo()},
+                                title="Undo (Ctrl+Z)",
+                                onclick=|_| AppMsg::Undo,
+                                >
+                                {"↶ Undo"}
+                            </button>
+                            <button
+                                disabled={!self.history.can_redo()},
+                                title="Redo (Ctrl+Shift+Z)",
+                                onclick=|_| AppMsg::Redo,
+                                >
+                                {"↷ Redo"}
+                            </button>
+                        </section>
                         <section class="fields-container",>
                             <textarea
                                 class="control-textarea",
@@ -387,15 +965,95 @@ impl Renderable<App> for App {
                                     one_dot_style: Some(self.style.default_one_dot_style.clone()),
                                     zero_dot_style: Some(self.style.default_zero_dot_style.clone()),
                                     arc_style: Some(self.style.default_arc_style.clone()),
+                                    decoration_style: Some(self.style.default_decoration_style.clone()),
+                                    color_style: Some(self.style.default_color_style.clone()),
+                                    animation_style: Some(self.style.default_animation_style.clone()),
                                 }},
                                 on_zero_dot_updated=|dot| AppMsg::UpdateDefaultZeroDotStyle(dot),
                                 on_one_dot_updated=|dot| AppMsg::UpdateDefaultOneDotStyle(dot),
                                 on_arc_style_updated=|arc| AppMsg::UpdateDefaultArcStyle(arc),
+                                on_decoration_style_updated=|decoration| AppMsg::UpdateDefaultDecorationStyle(decoration),
+                                on_color_style_updated=|color| AppMsg::UpdateDefaultColorStyle(color),
+                                on_animation_style_updated=|animation| AppMsg::UpdateDefaultAnimationStyle(animation),
                                 />
+                            <section class="background-style-section",>
+                                <h2 class="text-path-header",>{"Background"}</h2>
+                                <BackgroundStyleEditor:
+                                    background={self.style.background.clone()},
+                                    background_color={self.style.color.background_color.clone()},
+                                    on_updated=|background| AppMsg::UpdateBackground(background),
+                                    on_background_color_updated=|color| AppMsg::UpdateBackgroundColor(color),
+                                    />
+                            </section>
+                            {presets}
                             <hr class="controls-divider", />
                             {for path_styles}
+                            <button
+                                class="reset-button",
+                                onclick=|_| AppMsg::ResetToDefaults,
+                                >
+                                {"Reset to defaults"}
+                            </button>
                             </section>
                         <section class="download-container",>
+                            <label class="download-option",>
+                                <input
+                                    type="checkbox",
+                                    checked={self.diagram.bounding_mode == BoundingMode::CenteredSquare},
+                                    onclick=move |_| AppMsg::UpdateBoundingMode(next_bounding_mode.clone()),
+                                    />
+                                {"Square canvas"}
+                            </label>
+                            <label class="download-option",>
+                                <input
+                                    type="checkbox",
+                                    checked={self.diagram.accessibility.is_some()},
+                                    onclick=move |_| AppMsg::UpdateAccessibility(toggled_accessibility.clone()),
+                                    />
+                                {"Describe for screen readers"}
+                            </label>
+                            {accessibility_fields}
+                            <label class="download-option",>
+                                <input
+                                    type="checkbox",
+                                    checked={self.output_options.inline_uses},
+                                    onclick=move |_| AppMsg::UpdateOutputOptions(toggled_inline_uses.clone()),
+                                    />
+                                {"Inline dot shapes"}
+                            </label>
+                            <label class="download-option",>
+                                <input
+                                    type="checkbox",
+                                    checked={self.output_options.minify},
+                                    onclick=move |_| AppMsg::UpdateOutputOptions(toggled_minify.clone()),
+                                    />
+                                {"Minify"}
+                            </label>
+                            <label class="download-option",>
+                                <input
+                                    type="checkbox",
+                                    checked={self.output_options.pretty},
+                                    onclick=move |_| AppMsg::UpdateOutputOptions(toggled_pretty.clone()),
+                                    />
+                                {"Pretty print"}
+                            </label>
+                            <label class="download-option",>
+                                {"Decimal places"}
+                                <input
+                                    type="number",
+                                    min="0",
+                                    max="10",
+                                    value={match self.output_options.precision {
+                                        Some(precision) => precision.to_string(),
+                                        None => String::new(),
+                                    }},
+                                    oninput=move |e| AppMsg::UpdateOutputOptions(SvgOutputOptions {
+                                        // an empty field writes numbers at full precision
+                                        precision: e.value.parse().ok(),
+                                        ..current_output_options.clone()
+                                    }),
+                                    />
+                            </label>
                             <a
                                 class="download-button",
                                 download="radial-dots.svg",
@@ -403,6 +1061,119 @@ impl Renderable<App> for App {
                                 >
                                 {"Download"}
                             </a>
+                            <label class="download-option",>
+                                <input
+                                    type="checkbox",
+                                    checked={self.animation_mode == AnimationMode::Css},
+                                    onclick=move |_| AppMsg::UpdateAnimationMode(toggled_animation_mode.clone()),
+                                    />
+                                {"CSS animation"}
+                            </label>
+                            <button
+                                class="download-button",
+                                onclick=|_| AppMsg::DownloadAnimatedSvg,
+                                >
+                                {"Download Animated SVG"}
+                            </button>
+                            <label class="download-option",>
+                                <input
+                                    type="checkbox",
+                                    checked={self.raster_options.transparent_background},
+                                    onclick=move |_| AppMsg::UpdateRasterOptions(toggled_transparent_background.clone()),
+                                    />
+                                {"Transparent PNG"}
+                            </label>
+                            <label class="download-option",>
+                                <input
+                                    type="checkbox",
+                                    checked={self.raster_options.get_dpi().is_some()},
+                                    onclick=move |_| AppMsg::UpdateRasterOptions(toggled_print_size.clone()),
+                                    />
+                                {"Print size"}
+                            </label>
+                            {raster_size_fields}
+                            <button
+                                class="download-button",
+                                onclick=|_| AppMsg::DownloadPng,
+                                >
+                                {"Download PNG"}
+                            </button>
+                            <label class="download-option",>
+                                {"PDF diameter (mm)"}
+                                <input
+                                    type="number",
+                                    min="1",
+                                    value={self.pdf_options.diameter_mm.to_string()},
+                                    oninput=move |e| match parse_positive(&e.value) {
+                                        Some(diameter_mm) => AppMsg::UpdatePdfOptions(PdfOptions {
+                                            diameter_mm: diameter_mm,
+                                            ..current_pdf_options.clone()
+                                        }),
+                                        None => AppMsg::DoNothing,
+                                    },
+                                    />
+                            </label>
+                            <label class="download-option",>
+                                <input
+                                    type="checkbox",
+                                    checked={self.pdf_options.color_mode == PdfColorMode::Cmyk},
+                                    onclick=move |_| AppMsg::UpdatePdfOptions(toggled_cmyk.clone()),
+                                    />
+                                {"CMYK"}
+                            </label>
+                            <button
+                                class="download-button",
+                                onclick=|_| AppMsg::DownloadPdf,
+                                >
+                                {"Download PDF"}
+                            </button>
+                            <label class="download-option",>
+                                {"Plot diameter (mm)"}
+                                <input
+                                    type="number",
+                                    min="1",
+                                    value={self.plot_options.diameter_mm.to_string()},
+                                    oninput=move |e| match parse_positive(&e.value) {
+                                        Some(diameter_mm) => AppMsg::UpdatePlotOptions(PlotOptions {
+                                            diameter_mm: diameter_mm,
+                                            ..current_plot_options.clone()
+                                        }),
+                                        None => AppMsg::DoNothing,
+                                    },
+                                    />
+                            </label>
+                            <label class="download-option",>
+                                <input
+                                    type="checkbox",
+                                    checked={self.plot_options.hatch_spacing_mm.is_some()},
+                                    onclick=move |_| AppMsg::UpdatePlotOptions(toggled_hatch_fills.clone()),
+                                    />
+                                {"Hatch fills"}
+                            </label>
+                            <label class="download-option",>
+                                {"Feed rate (mm/min)"}
+                                <input
+                                    type="number",
+                                    min="1",
+                                    value={self.gcode_options.feed_rate.to_string()},
+                                    oninput=move |e| match parse_positive(&e.value) {
+                                        Some(feed_rate) => AppMsg::UpdateGcodeOptions(GcodeOptions {
+                                            feed_rate: feed_rate,
+                                            ..current_gcode_options.clone()
+                                        }),
+                                        None => AppMsg::DoNothing,
+                                    },
+                                    />
+                            </label>
+                            <button class="download-button", onclick=|_| AppMsg::DownloadDxf,>
+                                {"DXF"}
+                            </button>
+                            <button class="download-button", onclick=|_| AppMsg::DownloadHpgl,>
+                                {"HPGL"}
+                            </button>
+                            <button class="download-button", onclick=|_| AppMsg::DownloadGcode,>
+                                {"G-code"}
+                            </button>
                         </section>
                     </div>
                 </div>
@@ -412,6 +1183,376 @@ impl Renderable<App> for App {
 }
 
 impl App {
+    fn get_app_state(&self) -> SerializableAppState {
+        return SerializableAppState {
+            version: SCHEMA_VERSION,
+            diagram: &self.diagram,
+            style: &self.style,
+        };
+    }
+
+    /// Opens the first svg, png or json file among `files`, reporting
+    /// any others as ignored since only one design can be open at a time
+    fn open_document_files(&mut self, files: Vec<File>) -> ShouldRender {
+        let mut documents: Vec<(File, DocumentFormat)> = vec![];
+        let mut ignored: Vec<String> = vec![];
+        for file in files {
+            match get_document_format(&file) {
+                Some(format) => documents.push((file, format)),
+                None => ignored.push(format!("{} (unsupported file type)", file.name())),
+            }
+        }
+        if documents.is_empty() {
+            self.error_toasts.push(ErrorToast {
+                title: String::from("Error opening document"),
+                body: format!(
+                    "Expected an svg, png or json file. Ignored {}",
+                    ignored.join(", ")
+                ),
+            });
+            return true;
+        }
+
+        let (file, format) = documents.remove(0);
+        ignored.extend(
+            documents
+                .iter()
+                .map(|(other, _)| format!("{} (only one file is opened)", other.name())),
+        );
+        if !ignored.is_empty() {
+            self.error_toasts.push(ErrorToast {
+                title: String::from("Some files were not opened"),
+                body: format!("Opened {}. Ignored {}", file.name(), ignored.join(", ")),
+            });
+        }
+        self.read_document_file(file, format);
+        return true;
+    }
+
+    /// Reads `file` in the background, then sends its state to
+    /// `ConsumeDocument`. Starting a read cancels any read still in flight,
+    /// so only the most recently opened file is loaded. A read that fails
+    /// never calls back, so it is given up on after a timeout
+    fn read_document_file(&mut self, file: File, format: DocumentFormat) {
+        self.document_read_id += 1;
+        let read_id = self.document_read_id;
+        let callback = self
+            .link
+            .send_back(move |data: FileData| AppMsg::DocumentRead(read_id, format.clone(), data));
+        self.document_read_task = Some(self.reader.read_file(file, callback));
+        let timeout_callback = self
+            .link
+            .send_back(move |_| AppMsg::DocumentReadTimedOut(read_id));
+        self.document_read_timeout_task = Some(self.timeout.spawn(
+            Duration::from_millis(DOCUMENT_READ_TIMEOUT_MS),
+            timeout_callback,
+        ));
+    }
+
+    /// Progress of the file being opened, with a button to stop it
+    fn view_document_read_status(&self) -> Html<App> {
+        return match self.document_read_task {
+            Some(_) => html! {
+                <span class="document-read-status",>
+                    {"Opening…"}
+                    <button onclick=|_| AppMsg::CancelDocumentRead,>{"Cancel"}</button>
+                </span>
+            },
+            None => html! { <></> },
+        };
+    }
+
+    /// Loads the design autosaved by the last visit, if there is one
+    fn restore_from_storage(&mut self) {
+        let stored: Result<String, _> = self.storage.restore(AUTOSAVE_KEY);
+        if let Ok(config_string) = stored {
+            match get_state_from_config_string(&config_string) {
+                Ok(state) => {
+                    self.diagram = state.diagram;
+                    self.style = state.style;
+                }
+                Err(err) => self.error_toasts.push(ErrorToast {
+                    title: String::from("Error restoring autosaved design"),
+                    body: err.to_string(),
+                }),
+            }
+        }
+    }
+
+    fn schedule_autosave(&mut self) {
+        let callback = self.link.send_back(|_| AppMsg::Autosave);
+        self.autosave_task = Some(
+            self.timeout
+                .spawn(Duration::from_millis(AUTOSAVE_DELAY_MS), callback),
+        );
+    }
+
+    /// Loads a design shared by link, if the page was opened with one
+    fn restore_from_location_hash(&mut self) {
+        match decode_url_fragment(&get_location_hash()) {
+            Some(Ok(state)) => {
+                self.diagram = state.diagram;
+                self.style = state.style;
+            }
+            Some(Err(err)) => self.error_toasts.push(ErrorToast {
+                title: String::from("Error opening shared link"),
+                body: err.to_string(),
+            }),
+            None => {}
+        }
+    }
+
+    /// Keeps the url pointing at the current design, so it can be shared by
+    /// copying the address bar
+    fn update_location_hash(&self) {
+        replace_location_hash(&encode_url_fragment(&self.get_app_state()));
+    }
+
+    /// Loads the presets saved by the last visit, if there are any
+    fn restore_presets(&mut self) {
+        let stored: Result<String, _> = self.storage.restore(PRESETS_KEY);
+        if let Ok(json) = stored {
+            match serde_json::from_str(&json) {
+                Ok(presets) => self.presets = presets,
+                Err(err) => self.error_toasts.push(ErrorToast {
+                    title: String::from("Error restoring presets"),
+                    body: err.to_string(),
+                }),
+            }
+        }
+    }
+
+    fn store_presets(&mut self) {
+        self.storage.store(PRESETS_KEY, Ok(self.presets.export()));
+    }
+
+    /// Path presets are applied to and saved from, or `None` for the
+    /// defaults. A path that has since been deleted falls back to the
+    /// defaults
+    fn get_target_path(&self) -> Option<usize> {
+        return match self.preset_target {
+            PresetTarget::Path(index) if index < self.diagram.paths.len() => Some(index),
+            _ => None,
+        };
+    }
+
+    /// Zero or one dot style the preset target draws with, which is the
+    /// default unless the target path overrides it
+    fn get_target_dot(&self, is_one: bool) -> Dot {
+        return match (self.get_target_path(), is_one) {
+            (Some(index), false) => self.diagram.paths[index].get_zero_dot_style(&self.style),
+            (Some(index), true) => self.diagram.paths[index].get_one_dot_style(&self.style),
+            (None, false) => &self.style.default_zero_dot_style,
+            (None, true) => &self.style.default_one_dot_style,
+        }
+        .clone();
+    }
+
+    /// Arc style the preset target draws with
+    fn get_target_arc(&self) -> ArcStyle {
+        return match self.get_target_path() {
+            Some(index) => self.diagram.paths[index].get_arc_style(&self.style),
+            None => &self.style.default_arc_style,
+        }
+        .clone();
+    }
+
+    /// The preset library, with buttons to apply presets to the target and
+    /// to save the target's styles as new presets
+    fn view_presets(&self) -> Html<App> {
+        let target_path = self.get_target_path();
+        let path_targets = self.diagram.paths.iter().enumerate().map(|(index, path)| {
+            html! {
+                <label class="preset-target",>
+                    <input
+                        type="radio",
+                        name="preset-target",
+                        checked={target_path == Some(index)},
+                        onclick=move |_| AppMsg::UpdatePresetTarget(PresetTarget::Path(index)),
+                        />
+                    {format!("\"{}\"", path.text)}
+                </label>
+            }
+        });
+        let dots = self.presets.dots.iter().enumerate().map(|(index, preset)| {
+            html! {
+                <li class="preset",>
+                    <span class="preset-name",>{preset.name.clone()}</span>
+                    <button onclick=move |_| AppMsg::ApplyZeroDotPreset(index),>{"as 0"}</button>
+                    <button onclick=move |_| AppMsg::ApplyOneDotPreset(index),>{"as 1"}</button>
+                    <button onclick=move |_| AppMsg::RemovePreset(PresetKind::Dot, index),>{"x"}</button>
+                </li>
+            }
+        });
+        let arcs = self.presets.arcs.iter().enumerate().map(|(index, preset)| {
+            html! {
+                <li class="preset",>
+                    <span class="preset-name",>{preset.name.clone()}</span>
+                    <button onclick=move |_| AppMsg::ApplyArcPreset(index),>{"apply"}</button>
+                    <button onclick=move |_| AppMsg::RemovePreset(PresetKind::Arc, index),>{"x"}</button>
+                </li>
+            }
+        });
+        let drawing_styles = self.presets.drawing_styles.iter().enumerate().map(|(index, preset)| {
+            html! {
+                <li class="preset",>
+                    <span class="preset-name",>{preset.name.clone()}</span>
+                    <button onclick=move |_| AppMsg::ApplyDrawingStylePreset(index),>{"apply"}</button>
+                    <button onclick=move |_| AppMsg::RemovePreset(PresetKind::DrawingStyle, index),>{"x"}</button>
+                </li>
+            }
+        });
+        let can_save = !self.preset_name.trim().is_empty();
+
+        return html! {
+            <section class="preset-section",>
+                <h2 class="text-path-header",>{"Presets"}</h2>
+                <div class="preset-targets",>
+                    <label class="preset-target",>
+                        <input
+                            type="radio",
+                            name="preset-target",
+                            checked={target_path.is_none()},
+                            onclick=|_| AppMsg::UpdatePresetTarget(PresetTarget::Defaults),
+                            />
+                        {"Defaults"}
+                    </label>
+                    {for path_targets}
+                </div>
+                <h3>{"Dots"}</h3>
+                <ul class="preset-list",>{for dots}</ul>
+                <h3>{"Arcs"}</h3>
+                <ul class="preset-list",>{for arcs}</ul>
+                <h3>{"Drawing styles"}</h3>
+                <ul class="preset-list",>{for drawing_styles}</ul>
+                <div class="preset-save",>
+                    <input
+                        type="text",
+                        placeholder="Preset name",
+                        value={&self.preset_name},
+                        oninput=|e| AppMsg::UpdatePresetName(e.value),
+                        />
+                    <button disabled={!can_save}, onclick=|_| AppMsg::SaveZeroDotPreset,>{"Save 0 dot"}</button>
+                    <button disabled={!can_save}, onclick=|_| AppMsg::SaveOneDotPreset,>{"Save 1 dot"}</button>
+                    <button disabled={!can_save}, onclick=|_| AppMsg::SaveArcPreset,>{"Save arc"}</button>
+                    <button disabled={!can_save}, onclick=|_| AppMsg::SaveDrawingStylePreset,>{"Save drawing style"}</button>
+                </div>
+                <div class="preset-library",>
+                    <textarea
+                        placeholder="Paste an exported preset library",
+                        value={&self.preset_import},
+                        oninput=|e| AppMsg::UpdatePresetImport(e.value),
+                        />
+                    <button
+                        disabled={self.preset_import.trim().is_empty()},
+                        onclick=|_| AppMsg::ImportPresets,
+                        >
+                        {"Import"}
+                    </button>
+                    <button onclick=|_| AppMsg::ExportPresets,>{"Export"}</button>
+                </div>
+            </section>
+        };
+    }
+
+    /// Inputs for the title and language screen readers are given
+    fn view_accessibility_fields(&self) -> Html<App> {
+        return match &self.diagram.accessibility {
+            Some(accessibility) => {
+                let options_for_title = accessibility.clone();
+                let options_for_lang = accessibility.clone();
+                html! {
+                    <>
+                        <label class="download-option",>
+                            {"Title"}
+                            <input
+                                type="text",
+                                value={&accessibility.title},
+                                oninput=move |e| AppMsg::UpdateAccessibility(Some(AccessibilityOptions {
+                                    title: e.value,
+                                    ..options_for_title.clone()
+                                })),
+                                />
+                        </label>
+                        <label class="download-option",>
+                            {"Language"}
+                            <input
+                                type="text",
+                                value={&accessibility.lang},
+                                oninput=move |e| AppMsg::UpdateAccessibility(Some(AccessibilityOptions {
+                                    lang: e.value,
+                                    ..options_for_lang.clone()
+                                })),
+                                />
+                        </label>
+                    </>
+                }
+            }
+            None => html! { <></> },
+        };
+    }
+
+    /// Inputs for the png size: a pixel width, or a printed width and dpi
+    fn view_raster_size_fields(&self) -> Html<App> {
+        let current_raster_options = self.raster_options.clone();
+        return match self.raster_options.size {
+            RasterSize::Pixels(width) => html! {
+                <label class="download-option",>
+                    {"PNG width (px)"}
+                    <input
+                        type="number",
+                        min="1",
+                        value={width.to_string()},
+                        oninput=move |e| match e.value.parse() {
+                            Ok(width) => AppMsg::UpdateRasterOptions(RasterOptions {
+                                size: RasterSize::Pixels(width),
+                                ..current_raster_options.clone()
+                            }),
+                            Err(_) => AppMsg::DoNothing,
+                        },
+                        />
+                </label>
+            },
+            RasterSize::Physical { width_mm, dpi } => {
+                let options_for_dpi = self.raster_options.clone();
+                html! {
+                    <>
+                        <label class="download-option",>
+                            {"Width (mm)"}
+                            <input
+                                type="number",
+                                min="1",
+                                value={width_mm.to_string()},
+                                oninput=move |e| match e.value.parse() {
+                                    Ok(width_mm) => AppMsg::UpdateRasterOptions(RasterOptions {
+                                        size: RasterSize::Physical { width_mm: width_mm, dpi: dpi },
+                                        ..current_raster_options.clone()
+                                    }),
+                                    Err(_) => AppMsg::DoNothing,
+                                },
+                                />
+                        </label>
+                        <label class="download-option",>
+                            {"DPI"}
+                            <input
+                                type="number",
+                                min="1",
+                                value={dpi.to_string()},
+                                oninput=move |e| match e.value.parse() {
+                                    Ok(dpi) => AppMsg::UpdateRasterOptions(RasterOptions {
+                                        size: RasterSize::Physical { width_mm: width_mm, dpi: dpi },
+                                        ..options_for_dpi.clone()
+                                    }),
+                                    Err(_) => AppMsg::DoNothing,
+                                },
+                                />
+                        </label>
+                    </>
+                }
+            }
+        };
+    }
+
     fn get_paths_as_multiline_text(paths: &Vec<TextPath>) -> String {
         let lines: Vec<String> = paths.iter().map(|path| path.text.clone()).collect();
         return lines.join("\n");
@@ -101,7 +101,7 @@ impl Renderable<ArcStyleEditor> for ArcStyleEditor {
                 {svg_view(&self.arc_style, &ArcPreviewStyle {
                     color: &"#EEEEEE",
                     radius: 5.0,
-                 })}
+                 }, "Arc preview")}
             </form>
         };
     }
@@ -0,0 +1,250 @@
+use crate::components::float_field_set::FloatFieldSet;
+use crate::fig::background::{BackgroundFill, BackgroundShape, BackgroundStyle};
+use yew::{html, Callback, Component, ComponentLink, Html, Renderable, ShouldRender};
+
+pub struct BackgroundStyleEditor {
+    pub background: BackgroundStyle,
+    pub background_color: String,
+    pub on_updated: Callback<(BackgroundStyle)>,
+    pub on_background_color_updated: Callback<(String)>,
+}
+
+#[derive(Default, PartialEq, Clone)]
+pub struct BackgroundStyleEditorProps {
+    pub background: BackgroundStyle,
+    pub background_color: String,
+    // TODO I'm only wrapping this in option because Callback
+    // doesn't derive Default, but Option<Callback> does.
+    pub on_updated: Option<Callback<(BackgroundStyle)>>,
+    pub on_background_color_updated: Option<Callback<(String)>>,
+}
+
+pub enum BackgroundStyleEditorMsg {
+    UseFill(BackgroundFill),
+    UpdateBackgroundColor(String),
+    UpdateGradientFrom(String),
+    UpdateGradientTo(String),
+    UpdateGradientAngle(f64),
+    UseShape(BackgroundShape),
+    UpdateBorderWidth(f64),
+    UpdateBorderColor(String),
+}
+
+impl Component for BackgroundStyleEditor {
+    type Message = BackgroundStyleEditorMsg;
+    type Properties = BackgroundStyleEditorProps;
+
+    fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
+        BackgroundStyleEditor {
+            background: props.background,
+            background_color: props.background_color,
+            on_updated: match props.on_updated {
+                Some(x) => x,
+                None => panic!("on_updated must be specified"),
+            },
+            on_background_color_updated: match props.on_background_color_updated {
+                Some(x) => x,
+                None => panic!("on_background_color_updated must be specified"),
+            },
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let mut new_background = self.background.clone();
+        match msg {
+            BackgroundStyleEditorMsg::UpdateBackgroundColor(color) => {
+                self.on_background_color_updated.emit(color);
+                return false;
+            }
+            BackgroundStyleEditorMsg::UseFill(fill) => new_background.fill = fill,
+            BackgroundStyleEditorMsg::UpdateGradientFrom(color) => match &mut new_background.fill {
+                BackgroundFill::LinearGradient { from, .. } => *from = color,
+                BackgroundFill::RadialGradient { inner, .. } => *inner = color,
+                _ => {}
+            },
+            BackgroundStyleEditorMsg::UpdateGradientTo(color) => match &mut new_background.fill {
+                BackgroundFill::LinearGradient { to, .. } => *to = color,
+                BackgroundFill::RadialGradient { outer, .. } => *outer = color,
+                _ => {}
+            },
+            BackgroundStyleEditorMsg::UpdateGradientAngle(angle) => {
+                match &mut new_background.fill {
+                    BackgroundFill::LinearGradient { angle_degrees, .. } => *angle_degrees = angle,
+                    _ => {}
+                }
+            }
+            BackgroundStyleEditorMsg::UseShape(shape) => new_background.shape = shape,
+            BackgroundStyleEditorMsg::UpdateBorderWidth(width) => {
+                new_background.border_width = width
+            }
+            BackgroundStyleEditorMsg::UpdateBorderColor(color) => {
+                new_background.border_color = if color.trim().is_empty() {
+                    None
+                } else {
+                    Some(color)
+                }
+            }
+        };
+        self.on_updated.emit(new_background);
+
+        false // update given in onChange in parent state
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let should_render =
+            props.background != self.background || props.background_color != self.background_color;
+        self.background = props.background;
+        self.background_color = props.background_color;
+        self.on_updated = match props.on_updated {
+            Some(x) => x,
+            None => panic!("on_updated must be specified"),
+        };
+        self.on_background_color_updated = match props.on_background_color_updated {
+            Some(x) => x,
+            None => panic!("on_background_color_updated must be specified"),
+        };
+
+        return should_render;
+    }
+}
+
+impl Renderable<BackgroundStyleEditor> for BackgroundStyleEditor {
+    fn view(&self) -> Html<Self> {
+        // switching between gradients keeps the current end colors
+        let (from, to) = match &self.background.fill {
+            BackgroundFill::LinearGradient { from, to, .. } => (from.clone(), to.clone()),
+            BackgroundFill::RadialGradient { inner, outer } => (inner.clone(), outer.clone()),
+            _ => (self.background_color.clone(), String::from("#CCCCCC")),
+        };
+        let (linear_from, linear_to) = (from.clone(), to.clone());
+        let (radial_from, radial_to) = (from.clone(), to.clone());
+
+        let fill_dom = match &self.background.fill {
+            BackgroundFill::Solid => html! {
+                <>
+                    <label>{"Background Color"}</label>
+                    <input
+                        type="text",
+                        value={&self.background_color},
+                        oninput=|e| BackgroundStyleEditorMsg::UpdateBackgroundColor(e.value),
+                        />
+                </>
+            },
+            BackgroundFill::Transparent => html! { <></> },
+            _ => html! {
+                <>
+                    <label>{"Gradient From"}</label>
+                    <input
+                        type="text",
+                        value={from},
+                        oninput=|e| BackgroundStyleEditorMsg::UpdateGradientFrom(e.value),
+                        />
+                    <label>{"Gradient To"}</label>
+                    <input
+                        type="text",
+                        value={to},
+                        oninput=|e| BackgroundStyleEditorMsg::UpdateGradientTo(e.value),
+                        />
+                </>
+            },
+        };
+        let angle_dom = match &self.background.fill {
+            BackgroundFill::LinearGradient { angle_degrees, .. } => html! {
+                <FloatFieldSet:
+                    human_name="Gradient Angle",
+                    input_name="gradient_angle",
+                    value={*angle_degrees},
+                    max={360.0},
+                    on_input=|new_val| BackgroundStyleEditorMsg::UpdateGradientAngle(new_val),
+                />
+            },
+            _ => html! { <></> },
+        };
+        let is_linear = match self.background.fill {
+            BackgroundFill::LinearGradient { .. } => true,
+            _ => false,
+        };
+        let is_radial = match self.background.fill {
+            BackgroundFill::RadialGradient { .. } => true,
+            _ => false,
+        };
+        let toggled_shape = match self.background.shape {
+            BackgroundShape::Disk => BackgroundShape::Rectangle,
+            BackgroundShape::Rectangle => BackgroundShape::Disk,
+        };
+
+        return html! {
+            <form class="background-style-editor fieldset",>
+                <span class="fields",>
+                    <span class="background-fill-options",>
+                        <label>
+                            <input
+                                type="radio",
+                                checked={self.background.fill == BackgroundFill::Solid},
+                                onclick=|_| BackgroundStyleEditorMsg::UseFill(BackgroundFill::Solid),
+                                />
+                            {"Solid"}
+                        </label>
+                        <label>
+                            <input
+                                type="radio",
+                                checked={is_linear},
+                                onclick=move |_| BackgroundStyleEditorMsg::UseFill(BackgroundFill::LinearGradient {
+                                    from: linear_from.clone(),
+                                    to: linear_to.clone(),
+                                    angle_degrees: 90.0,
+                                }),
+                                />
+                            {"Linear Gradient"}
+                        </label>
+                        <label>
+                            <input
+                                type="radio",
+                                checked={is_radial},
+                                onclick=move |_| BackgroundStyleEditorMsg::UseFill(BackgroundFill::RadialGradient {
+                                    inner: radial_from.clone(),
+                                    outer: radial_to.clone(),
+                                }),
+                                />
+                            {"Radial Gradient"}
+                        </label>
+                        <label>
+                            <input
+                                type="radio",
+                                checked={self.background.fill == BackgroundFill::Transparent},
+                                onclick=|_| BackgroundStyleEditorMsg::UseFill(BackgroundFill::Transparent),
+                                />
+                            {"Transparent"}
+                        </label>
+                    </span>
+                    {fill_dom}
+                    {angle_dom}
+                    <label>
+                        <input
+                            type="checkbox",
+                            checked={self.background.shape == BackgroundShape::Disk},
+                            onclick=move |_| BackgroundStyleEditorMsg::UseShape(toggled_shape.clone()),
+                            />
+                        {"Circular background"}
+                    </label>
+                    <FloatFieldSet:
+                        human_name="Border Width",
+                        input_name="background_border_width",
+                        value={self.background.border_width},
+                        max={5.0},
+                        on_input=|new_val| BackgroundStyleEditorMsg::UpdateBorderWidth(new_val),
+                    />
+                    <label>{"Border Color (blank for stroke color)"}</label>
+                    <input
+                        type="text",
+                        value={match &self.background.border_color {
+                            Some(color) => color.clone(),
+                            None => String::new(),
+                        }},
+                        oninput=|e| BackgroundStyleEditorMsg::UpdateBorderColor(e.value),
+                        />
+                </span>
+            </form>
+        };
+    }
+}
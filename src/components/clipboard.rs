@@ -0,0 +1,22 @@
+use stdweb::js;
+use yew::Callback;
+
+/// Sends the text of every paste on the page that doesn't land in a text
+/// field, so pasting into an input still edits the input
+pub fn listen_for_pasted_text(callback: Callback<String>) {
+    let on_paste = move |text: String| callback.emit(text);
+    js! { @(no_return)
+        var on_paste = @{on_paste};
+        window.addEventListener("paste", function(e) {
+            var target = e.target;
+            if (target && (target.tagName === "INPUT" || target.tagName === "TEXTAREA")) {
+                return;
+            }
+            var text = e.clipboardData && e.clipboardData.getData("text/plain");
+            if (text) {
+                e.preventDefault();
+                on_paste(text);
+            }
+        });
+    }
+}
@@ -0,0 +1,200 @@
+use crate::fig::color_style::{ColorStyle, Palette};
+use yew::{html, Callback, Component, ComponentLink, Html, Renderable, ShouldRender};
+
+pub struct ColorStyleEditor {
+    pub color_style: ColorStyle,
+    pub on_updated: Callback<(ColorStyle)>,
+}
+
+#[derive(Default, PartialEq, Clone)]
+pub struct ColorStyleEditorProps {
+    pub color_style: ColorStyle,
+    // TODO I'm only wrapping this in option because Callback
+    // doesn't derive Default, but Option<Callback> does.
+    pub on_updated: Option<Callback<(ColorStyle)>>,
+}
+
+pub enum ColorStyleEditorMsg {
+    UpdateColor(String),
+    UsePalette(Palette),
+    UpdatePaletteFrom(String),
+    UpdatePaletteTo(String),
+    UpdateZeroDotFill(String),
+    UpdateZeroDotStroke(String),
+    UpdateOneDotFill(String),
+    UpdateOneDotStroke(String),
+}
+
+/// Blank fields mean "inherit"
+fn optional_color(color: String) -> Option<String> {
+    if color.trim().is_empty() {
+        return None;
+    }
+    return Some(color);
+}
+
+fn color_field_value(color: &Option<String>) -> String {
+    return match color {
+        Some(color) => color.clone(),
+        None => String::new(),
+    };
+}
+
+impl Component for ColorStyleEditor {
+    type Message = ColorStyleEditorMsg;
+    type Properties = ColorStyleEditorProps;
+
+    fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
+        ColorStyleEditor {
+            color_style: props.color_style,
+            on_updated: match props.on_updated {
+                Some(x) => x,
+                None => panic!("on_updated must be specified"),
+            },
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let mut new_style = self.color_style.clone();
+        match msg {
+            ColorStyleEditorMsg::UpdateColor(color) => new_style.color = optional_color(color),
+            ColorStyleEditorMsg::UsePalette(palette) => new_style.palette = palette,
+            ColorStyleEditorMsg::UpdatePaletteFrom(color) => match &mut new_style.palette {
+                Palette::AlongRing { from, .. } | Palette::ByRing { from, .. } => *from = color,
+                Palette::Solid => {}
+            },
+            ColorStyleEditorMsg::UpdatePaletteTo(color) => match &mut new_style.palette {
+                Palette::AlongRing { to, .. } | Palette::ByRing { to, .. } => *to = color,
+                Palette::Solid => {}
+            },
+            ColorStyleEditorMsg::UpdateZeroDotFill(color) => {
+                new_style.zero_dot.fill = optional_color(color)
+            }
+            ColorStyleEditorMsg::UpdateZeroDotStroke(color) => {
+                new_style.zero_dot.stroke = optional_color(color)
+            }
+            ColorStyleEditorMsg::UpdateOneDotFill(color) => {
+                new_style.one_dot.fill = optional_color(color)
+            }
+            ColorStyleEditorMsg::UpdateOneDotStroke(color) => {
+                new_style.one_dot.stroke = optional_color(color)
+            }
+        };
+        self.on_updated.emit(new_style);
+
+        false // update given in onChange in parent state
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let should_render = props.color_style != self.color_style;
+        self.color_style = props.color_style;
+        self.on_updated = match props.on_updated {
+            Some(x) => x,
+            None => panic!("on_updated must be specified"),
+        };
+
+        return should_render;
+    }
+}
+
+impl Renderable<ColorStyleEditor> for ColorStyleEditor {
+    fn view(&self) -> Html<Self> {
+        // switching palettes keeps the current end colors
+        let (from, to) = match &self.color_style.palette {
+            Palette::Solid => (String::from("#333333"), String::from("#3366cc")),
+            Palette::AlongRing { from, to } | Palette::ByRing { from, to } => {
+                (from.clone(), to.clone())
+            }
+        };
+        let (along_ring_from, along_ring_to) = (from.clone(), to.clone());
+        let (by_ring_from, by_ring_to) = (from.clone(), to.clone());
+
+        let palette_dom = match &self.color_style.palette {
+            Palette::Solid => html! { <></> },
+            _ => html! {
+                <>
+                    <label>{"Palette From"}</label>
+                    <input
+                        type="text",
+                        value={from},
+                        oninput=|e| ColorStyleEditorMsg::UpdatePaletteFrom(e.value),
+                        />
+                    <label>{"Palette To"}</label>
+                    <input
+                        type="text",
+                        value={to},
+                        oninput=|e| ColorStyleEditorMsg::UpdatePaletteTo(e.value),
+                        />
+                </>
+            },
+        };
+
+        return html! {
+            <form class="color-style-editor fieldset",>
+                <span class="fields",>
+                    <label>{"Color (blank for stroke color)"}</label>
+                    <input
+                        type="text",
+                        value={color_field_value(&self.color_style.color)},
+                        oninput=|e| ColorStyleEditorMsg::UpdateColor(e.value),
+                        />
+                    <label>
+                        <input
+                            type="radio",
+                            checked={self.color_style.palette == Palette::Solid},
+                            onclick=|_| ColorStyleEditorMsg::UsePalette(Palette::Solid),
+                            />
+                        {"Solid"}
+                    </label>
+                    <label>
+                        <input
+                            type="radio",
+                            checked={match self.color_style.palette { Palette::AlongRing { .. } => true, _ => false }},
+                            onclick=move |_| ColorStyleEditorMsg::UsePalette(Palette::AlongRing {
+                                from: along_ring_from.clone(),
+                                to: along_ring_to.clone(),
+                            }),
+                            />
+                        {"Gradient Around Ring"}
+                    </label>
+                    <label>
+                        <input
+                            type="radio",
+                            checked={match self.color_style.palette { Palette::ByRing { .. } => true, _ => false }},
+                            onclick=move |_| ColorStyleEditorMsg::UsePalette(Palette::ByRing {
+                                from: by_ring_from.clone(),
+                                to: by_ring_to.clone(),
+                            }),
+                            />
+                        {"Gradient By Ring"}
+                    </label>
+                    {palette_dom}
+                    <label>{"[0] Dot Fill"}</label>
+                    <input
+                        type="text",
+                        value={color_field_value(&self.color_style.zero_dot.fill)},
+                        oninput=|e| ColorStyleEditorMsg::UpdateZeroDotFill(e.value),
+                        />
+                    <label>{"[0] Dot Stroke"}</label>
+                    <input
+                        type="text",
+                        value={color_field_value(&self.color_style.zero_dot.stroke)},
+                        oninput=|e| ColorStyleEditorMsg::UpdateZeroDotStroke(e.value),
+                        />
+                    <label>{"[1] Dot Fill"}</label>
+                    <input
+                        type="text",
+                        value={color_field_value(&self.color_style.one_dot.fill)},
+                        oninput=|e| ColorStyleEditorMsg::UpdateOneDotFill(e.value),
+                        />
+                    <label>{"[1] Dot Stroke"}</label>
+                    <input
+                        type="text",
+                        value={color_field_value(&self.color_style.one_dot.stroke)},
+                        oninput=|e| ColorStyleEditorMsg::UpdateOneDotStroke(e.value),
+                        />
+                </span>
+            </form>
+        };
+    }
+}
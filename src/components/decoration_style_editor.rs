@@ -0,0 +1,277 @@
+use crate::components::float_field_set::FloatFieldSet;
+use crate::fig::decoration::{DecorationStyle, EndCapStyle, GuideArcStyle, SeparatorTickStyle};
+use yew::{html, Callback, Component, ComponentLink, Html, Renderable, ShouldRender};
+
+pub struct DecorationStyleEditor {
+    pub decoration_style: DecorationStyle,
+    pub on_updated: Callback<(DecorationStyle)>,
+}
+
+#[derive(Default, PartialEq, Clone)]
+pub struct DecorationStyleEditorProps {
+    pub decoration_style: DecorationStyle,
+    // TODO I'm only wrapping this in option because Callback
+    // doesn't derive Default, but Option<Callback> does.
+    pub on_updated: Option<Callback<(DecorationStyle)>>,
+}
+
+pub enum DecorationStyleEditorMsg {
+    ToggleGuideArc,
+    UpdateGuideArcStrokeWidth(f64),
+    UpdateGuideArcColor(String),
+    UpdateGuideArcDashPattern(String),
+
+    ToggleSeparatorTicks,
+    UpdateSeparatorTickLength(f64),
+    UpdateSeparatorTickStrokeWidth(f64),
+    UpdateBitsPerSeparator(f64),
+
+    ToggleEndCaps,
+    UpdateEndCapLength(f64),
+    UpdateEndCapStrokeWidth(f64),
+}
+
+impl Component for DecorationStyleEditor {
+    type Message = DecorationStyleEditorMsg;
+    type Properties = DecorationStyleEditorProps;
+
+    fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
+        DecorationStyleEditor {
+            decoration_style: props.decoration_style,
+            on_updated: match props.on_updated {
+                Some(x) => x,
+                None => panic!("on_updated must be specified"),
+            },
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let mut new_style = self.decoration_style.clone();
+        match msg {
+            DecorationStyleEditorMsg::ToggleGuideArc => {
+                new_style.guide_arc = match new_style.guide_arc {
+                    Some(_) => None,
+                    None => Some(GuideArcStyle {
+                        stroke_width: 0.1,
+                        color: None,
+                        dash_pattern: vec![],
+                    }),
+                }
+            }
+            DecorationStyleEditorMsg::UpdateGuideArcStrokeWidth(v) => {
+                if let Some(guide_arc) = &mut new_style.guide_arc {
+                    guide_arc.stroke_width = v;
+                }
+            }
+            DecorationStyleEditorMsg::UpdateGuideArcColor(color) => {
+                if let Some(guide_arc) = &mut new_style.guide_arc {
+                    guide_arc.color = if color.trim().is_empty() {
+                        None
+                    } else {
+                        Some(color)
+                    };
+                }
+            }
+            DecorationStyleEditorMsg::UpdateGuideArcDashPattern(pattern) => {
+                if let Some(guide_arc) = &mut new_style.guide_arc {
+                    // ignore partially typed patterns until they parse
+                    let parsed: Result<Vec<f64>, _> = pattern
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|part| !part.is_empty())
+                        .map(|part| part.parse::<f64>())
+                        .collect();
+                    match parsed {
+                        Ok(dash_pattern) => guide_arc.dash_pattern = dash_pattern,
+                        Err(_) => return false,
+                    }
+                }
+            }
+
+            DecorationStyleEditorMsg::ToggleSeparatorTicks => {
+                new_style.separator_ticks = match new_style.separator_ticks {
+                    Some(_) => None,
+                    None => Some(SeparatorTickStyle {
+                        length: 1.0,
+                        stroke_width: 0.1,
+                        bits_per_separator: 8,
+                    }),
+                }
+            }
+            DecorationStyleEditorMsg::UpdateSeparatorTickLength(v) => {
+                if let Some(ticks) = &mut new_style.separator_ticks {
+                    ticks.length = v;
+                }
+            }
+            DecorationStyleEditorMsg::UpdateSeparatorTickStrokeWidth(v) => {
+                if let Some(ticks) = &mut new_style.separator_ticks {
+                    ticks.stroke_width = v;
+                }
+            }
+            DecorationStyleEditorMsg::UpdateBitsPerSeparator(v) => {
+                if let Some(ticks) = &mut new_style.separator_ticks {
+                    ticks.bits_per_separator = v.round() as usize;
+                }
+            }
+
+            DecorationStyleEditorMsg::ToggleEndCaps => {
+                new_style.end_caps = match new_style.end_caps {
+                    Some(_) => None,
+                    None => Some(EndCapStyle {
+                        length: 2.0,
+                        stroke_width: 0.1,
+                    }),
+                }
+            }
+            DecorationStyleEditorMsg::UpdateEndCapLength(v) => {
+                if let Some(caps) = &mut new_style.end_caps {
+                    caps.length = v;
+                }
+            }
+            DecorationStyleEditorMsg::UpdateEndCapStrokeWidth(v) => {
+                if let Some(caps) = &mut new_style.end_caps {
+                    caps.stroke_width = v;
+                }
+            }
+        };
+        self.on_updated.emit(new_style);
+
+        false // update given in onChange in parent state
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let should_render = props.decoration_style != self.decoration_style;
+        self.decoration_style = props.decoration_style;
+        self.on_updated = match props.on_updated {
+            Some(x) => x,
+            None => panic!("on_updated must be specified"),
+        };
+
+        return should_render;
+    }
+}
+
+impl Renderable<DecorationStyleEditor> for DecorationStyleEditor {
+    fn view(&self) -> Html<Self> {
+        let guide_arc_dom = match &self.decoration_style.guide_arc {
+            Some(guide_arc) => {
+                let dash_pattern: Vec<String> = guide_arc
+                    .dash_pattern
+                    .iter()
+                    .map(|length| length.to_string())
+                    .collect();
+                html! {
+                    <>
+                        <FloatFieldSet:
+                            human_name="Guide Stroke Width",
+                            input_name="guide_arc_stroke_width",
+                            value={guide_arc.stroke_width},
+                            max={5.0},
+                            on_input=|new_val| DecorationStyleEditorMsg::UpdateGuideArcStrokeWidth(new_val),
+                        />
+                        <label>{"Guide Color (blank for stroke color)"}</label>
+                        <input
+                            type="text",
+                            value={match &guide_arc.color {
+                                Some(color) => color.clone(),
+                                None => String::new(),
+                            }},
+                            oninput=|e| DecorationStyleEditorMsg::UpdateGuideArcColor(e.value),
+                            />
+                        <label>{"Guide Dash Pattern"}</label>
+                        <input
+                            type="text",
+                            value={dash_pattern.join(" ")},
+                            oninput=|e| DecorationStyleEditorMsg::UpdateGuideArcDashPattern(e.value),
+                            />
+                    </>
+                }
+            }
+            None => html! { <></> },
+        };
+
+        let separator_ticks_dom = match &self.decoration_style.separator_ticks {
+            Some(ticks) => html! {
+                <>
+                    <FloatFieldSet:
+                        human_name="Separator Length",
+                        input_name="separator_tick_length",
+                        value={ticks.length},
+                        max={10.0},
+                        on_input=|new_val| DecorationStyleEditorMsg::UpdateSeparatorTickLength(new_val),
+                    />
+                    <FloatFieldSet:
+                        human_name="Separator Stroke Width",
+                        input_name="separator_tick_stroke_width",
+                        value={ticks.stroke_width},
+                        max={5.0},
+                        on_input=|new_val| DecorationStyleEditorMsg::UpdateSeparatorTickStrokeWidth(new_val),
+                    />
+                    <FloatFieldSet:
+                        human_name="Dots Between Separators",
+                        input_name="bits_per_separator",
+                        value={ticks.bits_per_separator as f64},
+                        max={32.0},
+                        on_input=|new_val| DecorationStyleEditorMsg::UpdateBitsPerSeparator(new_val),
+                    />
+                </>
+            },
+            None => html! { <></> },
+        };
+
+        let end_caps_dom = match &self.decoration_style.end_caps {
+            Some(caps) => html! {
+                <>
+                    <FloatFieldSet:
+                        human_name="End Cap Length",
+                        input_name="end_cap_length",
+                        value={caps.length},
+                        max={10.0},
+                        on_input=|new_val| DecorationStyleEditorMsg::UpdateEndCapLength(new_val),
+                    />
+                    <FloatFieldSet:
+                        human_name="End Cap Stroke Width",
+                        input_name="end_cap_stroke_width",
+                        value={caps.stroke_width},
+                        max={5.0},
+                        on_input=|new_val| DecorationStyleEditorMsg::UpdateEndCapStrokeWidth(new_val),
+                    />
+                </>
+            },
+            None => html! { <></> },
+        };
+
+        return html! {
+            <form class="decoration-style-editor fieldset",>
+                <span class="fields",>
+                    <label>
+                        <input
+                            type="checkbox",
+                            checked={self.decoration_style.guide_arc.is_some()},
+                            onclick=|_| DecorationStyleEditorMsg::ToggleGuideArc,
+                            />
+                        {"Guide Arc"}
+                    </label>
+                    {guide_arc_dom}
+                    <label>
+                        <input
+                            type="checkbox",
+                            checked={self.decoration_style.separator_ticks.is_some()},
+                            onclick=|_| DecorationStyleEditorMsg::ToggleSeparatorTicks,
+                            />
+                        {"Separator Ticks"}
+                    </label>
+                    {separator_ticks_dom}
+                    <label>
+                        <input
+                            type="checkbox",
+                            checked={self.decoration_style.end_caps.is_some()},
+                            onclick=|_| DecorationStyleEditorMsg::ToggleEndCaps,
+                            />
+                        {"End Caps"}
+                    </label>
+                    {end_caps_dom}
+                </span>
+            </form>
+        };
+    }
+}
@@ -1,7 +1,7 @@
 use crate::components::float_field_set::FloatFieldSet;
 use crate::components::svg_view::svg_view;
 use crate::drawing_style::DrawingColors;
-use crate::fig::dot::Dot;
+use crate::fig::dot::{Dot, DotOrientation, DotShape, TickDirection};
 use yew::{html, Callback, Component, ComponentLink, Html, Renderable, ShouldRender};
 
 pub struct DotEditor {
@@ -23,6 +23,75 @@ pub enum DotEditorMsg {
     CircleRadiusChange(f64),
     RingRadiusChange(f64),
     RingStrokeWidthChange(f64),
+    UseShape(DotShape),
+    ShapeRadiusChange(f64),
+    ShapeStrokeWidthChange(f64),
+    /// Sides of a polygon or points of a star
+    ShapeCornersChange(f64),
+    StarInnerRadiusChange(f64),
+    TickDirectionChange(TickDirection),
+    TickLengthChange(f64),
+    CustomSvgChange(String),
+    OrientationChange(DotOrientation),
+    RotationChange(f64),
+}
+
+/// Starting parameters for each shape, so switching shapes gives a dot of
+/// similar size
+fn get_shape_presets() -> Vec<(&'static str, DotShape)> {
+    return vec![
+        ("Circle", DotShape::CircleAndRing),
+        (
+            "Square",
+            DotShape::Square {
+                radius: 1.0,
+                stroke_width: 0.0,
+            },
+        ),
+        (
+            "Diamond",
+            DotShape::Diamond {
+                radius: 1.0,
+                stroke_width: 0.0,
+            },
+        ),
+        (
+            "Polygon",
+            DotShape::Polygon {
+                sides: 6,
+                radius: 1.0,
+                stroke_width: 0.0,
+            },
+        ),
+        (
+            "Star",
+            DotShape::Star {
+                points: 5,
+                radius: 1.5,
+                inner_radius: 0.6,
+                stroke_width: 0.0,
+            },
+        ),
+        (
+            "Tick",
+            DotShape::Tick {
+                direction: TickDirection::Radial,
+                length: 2.0,
+                stroke_width: 0.3,
+            },
+        ),
+        (
+            "Custom",
+            DotShape::Custom {
+                svg: String::from("<rect x=\"-1\" y=\"-1\" width=\"2\" height=\"2\" rx=\"0.5\"/>"),
+                radius: 1.5,
+            },
+        ),
+    ];
+}
+
+fn is_same_shape_kind(a: &DotShape, b: &DotShape) -> bool {
+    return std::mem::discriminant(a) == std::mem::discriminant(b);
 }
 
 impl Component for DotEditor {
@@ -41,23 +110,61 @@ impl Component for DotEditor {
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let mut dot = self.dot.clone();
         match msg {
-            DotEditorMsg::RingRadiusChange(v) => self.on_updated.emit(Dot {
-                ring_radius: v,
-                ring_stroke_width: self.dot.ring_stroke_width,
-                circle_radius: self.dot.circle_radius,
-            }),
-            DotEditorMsg::CircleRadiusChange(v) => self.on_updated.emit(Dot {
-                ring_radius: self.dot.ring_radius,
-                ring_stroke_width: self.dot.ring_stroke_width,
-                circle_radius: v,
-            }),
-            DotEditorMsg::RingStrokeWidthChange(v) => self.on_updated.emit(Dot {
-                ring_radius: self.dot.ring_radius,
-                ring_stroke_width: v,
-                circle_radius: self.dot.circle_radius,
-            }),
+            DotEditorMsg::RingRadiusChange(v) => dot.ring_radius = v,
+            DotEditorMsg::CircleRadiusChange(v) => dot.circle_radius = v,
+            DotEditorMsg::RingStrokeWidthChange(v) => dot.ring_stroke_width = v,
+            DotEditorMsg::UseShape(shape) => {
+                // ticks are only useful lined up with the ring
+                if let DotShape::Tick { .. } = shape {
+                    if dot.orientation == DotOrientation::Upright {
+                        dot.orientation = DotOrientation::Normal;
+                    }
+                }
+                dot.shape = shape;
+            }
+            DotEditorMsg::ShapeRadiusChange(v) => match &mut dot.shape {
+                DotShape::Square { radius, .. }
+                | DotShape::Diamond { radius, .. }
+                | DotShape::Polygon { radius, .. }
+                | DotShape::Star { radius, .. }
+                | DotShape::Custom { radius, .. } => *radius = v,
+                _ => {}
+            },
+            DotEditorMsg::ShapeStrokeWidthChange(v) => match &mut dot.shape {
+                DotShape::Square { stroke_width, .. }
+                | DotShape::Diamond { stroke_width, .. }
+                | DotShape::Polygon { stroke_width, .. }
+                | DotShape::Star { stroke_width, .. }
+                | DotShape::Tick { stroke_width, .. } => *stroke_width = v,
+                _ => {}
+            },
+            DotEditorMsg::ShapeCornersChange(v) => match &mut dot.shape {
+                DotShape::Polygon { sides, .. } => *sides = v.round() as u32,
+                DotShape::Star { points, .. } => *points = v.round() as u32,
+                _ => {}
+            },
+            DotEditorMsg::StarInnerRadiusChange(v) => match &mut dot.shape {
+                DotShape::Star { inner_radius, .. } => *inner_radius = v,
+                _ => {}
+            },
+            DotEditorMsg::TickDirectionChange(new_direction) => match &mut dot.shape {
+                DotShape::Tick { direction, .. } => *direction = new_direction,
+                _ => {}
+            },
+            DotEditorMsg::TickLengthChange(v) => match &mut dot.shape {
+                DotShape::Tick { length, .. } => *length = v,
+                _ => {}
+            },
+            DotEditorMsg::CustomSvgChange(new_svg) => match &mut dot.shape {
+                DotShape::Custom { svg, .. } => *svg = new_svg,
+                _ => {}
+            },
+            DotEditorMsg::OrientationChange(orientation) => dot.orientation = orientation,
+            DotEditorMsg::RotationChange(v) => dot.rotation_degrees = v,
         };
+        self.on_updated.emit(dot);
 
         false // update given in onChange in parent state
     }
@@ -75,11 +182,34 @@ impl Component for DotEditor {
     }
 }
 
-impl Renderable<DotEditor> for DotEditor {
-    fn view(&self) -> Html<Self> {
-        return html! {
-            <form class="dot-editor fieldset",>
-                <span class="fields",>
+impl DotEditor {
+    fn view_shape_fields(&self) -> Html<Self> {
+        let stroke_width_field = |stroke_width: f64| -> Html<Self> {
+            html! {
+                <FloatFieldSet:
+                    human_name="Stroke Width",
+                    input_name="shape_stroke_width",
+                    value={stroke_width},
+                    max={5.0},
+                    on_input=|new_val| DotEditorMsg::ShapeStrokeWidthChange(new_val),
+                />
+            }
+        };
+        let radius_field = |radius: f64| -> Html<Self> {
+            html! {
+                <FloatFieldSet:
+                    human_name="Radius",
+                    input_name="shape_radius",
+                    value={radius},
+                    max={10.0},
+                    on_input=|new_val| DotEditorMsg::ShapeRadiusChange(new_val),
+                />
+            }
+        };
+
+        return match &self.dot.shape {
+            DotShape::CircleAndRing => html! {
+                <>
                     <FloatFieldSet:
                         human_name="Circle Radius",
                         input_name="circle_radius",
@@ -101,8 +231,174 @@ impl Renderable<DotEditor> for DotEditor {
                         max={5.0},
                         on_input=|new_val| DotEditorMsg::RingStrokeWidthChange(new_val),
                     />
+                </>
+            },
+            DotShape::Square {
+                radius,
+                stroke_width,
+            }
+            | DotShape::Diamond {
+                radius,
+                stroke_width,
+            } => html! {
+                <>
+                    {radius_field(*radius)}
+                    {stroke_width_field(*stroke_width)}
+                </>
+            },
+            DotShape::Polygon {
+                sides,
+                radius,
+                stroke_width,
+            } => html! {
+                <>
+                    <FloatFieldSet:
+                        human_name="Sides",
+                        input_name="shape_corners",
+                        value={*sides as f64},
+                        max={12.0},
+                        on_input=|new_val| DotEditorMsg::ShapeCornersChange(new_val),
+                    />
+                    {radius_field(*radius)}
+                    {stroke_width_field(*stroke_width)}
+                </>
+            },
+            DotShape::Star {
+                points,
+                radius,
+                inner_radius,
+                stroke_width,
+            } => html! {
+                <>
+                    <FloatFieldSet:
+                        human_name="Points",
+                        input_name="shape_corners",
+                        value={*points as f64},
+                        max={12.0},
+                        on_input=|new_val| DotEditorMsg::ShapeCornersChange(new_val),
+                    />
+                    {radius_field(*radius)}
+                    <FloatFieldSet:
+                        human_name="Inner Radius",
+                        input_name="star_inner_radius",
+                        value={*inner_radius},
+                        max={10.0},
+                        on_input=|new_val| DotEditorMsg::StarInnerRadiusChange(new_val),
+                    />
+                    {stroke_width_field(*stroke_width)}
+                </>
+            },
+            DotShape::Tick {
+                direction,
+                length,
+                stroke_width,
+            } => html! {
+                <>
+                    <label>
+                        <input
+                            type="radio",
+                            checked={*direction == TickDirection::Radial},
+                            onclick=|_| DotEditorMsg::TickDirectionChange(TickDirection::Radial),
+                            />
+                        {"Radial"}
+                    </label>
+                    <label>
+                        <input
+                            type="radio",
+                            checked={*direction == TickDirection::Tangential},
+                            onclick=|_| DotEditorMsg::TickDirectionChange(TickDirection::Tangential),
+                            />
+                        {"Tangential"}
+                    </label>
+                    <FloatFieldSet:
+                        human_name="Length",
+                        input_name="tick_length",
+                        value={*length},
+                        max={10.0},
+                        on_input=|new_val| DotEditorMsg::TickLengthChange(new_val),
+                    />
+                    {stroke_width_field(*stroke_width)}
+                </>
+            },
+            DotShape::Custom { svg, radius } => html! {
+                <>
+                    <label>{"SVG (centered on 0,0)"}</label>
+                    <textarea
+                        class="dot-custom-svg",
+                        value={svg},
+                        oninput=|e| DotEditorMsg::CustomSvgChange(e.value),
+                        />
+                    {match self.dot.get_shape_error() {
+                        Some(error) => html! {
+                            <span class="dot-shape-error",>{error}</span>
+                        },
+                        None => html! { <></> },
+                    }}
+                    {radius_field(*radius)}
+                </>
+            },
+        };
+    }
+}
+
+impl Renderable<DotEditor> for DotEditor {
+    fn view(&self) -> Html<Self> {
+        let shape_options = get_shape_presets().into_iter().map(|(name, shape)| {
+            let checked = is_same_shape_kind(&shape, &self.dot.shape);
+            html! {
+                <label>
+                    <input
+                        type="radio",
+                        checked={checked},
+                        onclick=move |_| DotEditorMsg::UseShape(shape.clone()),
+                        />
+                    {name}
+                </label>
+            }
+        });
+
+        return html! {
+            <form class="dot-editor fieldset",>
+                <span class="fields",>
+                    <span class="dot-shape-options",>
+                        {for shape_options}
+                    </span>
+                    {self.view_shape_fields()}
+                    <span class="dot-orientation-options",>
+                        <label>
+                            <input
+                                type="radio",
+                                checked={self.dot.orientation == DotOrientation::Upright},
+                                onclick=|_| DotEditorMsg::OrientationChange(DotOrientation::Upright),
+                                />
+                            {"Upright"}
+                        </label>
+                        <label>
+                            <input
+                                type="radio",
+                                checked={self.dot.orientation == DotOrientation::Normal},
+                                onclick=|_| DotEditorMsg::OrientationChange(DotOrientation::Normal),
+                                />
+                            {"Face Outward"}
+                        </label>
+                        <label>
+                            <input
+                                type="radio",
+                                checked={self.dot.orientation == DotOrientation::Tangent},
+                                onclick=|_| DotEditorMsg::OrientationChange(DotOrientation::Tangent),
+                                />
+                            {"Follow Ring"}
+                        </label>
+                    </span>
+                    <FloatFieldSet:
+                        human_name="Rotation (degrees)",
+                        input_name="rotation_degrees",
+                        value={self.dot.rotation_degrees},
+                        max={360.0},
+                        on_input=|new_val| DotEditorMsg::RotationChange(new_val),
+                    />
                 </span>
-                {svg_view(&self.dot, &"#EEEEEE" )}
+                {svg_view(&self.dot, &"#EEEEEE", "Dot shape preview")}
             </form>
         };
     }
@@ -0,0 +1,20 @@
+use stdweb::js;
+
+/// Has the browser save `bytes` as a file, by clicking a temporary link to
+/// a data url holding them
+pub fn download_bytes(bytes: &[u8], mime_type: &str, file_name: &str) {
+    let href: String = format!(
+        "data:{};base64,{}",
+        mime_type,
+        base64::encode_config(bytes, base64::STANDARD)
+    );
+    let file_name: String = file_name.to_string();
+    js! { @(no_return)
+        var link = document.createElement("a");
+        link.href = @{href};
+        link.download = @{file_name};
+        document.body.appendChild(link);
+        link.click();
+        document.body.removeChild(link);
+    }
+}
@@ -0,0 +1,21 @@
+use stdweb::js;
+use stdweb::unstable::TryInto;
+
+/// Fragment of the page's url, with its leading `#`, or empty if it has none
+pub fn get_location_hash() -> String {
+    let hash = js! { return window.location.hash; };
+    let hash: Result<String, _> = hash.try_into();
+    return match hash {
+        Ok(hash) => hash,
+        Err(_) => String::new(),
+    };
+}
+
+/// Swaps the url fragment without adding a history entry, so the back
+/// button doesn't step through every edit
+pub fn replace_location_hash(fragment: &str) {
+    let hash: String = format!("#{}", fragment);
+    js! { @(no_return)
+        window.history.replaceState(null, "", @{hash});
+    }
+}
@@ -1,7 +1,14 @@
+pub mod animation_style_editor;
 pub mod app;
 pub mod arc_style_editor;
+pub mod background_style_editor;
+pub mod clipboard;
+pub mod color_style_editor;
+pub mod decoration_style_editor;
 pub mod dot_editor;
+pub mod download;
 pub mod error_toast;
 pub mod float_field_set;
+pub mod location;
 pub mod svg_view;
 pub mod text_path_style_editor;
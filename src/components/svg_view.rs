@@ -1,9 +1,14 @@
+use crate::svg::output::SvgOutputOptions;
 use crate::svg::svg_drawable::SvgRenderer;
 
 use yew::{html, Html};
 
-pub fn svg_data_url<TStyle>(svg_renderer: &SvgRenderer<TStyle>, style: &TStyle) -> String {
-    let raw_svg_string: String = svg_renderer.as_standalone_svg(style);
+pub fn svg_data_url<TStyle>(
+    svg_renderer: &SvgRenderer<TStyle>,
+    style: &TStyle,
+    options: &SvgOutputOptions,
+) -> String {
+    let raw_svg_string: String = svg_renderer.as_svg_string(style, options);
     let img_base64_src: String = format!(
         "data:image/svg+xml;base64,{}",
         base64::encode_config(&raw_svg_string, base64::STANDARD)
@@ -11,13 +16,15 @@ pub fn svg_data_url<TStyle>(svg_renderer: &SvgRenderer<TStyle>, style: &TStyle)
     return img_base64_src;
 }
 
+/// Preview of the svg, with `alt` describing it to screen readers
 pub fn svg_view<T: yew::html::Component, TStyle>(
     svg_renderer: &SvgRenderer<TStyle>,
     style: &TStyle,
+    alt: &str,
 ) -> Html<T> {
-    let img_base64_src = svg_data_url(svg_renderer, style);
+    let img_base64_src = svg_data_url(svg_renderer, style, &SvgOutputOptions::default());
 
     return html! {
-        <img class="preview-image", src=img_base64_src, />
+        <img class="preview-image", src=img_base64_src, alt=alt, />
     };
 }
@@ -1,5 +1,12 @@
-use crate::components::{arc_style_editor::ArcStyleEditor, dot_editor::DotEditor};
+use crate::components::{
+    animation_style_editor::AnimationStyleEditor, arc_style_editor::ArcStyleEditor,
+    color_style_editor::ColorStyleEditor, decoration_style_editor::DecorationStyleEditor,
+    dot_editor::DotEditor,
+};
 use crate::drawing_style::DrawingColors;
+use crate::fig::animation::AnimationStyle;
+use crate::fig::color_style::ColorStyle;
+use crate::fig::decoration::DecorationStyle;
 use crate::fig::dot::Dot;
 use crate::fig::text_path::{ArcStyle, TextPathStyle};
 use yew::{html, Callback, Component, ComponentLink, Html, Renderable, ShouldRender};
@@ -10,10 +17,16 @@ pub struct TextPathStyleEditor {
     pub on_one_dot_updated: Callback<Option<Dot>>,
     pub on_zero_dot_updated: Callback<Option<Dot>>,
     pub on_arc_style_updated: Callback<Option<ArcStyle>>,
+    pub on_decoration_style_updated: Callback<Option<DecorationStyle>>,
+    pub on_color_style_updated: Callback<Option<ColorStyle>>,
+    pub on_animation_style_updated: Callback<Option<AnimationStyle>>,
 
     pub on_add_one_dot_override: Option<Callback<()>>,
     pub on_add_zero_dot_override: Option<Callback<()>>,
     pub on_add_arc_style_override: Option<Callback<()>>,
+    pub on_add_decoration_style_override: Option<Callback<()>>,
+    pub on_add_color_style_override: Option<Callback<()>>,
+    pub on_add_animation_style_override: Option<Callback<()>>,
 
     pub can_remove: bool,
     pub collapsed: bool,
@@ -27,10 +40,16 @@ pub struct TextPathStyleEditorProps {
     pub on_one_dot_updated: Option<Callback<Option<Dot>>>,
     pub on_zero_dot_updated: Option<Callback<Option<Dot>>>,
     pub on_arc_style_updated: Option<Callback<Option<ArcStyle>>>,
+    pub on_decoration_style_updated: Option<Callback<Option<DecorationStyle>>>,
+    pub on_color_style_updated: Option<Callback<Option<ColorStyle>>>,
+    pub on_animation_style_updated: Option<Callback<Option<AnimationStyle>>>,
 
     pub on_add_one_dot_override: Option<Callback<()>>,
     pub on_add_zero_dot_override: Option<Callback<()>>,
     pub on_add_arc_style_override: Option<Callback<()>>,
+    pub on_add_decoration_style_override: Option<Callback<()>>,
+    pub on_add_color_style_override: Option<Callback<()>>,
+    pub on_add_animation_style_override: Option<Callback<()>>,
 
     pub can_remove: bool,
 }
@@ -39,11 +58,17 @@ pub enum TextPathStyleEditorMsg {
     OneDotUpdated(Option<Dot>),
     ZeroDotUpdated(Option<Dot>),
     ArcStyleUpdated(Option<ArcStyle>),
+    DecorationStyleUpdated(Option<DecorationStyle>),
+    ColorStyleUpdated(Option<ColorStyle>),
+    AnimationStyleUpdated(Option<AnimationStyle>),
     ToggleCollapsed,
 
     OnAddOneDot,
     OnAddZeroDot,
     OnAddArcStyle,
+    OnAddDecorationStyle,
+    OnAddColorStyle,
+    OnAddAnimationStyle,
 }
 
 impl Component for TextPathStyleEditor {
@@ -67,12 +92,27 @@ impl Component for TextPathStyleEditor {
                 Some(x) => x,
                 None => panic!("on_arc_style_updated must be specified"),
             },
+            on_decoration_style_updated: match props.on_decoration_style_updated {
+                Some(x) => x,
+                None => panic!("on_decoration_style_updated must be specified"),
+            },
+            on_color_style_updated: match props.on_color_style_updated {
+                Some(x) => x,
+                None => panic!("on_color_style_updated must be specified"),
+            },
+            on_animation_style_updated: match props.on_animation_style_updated {
+                Some(x) => x,
+                None => panic!("on_animation_style_updated must be specified"),
+            },
 
             collapsed: false,
 
             on_add_one_dot_override: props.on_add_one_dot_override,
             on_add_zero_dot_override: props.on_add_zero_dot_override,
             on_add_arc_style_override: props.on_add_arc_style_override,
+            on_add_decoration_style_override: props.on_add_decoration_style_override,
+            on_add_color_style_override: props.on_add_color_style_override,
+            on_add_animation_style_override: props.on_add_animation_style_override,
             can_remove: props.can_remove,
         }
     }
@@ -84,6 +124,15 @@ impl Component for TextPathStyleEditor {
             TextPathStyleEditorMsg::ArcStyleUpdated(arc_style) => {
                 self.on_arc_style_updated.emit(arc_style)
             }
+            TextPathStyleEditorMsg::DecorationStyleUpdated(decoration_style) => {
+                self.on_decoration_style_updated.emit(decoration_style)
+            }
+            TextPathStyleEditorMsg::ColorStyleUpdated(color_style) => {
+                self.on_color_style_updated.emit(color_style)
+            }
+            TextPathStyleEditorMsg::AnimationStyleUpdated(animation_style) => {
+                self.on_animation_style_updated.emit(animation_style)
+            }
             TextPathStyleEditorMsg::ToggleCollapsed => {
                 self.collapsed = !self.collapsed;
                 return true;
@@ -100,6 +149,22 @@ impl Component for TextPathStyleEditor {
                 Some(x) => x.emit(()),
                 None => {}
             },
+            TextPathStyleEditorMsg::OnAddDecorationStyle => {
+                match &self.on_add_decoration_style_override {
+                    Some(x) => x.emit(()),
+                    None => {}
+                }
+            }
+            TextPathStyleEditorMsg::OnAddColorStyle => match &self.on_add_color_style_override {
+                Some(x) => x.emit(()),
+                None => {}
+            },
+            TextPathStyleEditorMsg::OnAddAnimationStyle => {
+                match &self.on_add_animation_style_override {
+                    Some(x) => x.emit(()),
+                    None => {}
+                }
+            }
         };
 
         false // update given in onChange in parent state
@@ -126,10 +191,25 @@ impl Component for TextPathStyleEditor {
             Some(x) => x,
             None => panic!("on_arc_style_updated must be specified"),
         };
+        self.on_decoration_style_updated = match props.on_decoration_style_updated {
+            Some(x) => x,
+            None => panic!("on_decoration_style_updated must be specified"),
+        };
+        self.on_color_style_updated = match props.on_color_style_updated {
+            Some(x) => x,
+            None => panic!("on_color_style_updated must be specified"),
+        };
+        self.on_animation_style_updated = match props.on_animation_style_updated {
+            Some(x) => x,
+            None => panic!("on_animation_style_updated must be specified"),
+        };
 
         self.on_add_one_dot_override = props.on_add_one_dot_override;
         self.on_add_zero_dot_override = props.on_add_zero_dot_override;
         self.on_add_arc_style_override = props.on_add_arc_style_override;
+        self.on_add_decoration_style_override = props.on_add_decoration_style_override;
+        self.on_add_color_style_override = props.on_add_color_style_override;
+        self.on_add_animation_style_override = props.on_add_animation_style_override;
         self.can_remove = props.can_remove;
 
         return should_render;
@@ -242,6 +322,102 @@ impl Renderable<TextPathStyleEditor> for TextPathStyleEditor {
             }
         };
 
+        let decoration_dom = match &self.style.decoration_style {
+            Some(decoration) => html! {<section>
+                {if self.can_remove {
+                    html!{
+                        <button class="remove-override", onclick=|_| TextPathStyleEditorMsg::DecorationStyleUpdated(None), >
+                            {"x"}
+                        </button>
+                    }
+                } else {
+                    html! {
+                        <></>
+                    }
+                }}
+                <h3>{"Decorations"}</h3>
+                <DecorationStyleEditor:
+                    decoration_style={decoration.clone()},
+                    on_updated=|new_decoration| TextPathStyleEditorMsg::DecorationStyleUpdated(Some(new_decoration)),
+                    />
+                </section>
+            },
+            _ => {
+                html! {
+                    <button
+                        class="add-override-fallback",
+                        onclick=|_| TextPathStyleEditorMsg::OnAddDecorationStyle,
+                        >
+                        {"⊕ override decorations"}
+                    </button>
+                }
+            }
+        };
+
+        let color_dom = match &self.style.color_style {
+            Some(color_style) => html! {<section>
+                {if self.can_remove {
+                    html!{
+                        <button class="remove-override", onclick=|_| TextPathStyleEditorMsg::ColorStyleUpdated(None), >
+                            {"x"}
+                        </button>
+                    }
+                } else {
+                    html! {
+                        <></>
+                    }
+                }}
+                <h3>{"Colors"}</h3>
+                <ColorStyleEditor:
+                    color_style={color_style.clone()},
+                    on_updated=|new_color_style| TextPathStyleEditorMsg::ColorStyleUpdated(Some(new_color_style)),
+                    />
+                </section>
+            },
+            _ => {
+                html! {
+                    <button
+                        class="add-override-fallback",
+                        onclick=|_| TextPathStyleEditorMsg::OnAddColorStyle,
+                        >
+                        {"⊕ override colors"}
+                    </button>
+                }
+            }
+        };
+
+        let animation_dom = match &self.style.animation_style {
+            Some(animation_style) => html! {<section>
+                {if self.can_remove {
+                    html!{
+                        <button class="remove-override", onclick=|_| TextPathStyleEditorMsg::AnimationStyleUpdated(None), >
+                            {"x"}
+                        </button>
+                    }
+                } else {
+                    html! {
+                        <></>
+                    }
+                }}
+                <h3>{"Animation"}</h3>
+                <AnimationStyleEditor:
+                    animation_style={animation_style.clone()},
+                    on_updated=|new_animation_style| TextPathStyleEditorMsg::AnimationStyleUpdated(Some(new_animation_style)),
+                    />
+                </section>
+            },
+            _ => {
+                html! {
+                    <button
+                        class="add-override-fallback",
+                        onclick=|_| TextPathStyleEditorMsg::OnAddAnimationStyle,
+                        >
+                        {"⊕ override animation"}
+                    </button>
+                }
+            }
+        };
+
         return html! {
             <section class="text-path-style-editor",>
                 <button class="toggle-collapsed", onclick=|_| TextPathStyleEditorMsg::ToggleCollapsed, >
@@ -255,6 +431,9 @@ impl Renderable<TextPathStyleEditor> for TextPathStyleEditor {
                         {zero_dot_dom}
                         {one_dot_dom}
                         {arc_dom}
+                        {decoration_dom}
+                        {color_dom}
+                        {animation_dom}
                     </>
                 }}}
             </section>
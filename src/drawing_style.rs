@@ -1,3 +1,7 @@
+use crate::fig::animation::AnimationStyle;
+use crate::fig::background::BackgroundStyle;
+use crate::fig::color_style::ColorStyle;
+use crate::fig::decoration::DecorationStyle;
 use crate::fig::dot::Dot;
 use crate::fig::text_path::ArcStyle;
 use serde::{Deserialize, Serialize};
@@ -8,10 +12,18 @@ pub struct DrawingColors {
     pub background_color: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DrawingStyle {
     pub color: DrawingColors,
     pub default_zero_dot_style: Dot,
     pub default_one_dot_style: Dot,
     pub default_arc_style: ArcStyle,
+    #[serde(default)]
+    pub default_decoration_style: DecorationStyle,
+    #[serde(default)]
+    pub default_color_style: ColorStyle,
+    #[serde(default)]
+    pub background: BackgroundStyle,
+    #[serde(default)]
+    pub default_animation_style: AnimationStyle,
 }
@@ -0,0 +1,157 @@
+use crate::svg::element::SvgElement;
+use serde::{Deserialize, Serialize};
+
+const KEYFRAMES_NAME: &str = "dots-reveal";
+
+/// Pace of each dot's fade in
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Default for Easing {
+    fn default() -> Easing {
+        Easing::EaseOut
+    }
+}
+
+impl Easing {
+    fn as_css(&self) -> &'static str {
+        return match self {
+            Easing::Linear => "linear",
+            Easing::EaseIn => "ease-in",
+            Easing::EaseOut => "ease-out",
+            Easing::EaseInOut => "ease-in-out",
+        };
+    }
+
+    /// Control points of the css timing function, for smil's `keySplines`
+    fn get_key_spline(&self) -> Option<&'static str> {
+        return match self {
+            Easing::Linear => None,
+            Easing::EaseIn => Some("0.42 0 1 1"),
+            Easing::EaseOut => Some("0 0 0.58 1"),
+            Easing::EaseInOut => Some("0.42 0 0.58 1"),
+        };
+    }
+}
+
+/// How dots are revealed in animated exports. Dots fade in one after
+/// another, in bit order
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct AnimationStyle {
+    /// Seconds from the start of the animation to the first dot
+    pub delay_s: f64,
+    /// Seconds each dot takes to fade in. The next dot starts as the one
+    /// before it finishes
+    pub duration_s: f64,
+    pub easing: Easing,
+}
+
+impl Default for AnimationStyle {
+    fn default() -> AnimationStyle {
+        AnimationStyle {
+            delay_s: 0.0,
+            duration_s: 0.1,
+            easing: Easing::default(),
+        }
+    }
+}
+
+/// How animated exports are written
+#[derive(Debug, PartialEq, Clone)]
+pub enum AnimationMode {
+    /// `<animate>` elements, which play wherever the svg is shown, even as
+    /// an `<img>`
+    Smil,
+    /// A `<style>` of css keyframe animations, for svgs inlined in a page
+    Css,
+}
+
+/// Seconds as a css/smil clock value, to the millisecond
+fn format_seconds(seconds: f64) -> String {
+    return format!("{}s", (seconds * 1000.0).round() / 1000.0);
+}
+
+/// `<style>` holding the keyframes that css animated dots play
+pub fn css_keyframes() -> SvgElement {
+    return SvgElement::new("style").text(&format!(
+        "@keyframes {}{{from{{opacity:0}}to{{opacity:1}}}}",
+        KEYFRAMES_NAME
+    ));
+}
+
+impl AnimationStyle {
+    /// Seconds from the start of the animation until the `index`th dot
+    /// starts to fade in
+    pub fn get_dot_start(&self, index: usize) -> f64 {
+        return self.delay_s + self.duration_s * index as f64;
+    }
+
+    /// Makes `dot` fade in at the time the `index`th dot is revealed
+    pub fn animate_dot(&self, dot: SvgElement, index: usize, mode: &AnimationMode) -> SvgElement {
+        let start = self.get_dot_start(index);
+        return match mode {
+            AnimationMode::Smil => {
+                let mut animate = SvgElement::new("animate")
+                    .attr("attributeName", "opacity")
+                    .attr("from", 0.0)
+                    .attr("to", 1.0)
+                    .attr("begin", format_seconds(start))
+                    .attr("dur", format_seconds(self.duration_s))
+                    .attr("fill", "freeze");
+                animate = match self.easing.get_key_spline() {
+                    Some(spline) => animate
+                        .attr("calcMode", "spline")
+                        .attr("keyTimes", "0;1")
+                        .attr("keySplines", spline),
+                    None => animate.attr("calcMode", "linear"),
+                };
+                dot.attr("opacity", 0.0).child(animate)
+            }
+            AnimationMode::Css => dot.attr(
+                "style",
+                format!(
+                    "animation:{} {} {} {} both",
+                    KEYFRAMES_NAME,
+                    format_seconds(self.duration_s),
+                    self.easing.as_css(),
+                    format_seconds(start)
+                ),
+            ),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fig::animation::{css_keyframes, AnimationMode, AnimationStyle};
+    use crate::svg::element::SvgElement;
+    use crate::svg::output::SvgOutputOptions;
+
+    #[test]
+    fn test_animate_dot() {
+        let style = AnimationStyle::default();
+        let render = |mode: &AnimationMode| {
+            SvgOutputOptions::default().render(&style.animate_dot(
+                SvgElement::new("circle"),
+                3,
+                mode,
+            ))
+        };
+
+        let smil = render(&AnimationMode::Smil);
+        assert!(smil.starts_with("<circle opacity=\"0\"><animate "));
+        assert!(smil.contains("begin=\"0.3s\" dur=\"0.1s\""));
+        assert!(smil.contains("keySplines=\"0 0 0.58 1\""));
+
+        let css = render(&AnimationMode::Css);
+        assert!(css.contains("animation:dots-reveal 0.1s ease-out 0.3s both"));
+        assert!(SvgOutputOptions::default()
+            .render(&css_keyframes())
+            .contains("@keyframes dots-reveal"));
+    }
+}
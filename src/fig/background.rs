@@ -0,0 +1,222 @@
+use crate::drawing_style::DrawingColors;
+use crate::geom::rect::Rect;
+use crate::geom::vector::Vector2;
+use crate::svg::element::SvgElement;
+use crate::svg::svg_drawable::SvgFragment;
+use crate::svg::util::rect_svg;
+use serde::{Deserialize, Serialize};
+
+const GRADIENT_ID: &str = "background-fill";
+
+/// What the area behind the rings is filled with
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub enum BackgroundFill {
+    /// The drawing's background color
+    Solid,
+    /// Blend from one side of the background to the other, along a line
+    /// `angle_degrees` clockwise from left-to-right
+    LinearGradient {
+        from: String,
+        to: String,
+        angle_degrees: f64,
+    },
+    /// Blend outward from the center of the rings to the background's edge
+    RadialGradient { inner: String, outer: String },
+    /// Nothing behind the rings
+    Transparent,
+}
+
+impl Default for BackgroundFill {
+    fn default() -> BackgroundFill {
+        BackgroundFill::Solid
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub enum BackgroundShape {
+    /// Fill the whole image
+    Rectangle,
+    /// A disk centered on the rings, reaching `diagram_padding` past the
+    /// largest ring
+    Disk,
+}
+
+impl Default for BackgroundShape {
+    fn default() -> BackgroundShape {
+        BackgroundShape::Rectangle
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
+pub struct BackgroundStyle {
+    pub fill: BackgroundFill,
+    pub shape: BackgroundShape,
+    /// Width of the line around the background's edge. No border when 0
+    pub border_width: f64,
+    /// Falls back to the drawing's stroke color when unset
+    pub border_color: Option<String>,
+}
+
+pub struct BackgroundRenderStyle<'a> {
+    pub colors: &'a DrawingColors,
+    /// Bounds of the whole image
+    pub bounds: Rect,
+    /// Radius of the disk, for disk backgrounds
+    pub disk_radius: f64,
+}
+
+impl BackgroundStyle {
+    /// Bounds of the disk, border included, for disk backgrounds
+    pub fn get_disk_bounding_rect(&self, disk_radius: f64) -> Option<Rect> {
+        return match self.shape {
+            BackgroundShape::Disk => Some(Rect::around_point(
+                &Vector2::zero(),
+                disk_radius + self.border_width / 2.0,
+            )),
+            BackgroundShape::Rectangle => None,
+        };
+    }
+
+    fn get_gradient(&self, shape_bounds: &Rect) -> Option<SvgElement> {
+        let stop = |offset: f64, color: &str| {
+            SvgElement::new("stop")
+                .attr("offset", offset)
+                .attr("stop-color", color)
+        };
+        return match &self.fill {
+            BackgroundFill::LinearGradient {
+                from,
+                to,
+                angle_degrees,
+            } => {
+                // the gradient line runs through the center, just long
+                // enough to reach the corners
+                let angle = angle_degrees.to_radians();
+                let direction = Vector2::from_polar(1.0, angle);
+                let reach = (shape_bounds.width / 2.0 * direction.x).abs()
+                    + (shape_bounds.height / 2.0 * direction.y).abs();
+                let center = shape_bounds.center();
+                let start = center - direction * reach;
+                let end = center + direction * reach;
+                Some(
+                    SvgElement::new("linearGradient")
+                        .attr("id", GRADIENT_ID)
+                        .attr("gradientUnits", "userSpaceOnUse")
+                        .attr("x1", start.x)
+                        .attr("y1", start.y)
+                        .attr("x2", end.x)
+                        .attr("y2", end.y)
+                        .child(stop(0.0, from))
+                        .child(stop(1.0, to)),
+                )
+            }
+            BackgroundFill::RadialGradient { inner, outer } => {
+                let corners = [shape_bounds.min(), shape_bounds.max()];
+                let radius = corners
+                    .iter()
+                    .map(|corner| Vector2::new(corner.x.abs(), corner.y.abs()))
+                    .fold(Vector2::zero(), |farthest, corner| {
+                        Vector2::new(farthest.x.max(corner.x), farthest.y.max(corner.y))
+                    })
+                    .length();
+                Some(
+                    SvgElement::new("radialGradient")
+                        .attr("id", GRADIENT_ID)
+                        .attr("gradientUnits", "userSpaceOnUse")
+                        .attr("cx", 0.0)
+                        .attr("cy", 0.0)
+                        .attr("r", radius)
+                        .child(stop(0.0, inner))
+                        .child(stop(1.0, outer)),
+                )
+            }
+            BackgroundFill::Solid | BackgroundFill::Transparent => None,
+        };
+    }
+}
+
+impl<'a> SvgFragment<BackgroundRenderStyle<'a>> for BackgroundStyle {
+    /// Builds the background, with any gradient it uses defined alongside it
+    fn as_svg_fragment(&self, style: &BackgroundRenderStyle<'a>) -> SvgElement {
+        let shape_bounds = match self.shape {
+            BackgroundShape::Rectangle => style.bounds,
+            BackgroundShape::Disk => Rect::around_point(&Vector2::zero(), style.disk_radius),
+        };
+        let fill = match &self.fill {
+            BackgroundFill::Solid => style.colors.background_color.clone(),
+            BackgroundFill::Transparent => String::from("none"),
+            _ => format!("url(#{})", GRADIENT_ID),
+        };
+
+        let mut shape = match self.shape {
+            // inset so the whole border stays in the image
+            BackgroundShape::Rectangle => {
+                rect_svg(&style.bounds.expand(-self.border_width / 2.0), &fill)
+            }
+            BackgroundShape::Disk => SvgElement::new("circle")
+                .attr("cx", 0.0)
+                .attr("cy", 0.0)
+                .attr("r", style.disk_radius)
+                .attr("fill", fill),
+        };
+        if self.border_width > 0.0 {
+            let border_color = match &self.border_color {
+                Some(color) => color,
+                None => &style.colors.stroke_color,
+            };
+            shape = shape
+                .attr("stroke", border_color)
+                .attr("stroke-width", self.border_width);
+        }
+
+        let mut background = SvgElement::group();
+        if let Some(gradient) = self.get_gradient(&shape_bounds) {
+            background = background.child(SvgElement::new("defs").child(gradient));
+        }
+        return background.child(shape);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_style::DrawingColors;
+    use crate::fig::background::{
+        BackgroundFill, BackgroundRenderStyle, BackgroundShape, BackgroundStyle,
+    };
+    use crate::geom::rect::Rect;
+    use crate::svg::output::SvgOutputOptions;
+    use crate::svg::svg_drawable::SvgFragment;
+
+    #[test]
+    fn test_gradient_disk_background() {
+        let background = BackgroundStyle {
+            fill: BackgroundFill::RadialGradient {
+                inner: "#ffffff".to_string(),
+                outer: "#000000".to_string(),
+            },
+            shape: BackgroundShape::Disk,
+            border_width: 1.0,
+            border_color: None,
+        };
+
+        // the border reaches half its width past the disk
+        let disk_bounds = background.get_disk_bounding_rect(10.0).unwrap();
+        assert_eq!(disk_bounds.width, 21.0);
+
+        let svg = SvgOutputOptions::default().render(&background.as_svg_fragment(
+            &BackgroundRenderStyle {
+                colors: &DrawingColors {
+                    stroke_color: "#333333".to_string(),
+                    background_color: "#eeeeee".to_string(),
+                },
+                bounds: disk_bounds,
+                disk_radius: 10.0,
+            },
+        ));
+        assert!(svg.contains("<radialGradient id=\"background-fill\""));
+        assert!(svg.contains("fill=\"url(#background-fill)\" stroke=\"#333333\""));
+
+        let rectangle = BackgroundStyle::default();
+        assert_eq!(rectangle.get_disk_bounding_rect(10.0), None::<Rect>);
+    }
+}
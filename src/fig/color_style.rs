@@ -0,0 +1,142 @@
+use crate::color::parse_color;
+use serde::{Deserialize, Serialize};
+
+/// Fill and stroke for one kind of dot. Unset colors fall back to the
+/// path's color
+#[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
+pub struct DotColors {
+    pub fill: Option<String>,
+    pub stroke: Option<String>,
+}
+
+/// How the base color of dots varies across a drawing
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub enum Palette {
+    /// Every dot on the path has the path's color
+    Solid,
+    /// Blend from one color at the first dot to another at the last
+    AlongRing { from: String, to: String },
+    /// Each path has a single color, blending from one color on the first
+    /// path to another on the last
+    ByRing { from: String, to: String },
+}
+
+impl Default for Palette {
+    fn default() -> Palette {
+        Palette::Solid
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
+pub struct ColorStyle {
+    /// Color of the path's dots and decorations. Falls back to the
+    /// drawing's stroke color when unset
+    pub color: Option<String>,
+    pub palette: Palette,
+    /// Overrides for zero dots, taking precedence over the palette
+    pub zero_dot: DotColors,
+    /// Overrides for one dots, taking precedence over the palette
+    pub one_dot: DotColors,
+}
+
+/// Where a dot sits in the drawing, for palettes that vary by position
+pub struct DotPosition {
+    pub dot_index: usize,
+    pub num_dots: usize,
+    pub path_index: usize,
+    pub num_paths: usize,
+}
+
+/// Color `fraction` of the way from `from` to `to`. Colors that don't parse
+/// are returned as given, so the blend degrades to the start color
+fn blend(from: &str, to: &str, fraction: f64) -> String {
+    return match (parse_color(from), parse_color(to)) {
+        (Some(from), Some(to)) => from.lerp(&to, fraction).to_hex(),
+        _ => from.to_string(),
+    };
+}
+
+fn get_fraction(index: usize, count: usize) -> f64 {
+    if count < 2 {
+        return 0.0;
+    }
+    return index as f64 / (count - 1) as f64;
+}
+
+impl ColorStyle {
+    /// Color of the path as a whole, used for its decorations
+    pub fn get_path_color(
+        &self,
+        stroke_color: &str,
+        path_index: usize,
+        num_paths: usize,
+    ) -> String {
+        return match &self.palette {
+            Palette::ByRing { from, to } => blend(from, to, get_fraction(path_index, num_paths)),
+            _ => match &self.color {
+                Some(color) => color.clone(),
+                None => stroke_color.to_string(),
+            },
+        };
+    }
+
+    /// Base color of the dot at `position`, before per-bit overrides
+    pub fn get_dot_color(&self, stroke_color: &str, position: &DotPosition) -> String {
+        return match &self.palette {
+            Palette::AlongRing { from, to } => blend(
+                from,
+                to,
+                get_fraction(position.dot_index, position.num_dots),
+            ),
+            _ => self.get_path_color(stroke_color, position.path_index, position.num_paths),
+        };
+    }
+
+    pub fn get_dot_colors(&self, is_one: bool) -> &DotColors {
+        return if is_one {
+            &self.one_dot
+        } else {
+            &self.zero_dot
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fig::color_style::{ColorStyle, DotPosition, Palette};
+
+    #[test]
+    fn test_palettes() {
+        let position = |dot_index: usize, path_index: usize| DotPosition {
+            dot_index: dot_index,
+            num_dots: 3,
+            path_index: path_index,
+            num_paths: 2,
+        };
+
+        let solid = ColorStyle::default();
+        assert_eq!(solid.get_dot_color("#333333", &position(1, 1)), "#333333");
+
+        let along_ring = ColorStyle {
+            palette: Palette::AlongRing {
+                from: "#000000".to_string(),
+                to: "#ffffff".to_string(),
+            },
+            ..ColorStyle::default()
+        };
+        assert_eq!(
+            along_ring.get_dot_color("#333333", &position(1, 0)),
+            "#808080"
+        );
+        assert_eq!(along_ring.get_path_color("#333333", 0, 2), "#333333");
+
+        let by_ring = ColorStyle {
+            palette: Palette::ByRing {
+                from: "#ff0000".to_string(),
+                to: "#0000ff".to_string(),
+            },
+            ..ColorStyle::default()
+        };
+        assert_eq!(by_ring.get_dot_color("#333333", &position(2, 1)), "#0000ff");
+    }
+}
@@ -0,0 +1,202 @@
+use crate::fig::text_path::ArcStyle;
+use crate::float_utils::fmax;
+use crate::geom::rect::{bounding_rect_of_circles, union_all, Rect};
+use crate::geom::vector::Vector2;
+use crate::svg::element::SvgElement;
+use crate::svg::path::PathData;
+use crate::svg::svg_drawable::SvgFragment;
+use crate::svg::util::line_svg;
+use serde::{Deserialize, Serialize};
+
+/// Thin line drawn along the arc a text path follows
+#[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
+pub struct GuideArcStyle {
+    pub stroke_width: f64,
+    /// Falls back to the drawing's stroke color when unset
+    pub color: Option<String>,
+    /// Alternating dash / gap lengths. Empty for a solid line
+    pub dash_pattern: Vec<f64>,
+}
+
+/// Radial ticks drawn between groups of dots (by default, between characters)
+#[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
+pub struct SeparatorTickStyle {
+    pub length: f64,
+    pub stroke_width: f64,
+    pub bits_per_separator: usize,
+}
+
+/// Radial ticks drawn just before the first and just after the last dot
+#[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
+pub struct EndCapStyle {
+    pub length: f64,
+    pub stroke_width: f64,
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
+pub struct DecorationStyle {
+    pub guide_arc: Option<GuideArcStyle>,
+    pub separator_ticks: Option<SeparatorTickStyle>,
+    pub end_caps: Option<EndCapStyle>,
+}
+
+pub struct DecorationRenderStyle<'a> {
+    pub arc_style: &'a ArcStyle,
+    pub num_dots: usize,
+    pub stroke_color: &'a str,
+}
+
+/// End points of a tick along the ray from (0,0) at `angle`, centered at
+/// `radius` and spanning `length`
+fn get_radial_tick_ends(radius: f64, angle: f64, length: f64) -> (Vector2, Vector2) {
+    return (
+        Vector2::from_polar(radius - length / 2.0, angle),
+        Vector2::from_polar(radius + length / 2.0, angle),
+    );
+}
+
+impl DecorationStyle {
+    /// Distance the decorations extend past the radius of the arc they follow
+    pub fn get_bounding_extent(&self) -> f64 {
+        let mut extent: f64 = 0.0;
+        if let Some(guide_arc) = &self.guide_arc {
+            extent = fmax(&extent, &(guide_arc.stroke_width / 2.0));
+        }
+        if let Some(ticks) = &self.separator_ticks {
+            extent = fmax(&extent, &((ticks.length + ticks.stroke_width) / 2.0));
+        }
+        if let Some(caps) = &self.end_caps {
+            extent = fmax(&extent, &((caps.length + caps.stroke_width) / 2.0));
+        }
+        return extent;
+    }
+
+    fn get_separator_angles(&self, style: &DecorationRenderStyle) -> Vec<f64> {
+        let mut angles: Vec<f64> = vec![];
+        if let Some(ticks) = &self.separator_ticks {
+            if ticks.bits_per_separator > 0 {
+                let mut boundary = ticks.bits_per_separator;
+                while boundary < style.num_dots {
+                    // dot `i` sits at step `i + 1`, so the gap between dots
+                    // `boundary - 1` and `boundary` is at step `boundary + 0.5`
+                    angles.push(
+                        style
+                            .arc_style
+                            .get_step_angle(boundary as f64 + 0.5, style.num_dots),
+                    );
+                    boundary += ticks.bits_per_separator;
+                }
+            }
+        }
+        return angles;
+    }
+
+    fn get_end_cap_angles(&self, style: &DecorationRenderStyle) -> Vec<f64> {
+        if self.end_caps.is_none() || style.num_dots == 0 {
+            return vec![];
+        }
+        return vec![
+            style.arc_style.get_step_angle(0.5, style.num_dots),
+            style
+                .arc_style
+                .get_step_angle(style.num_dots as f64 + 0.5, style.num_dots),
+        ];
+    }
+
+    /// Tight bounds of the decorations, if any are drawn
+    pub fn get_bounding_rect(&self, style: &DecorationRenderStyle) -> Option<Rect> {
+        let arc_style = style.arc_style;
+        let mut rects: Vec<Option<Rect>> = vec![];
+
+        if let Some(guide_arc) = &self.guide_arc {
+            rects.push(Some(
+                arc_style
+                    .get_arc()
+                    .bounding_rect()
+                    .expand(guide_arc.stroke_width / 2.0),
+            ));
+        }
+
+        let tick_groups = vec![
+            (
+                self.get_separator_angles(style),
+                self.separator_ticks
+                    .as_ref()
+                    .map(|ticks| (ticks.length, ticks.stroke_width)),
+            ),
+            (
+                self.get_end_cap_angles(style),
+                self.end_caps
+                    .as_ref()
+                    .map(|caps| (caps.length, caps.stroke_width)),
+            ),
+        ];
+        for (angles, dimensions) in tick_groups {
+            if let Some((length, stroke_width)) = dimensions {
+                let mut tick_ends: Vec<(Vector2, f64)> = vec![];
+                for angle in angles {
+                    let (inner, outer) = get_radial_tick_ends(arc_style.radius, angle, length);
+                    tick_ends.push((inner, stroke_width / 2.0));
+                    tick_ends.push((outer, stroke_width / 2.0));
+                }
+                rects.push(bounding_rect_of_circles(&tick_ends));
+            }
+        }
+
+        return union_all(rects);
+    }
+}
+
+impl<'a> SvgFragment<DecorationRenderStyle<'a>> for DecorationStyle {
+    /// Builds an svg for the decorations of a single text path
+    ///
+    /// Like the text path itself, the decorations are centered on (0,0)
+    fn as_svg_fragment(&self, style: &DecorationRenderStyle<'a>) -> SvgElement {
+        let arc_style = style.arc_style;
+        let mut parts: Vec<SvgElement> = vec![];
+
+        if let Some(guide_arc) = &self.guide_arc {
+            let mut guide = SvgElement::new("path")
+                .attr("d", PathData::from_arc(&arc_style.get_arc()))
+                .attr("fill", "none")
+                .attr(
+                    "stroke",
+                    match &guide_arc.color {
+                        Some(color) => color.as_str(),
+                        None => style.stroke_color,
+                    },
+                )
+                .attr("stroke-width", guide_arc.stroke_width);
+            if !guide_arc.dash_pattern.is_empty() {
+                guide.set_attr("stroke-dasharray", guide_arc.dash_pattern.clone());
+            }
+            parts.push(guide);
+        }
+
+        if let Some(ticks) = &self.separator_ticks {
+            for angle in self.get_separator_angles(style) {
+                let (inner, outer) = get_radial_tick_ends(arc_style.radius, angle, ticks.length);
+                parts.push(line_svg(
+                    &inner,
+                    &outer,
+                    ticks.stroke_width,
+                    style.stroke_color,
+                ));
+            }
+        }
+
+        if let Some(caps) = &self.end_caps {
+            for angle in self.get_end_cap_angles(style) {
+                let (inner, outer) = get_radial_tick_ends(arc_style.radius, angle, caps.length);
+                parts.push(line_svg(
+                    &inner,
+                    &outer,
+                    caps.stroke_width,
+                    style.stroke_color,
+                ));
+            }
+        }
+
+        return SvgElement::group().children(parts);
+    }
+}
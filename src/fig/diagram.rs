@@ -1,70 +1,362 @@
 use crate::drawing_style::DrawingStyle;
+use crate::fig::animation::{css_keyframes, AnimationMode};
+use crate::fig::background::BackgroundRenderStyle;
+use crate::fig::dot::DotDefinitions;
 use crate::fig::text_path::*;
 use crate::float_utils::fmax;
-use crate::geom::*;
-use crate::serializable_app_state::{serialize, SerializableAppState};
+use crate::geom::rect::{union_all, Rect};
+use crate::geom::transform::Transform2D;
+use crate::geom::vector::Vector2;
+use crate::migrations::SCHEMA_VERSION;
+use crate::pdf::render::{render_pdf, PdfOptions};
+use crate::plot::dxf::render_dxf;
+use crate::plot::gcode::{render_gcode, GcodeOptions};
+use crate::plot::hpgl::render_hpgl;
+use crate::plot::toolpath::{get_toolpaths, order_toolpaths, PlotOptions, Toolpath};
+use crate::raster::png::{encode_png, PngMetadata};
+use crate::raster::render::{rasterize, RasterOptions};
+use crate::serializable_app_state::{serialize, SerializableAppState, DOTS_NAMESPACE};
+use crate::svg::element::SvgElement;
 use crate::svg::svg_drawable::{SvgFragment, SvgRenderer};
-use crate::svg::util::*;
+use crate::svg::util::svg_root;
+use crate::svg::walk::{get_view_box, walk_document, DrawItem};
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// How the exported image is framed around the diagram
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub enum BoundingMode {
+    /// Crop to the space the dots and decorations actually cover
+    Tight,
+    /// A square centered on the center of the rings, large enough to hold the
+    /// largest ring
+    CenteredSquare,
+}
+
+impl Default for BoundingMode {
+    fn default() -> BoundingMode {
+        BoundingMode::Tight
+    }
+}
+
+/// What screen readers are told about exported svgs
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct AccessibilityOptions {
+    pub title: String,
+    /// Language of the encoded text, as a BCP 47 tag like `en`
+    pub lang: String,
+}
+
+impl Default for AccessibilityOptions {
+    fn default() -> AccessibilityOptions {
+        AccessibilityOptions {
+            title: String::from("Radial Dots"),
+            lang: String::from("en"),
+        }
+    }
+}
+
+const TITLE_ID: &str = "diagram-title";
+const DESC_ID: &str = "diagram-desc";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Diagram {
     pub paths: Vec<TextPath>,
     pub diagram_padding: f64,
+    #[serde(default)]
+    pub bounding_mode: BoundingMode,
+    /// Describes the svg to screen readers when set
+    #[serde(default)]
+    pub accessibility: Option<AccessibilityOptions>,
 }
 
 impl Diagram {
-    fn get_bounding_rect(&self, style: &DrawingStyle) -> Rect {
+    /// Bounds of the diagram, including padding, in the coordinate space
+    /// where the rings are centered on (0,0)
+    pub fn get_bounding_rect(&self, style: &DrawingStyle) -> Rect {
+        let content_rect: Rect = match self.bounding_mode {
+            BoundingMode::Tight => {
+                match union_all(self.paths.iter().map(|path| path.get_bounding_rect(style))) {
+                    Some(rect) => rect,
+                    None => Rect::around_point(&Vector2::zero(), 1.0),
+                }
+            }
+            BoundingMode::CenteredSquare => {
+                Rect::around_point(&Vector2::zero(), self.get_outer_radius(style))
+            }
+        };
+
+        let padded_rect = content_rect.expand(self.diagram_padding);
+        return match style
+            .background
+            .get_disk_bounding_rect(self.get_background_disk_radius(style))
+        {
+            Some(disk_rect) => padded_rect.union(&disk_rect),
+            None => padded_rect,
+        };
+    }
+
+    /// The text the rings encode, one ring per line, innermost first
+    pub fn get_description(&self) -> String {
+        return self
+            .paths
+            .iter()
+            .map(|path| path.text.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+    }
+
+    /// Radius of the background, for disk backgrounds
+    fn get_background_disk_radius(&self, style: &DrawingStyle) -> f64 {
+        return self.get_outer_radius(style) + self.diagram_padding;
+    }
+
+    /// Distance from the center to the outer edge of the largest ring
+    pub fn get_outer_radius(&self, style: &DrawingStyle) -> f64 {
         let mut radius: f64 = 1.0;
         for path in self.paths.iter() {
             radius = fmax(&radius, &path.get_bounding_radius(style));
         }
-
-        return Rect {
-            x: 0.0,
-            y: 0.0,
-            width: (radius + self.diagram_padding) * 2.0,
-            height: (radius + self.diagram_padding) * 2.0,
-        };
+        return radius;
     }
 }
 
 impl SvgRenderer<DrawingStyle> for Diagram {
-    fn as_standalone_svg(&self, style: &DrawingStyle) -> String {
-        let mut svg_parts: Vec<String> = Vec::with_capacity(self.paths.len() + 1);
+    fn as_standalone_svg(&self, style: &DrawingStyle) -> SvgElement {
+        return self.build_svg(style, true, None);
+    }
+}
+
+impl Diagram {
+    /// Builds the svg with each path's dots fading in one after another, in
+    /// bit order. The static svg is unaffected
+    pub fn as_animated_svg(&self, style: &DrawingStyle, mode: &AnimationMode) -> SvgElement {
+        return self.build_svg(style, true, Some(mode));
+    }
+
+    fn build_svg(
+        &self,
+        style: &DrawingStyle,
+        draw_background: bool,
+        animation: Option<&AnimationMode>,
+    ) -> SvgElement {
         let diagram_bounds: Rect = self.get_bounding_rect(style);
-        let diagram_center: Vector2 = diagram_bounds.center();
-        svg_parts.push(rect_svg(&diagram_bounds, &style.color.background_color));
 
+        let serialized_content = serialize(&SerializableAppState {
+            version: SCHEMA_VERSION,
+            diagram: self,
+            style: style,
+        });
+
+        let mut dot_definitions = DotDefinitions::new();
         for path in self.paths.iter() {
-            svg_parts.push(translate_svg(
-                &path.as_svg_fragment(style),
-                diagram_center.x,
-                diagram_center.y,
-            ));
+            for dot in path.get_dot_styles(style) {
+                dot_definitions.add(dot);
+            }
         }
+        let mut root = svg_root(&diagram_bounds)
+            .attr("xmlns:xlink", "http://www.w3.org/1999/xlink")
+            .attr("xmlns:dots", DOTS_NAMESPACE);
+        if let Some(accessibility) = &self.accessibility {
+            // the title has to be the first child for screen readers to
+            // find it
+            root = root
+                .attr("role", "img")
+                .attr("aria-labelledby", format!("{} {}", TITLE_ID, DESC_ID))
+                .attr("lang", accessibility.lang.as_str())
+                .child(
+                    SvgElement::new("title")
+                        .attr("id", TITLE_ID)
+                        .text(&accessibility.title),
+                )
+                .child(
+                    SvgElement::new("desc")
+                        .attr("id", DESC_ID)
+                        .text(&self.get_description()),
+                );
+        }
+        // metadata survives being re-saved by editors that drop unknown
+        // attributes
+        root = root
+            .child(
+                SvgElement::new("metadata")
+                    .child(SvgElement::new("dots:config").text(&serialized_content)),
+            )
+            .child(dot_definitions.as_defs_element());
+        if animation == Some(&AnimationMode::Css) {
+            root = root.child(css_keyframes());
+        }
+        if draw_background {
+            root = root.child(style.background.as_svg_fragment(&BackgroundRenderStyle {
+                colors: &style.color,
+                bounds: diagram_bounds,
+                disk_radius: self.get_background_disk_radius(style),
+            }));
+        }
+        return root.children(self.paths.iter().enumerate().map(|(index, path)| {
+            path.as_svg_fragment(&TextPathRenderStyle {
+                style: style,
+                dot_definitions: &dot_definitions,
+                path_index: index,
+                num_paths: self.paths.len(),
+                animation: animation,
+                describe: self.accessibility.is_some(),
+            })
+        }));
+    }
 
+    /// Renders the diagram as a png, with the serialized app state stored in
+    /// a `dots:config` text chunk
+    pub fn as_png(&self, style: &DrawingStyle, options: &RasterOptions) -> Vec<u8> {
+        let document = self.build_svg(style, !options.transparent_background, None);
         let serialized_content = serialize(&SerializableAppState {
+            version: SCHEMA_VERSION,
             diagram: self,
             style: style,
         });
-        let escaped_serialized_content: String =
-            xml::escape::escape_str_attribute(&serialized_content).to_string();
-
-        return format!(
-            concat!(
-                "<svg ",
-                "xmlns='http://www.w3.org/2000/svg' ",
-                "xmlns:dots='dots' dots:config='{}' ",
-                "viewBox='{} {} {} {}'>{}</svg>",
-            ),
-            escaped_serialized_content,
-            diagram_bounds.x,
-            diagram_bounds.y,
-            diagram_bounds.width,
-            diagram_bounds.height,
-            svg_parts.join("")
+        return encode_png(
+            &rasterize(&document, options),
+            &PngMetadata {
+                dpi: get_view_box(&document).and_then(|view_box| options.get_image_dpi(&view_box)),
+                text: vec![("dots:config".to_string(), serialized_content)],
+            },
+        );
+    }
+
+    /// Renders the diagram as a single page pdf, scaled so the largest ring
+    /// prints at `options.diameter_mm`. The serialized app state is stored
+    /// in the `DotsConfig` entry of the document information
+    pub fn as_pdf(&self, style: &DrawingStyle, options: &PdfOptions) -> Vec<u8> {
+        let document = self.build_svg(style, !options.transparent_background, None);
+        let serialized_content = serialize(&SerializableAppState {
+            version: SCHEMA_VERSION,
+            diagram: self,
+            style: style,
+        });
+        let millimeters_per_unit = options.diameter_mm / (self.get_outer_radius(style) * 2.0);
+        return render_pdf(
+            &document,
+            millimeters_per_unit,
+            options,
+            &[
+                ("Title", "Radial Dots"),
+                ("Producer", "radial_dots"),
+                ("DotsConfig", &serialized_content),
+            ],
+        );
+    }
+
+    /// Shapes to plot, in millimeters with y pointing up and the origin at
+    /// the bottom left corner of the diagram. The background is left out
+    fn get_plot_items(&self, style: &DrawingStyle, options: &PlotOptions) -> Vec<DrawItem> {
+        let bounds = self.get_bounding_rect(style);
+        let millimeters_per_unit = options.diameter_mm / (self.get_outer_radius(style) * 2.0);
+        let to_millimeters = Transform2D::translate(-bounds.x, -(bounds.y + bounds.height)).then(
+            &Transform2D::scale(millimeters_per_unit, -millimeters_per_unit),
+        );
+        return walk_document(&self.build_svg(style, false, None), &to_millimeters);
+    }
+
+    fn get_toolpaths(&self, style: &DrawingStyle, options: &PlotOptions) -> Vec<Toolpath> {
+        let groups = get_toolpaths(&self.get_plot_items(style, options), options);
+        if options.optimize_order {
+            return order_toolpaths(groups, &Vector2::zero());
+        }
+        return groups
+            .into_iter()
+            .flat_map(|group| group.into_iter())
+            .collect();
+    }
+
+    /// Renders the diagram as a dxf drawing, for CAD tools
+    pub fn as_dxf(&self, style: &DrawingStyle, options: &PlotOptions) -> String {
+        return render_dxf(&self.get_plot_items(style, options), options.tolerance_mm);
+    }
+
+    /// Renders the diagram as hpgl, for pen plotters
+    pub fn as_hpgl(&self, style: &DrawingStyle, options: &PlotOptions) -> String {
+        return render_hpgl(&self.get_toolpaths(style, options));
+    }
+
+    /// Renders the diagram as g-code, for laser cutters and engravers
+    pub fn as_gcode(
+        &self,
+        style: &DrawingStyle,
+        options: &PlotOptions,
+        gcode_options: &GcodeOptions,
+    ) -> String {
+        return render_gcode(&self.get_toolpaths(style, options), gcode_options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_style::{DrawingColors, DrawingStyle};
+    use crate::fig::diagram::{AccessibilityOptions, BoundingMode, Diagram};
+    use crate::fig::dot::Dot;
+    use crate::fig::text_path::{ArcStyle, TextPath, TextPathStyle};
+    use crate::serializable_app_state::get_state_from_document_string;
+    use crate::svg::output::SvgOutputOptions;
+    use crate::svg::svg_drawable::SvgRenderer;
+
+    #[test]
+    fn test_svg_export() {
+        let dot = Dot {
+            circle_radius: 1.0,
+            ring_radius: 2.0,
+            ring_stroke_width: 0.1,
+            ..Dot::default()
+        };
+        let style = DrawingStyle {
+            color: DrawingColors {
+                stroke_color: "#333333".to_string(),
+                background_color: "#EEEEEE".to_string(),
+            },
+            default_zero_dot_style: dot.clone(),
+            default_one_dot_style: dot,
+            default_arc_style: ArcStyle {
+                radius: 5.0,
+                arc_percentage: 1.0,
+                arc_offset_percentage: 0.0,
+            },
+            default_decoration_style: Default::default(),
+            default_color_style: Default::default(),
+            background: Default::default(),
+            default_animation_style: Default::default(),
+        };
+        let diagram = Diagram {
+            paths: vec!["the quick brown fox", "jumps over", "the lazy dog"]
+                .into_iter()
+                .map(|line| TextPath {
+                    text: line.to_string(),
+                    style: TextPathStyle::default(),
+                })
+                .collect(),
+            diagram_padding: 5.0,
+            bounding_mode: BoundingMode::Tight,
+            accessibility: Some(AccessibilityOptions::default()),
+        };
+
+        // each dot style is defined once and placed with <use>
+        let with_uses = diagram.as_svg_string(&style, &SvgOutputOptions::default());
+        let inlined = diagram.as_svg_string(
+            &style,
+            &SvgOutputOptions {
+                inline_uses: true,
+                ..SvgOutputOptions::default()
+            },
         );
+        assert!(!inlined.contains("<use"));
+        assert!(with_uses.len() * 3 < inlined.len() * 2);
+
+        // the root describes the whole drawing
+        assert!(with_uses.contains("role=\"img\""));
+        assert!(with_uses.contains("aria-labelledby=\"diagram-title diagram-desc\""));
+        assert!(with_uses.contains("<title id=\"diagram-title\">Radial Dots</title>"));
+        assert!(with_uses.contains("the quick brown fox\njumps over\nthe lazy dog</desc>"));
+
+        // the config is read back from the metadata
+        let state = get_state_from_document_string(&with_uses).unwrap();
+        assert_eq!(state.diagram.paths.len(), diagram.paths.len());
     }
 }
@@ -1,61 +1,335 @@
 use crate::float_utils::fmax;
-use crate::geom::{Rect, Vector2};
+use crate::geom::rect::Rect;
+use crate::geom::transform::Transform2D;
+use crate::geom::vector::Vector2;
+use crate::svg::element::SvgElement;
+use crate::svg::parse::parse_fragment;
+use crate::svg::path::PathData;
 use crate::svg::svg_drawable::{SvgFragment, SvgRenderer};
-use crate::svg::util::translate_svg;
+use crate::svg::util::svg_root;
 use serde::{Deserialize, Serialize};
 
+/// Which way a tick dot points, relative to the glyph's up. Ticks on
+/// dots oriented to the ring's normal point radially when `Radial`
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub enum TickDirection {
+    /// Along the glyph's up
+    Radial,
+    /// Across the glyph's up
+    Tangential,
+}
+
+/// How a dot's glyph is turned as it is placed around the ring
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub enum DotOrientation {
+    /// Up is up on the page, wherever the dot is
+    Upright,
+    /// Up points away from the center of the ring
+    Normal,
+    /// Up points along the ring, in the direction the text runs
+    Tangent,
+}
+
+impl Default for DotOrientation {
+    fn default() -> DotOrientation {
+        DotOrientation::Upright
+    }
+}
+
+/// Glyph drawn for a dot
+///
+/// Glyphs are drawn in the dot's local space, centered on (0,0), and turned
+/// according to the dot's orientation. Closed shapes take the fill
+/// color and are outlined in the stroke color when `stroke_width` is set.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub enum DotShape {
+    /// Filled circle of `circle_radius` inside a ring of `ring_radius`
+    CircleAndRing,
+    /// Axis aligned square with its corners `radius` from the center
+    Square { radius: f64, stroke_width: f64 },
+    /// Square standing on a corner, with its corners `radius` from the center
+    Diamond { radius: f64, stroke_width: f64 },
+    /// Regular polygon with a corner pointing up
+    Polygon {
+        sides: u32,
+        radius: f64,
+        stroke_width: f64,
+    },
+    /// Star whose points are `radius` and whose inner corners are
+    /// `inner_radius` from the center
+    Star {
+        points: u32,
+        radius: f64,
+        inner_radius: f64,
+        stroke_width: f64,
+    },
+    /// Straight line centered on the dot, drawn in the stroke color
+    Tick {
+        direction: TickDirection,
+        length: f64,
+        stroke_width: f64,
+    },
+    /// User supplied svg markup, drawn in local space. `radius` is how far
+    /// the markup reaches from the center, as it can't be measured
+    Custom { svg: String, radius: f64 },
+}
+
+impl Default for DotShape {
+    fn default() -> DotShape {
+        DotShape::CircleAndRing
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
 pub struct Dot {
     pub circle_radius: f64,
     pub ring_radius: f64,
     pub ring_stroke_width: f64,
+    #[serde(default)]
+    pub shape: DotShape,
+    #[serde(default)]
+    pub orientation: DotOrientation,
+    /// Extra rotation of the glyph, clockwise, on top of its orientation
+    #[serde(default)]
+    pub rotation_degrees: f64,
+}
+
+pub struct DotRenderStyle<'a> {
+    pub fill_color: &'a str,
+    pub stroke_color: &'a str,
+    /// Maps the dot's local space, where it is centered on (0,0), to the
+    /// space it is drawn in
+    pub transform: Transform2D,
+}
+
+/// The distinct dot styles used in a drawing, each drawn once in `<defs>` and
+/// then placed with `<use>`
+#[derive(Debug, Default)]
+pub struct DotDefinitions {
+    dots: Vec<Dot>,
+}
+
+/// Corners of a shape alternating between `radii`, starting straight up
+fn get_corners(num_corners: u32, radii: &[f64], start_angle: f64) -> Vec<Vector2> {
+    let step = std::f64::consts::PI * 2.0 / num_corners as f64;
+    return (0..num_corners)
+        .map(|index| {
+            Vector2::from_polar(
+                radii[index as usize % radii.len()],
+                start_angle + step * index as f64,
+            )
+        })
+        .collect();
+}
+
+/// Closed shape through `corners`, filled and optionally outlined
+fn closed_shape(corners: &[Vector2], stroke_width: f64) -> SvgElement {
+    let shape = SvgElement::new("path").attr("d", PathData::polygon(corners));
+    if stroke_width > 0.0 {
+        // round joins keep the outline within the bounding radius
+        return shape
+            .attr("stroke-width", stroke_width)
+            .attr("stroke-linejoin", "round");
+    }
+    return shape.attr("stroke", "none");
 }
 
 impl Dot {
     pub fn get_bounding_radius(&self) -> f64 {
-        let ring_radius: f64 = self.ring_radius + self.ring_stroke_width / 2.0;
-        return fmax(&self.circle_radius, &ring_radius);
+        return match &self.shape {
+            DotShape::CircleAndRing => {
+                let ring_radius: f64 = self.ring_radius + self.ring_stroke_width / 2.0;
+                fmax(&self.circle_radius, &ring_radius)
+            }
+            DotShape::Square {
+                radius,
+                stroke_width,
+            }
+            | DotShape::Diamond {
+                radius,
+                stroke_width,
+            }
+            | DotShape::Polygon {
+                radius,
+                stroke_width,
+                ..
+            }
+            | DotShape::Star {
+                radius,
+                stroke_width,
+                ..
+            } => radius + stroke_width / 2.0,
+            DotShape::Tick {
+                length,
+                stroke_width,
+                ..
+            } => f64::hypot(length / 2.0, stroke_width / 2.0),
+            DotShape::Custom { radius, .. } => *radius,
+        };
+    }
+
+    /// Rotation of the glyph, in radians, for a dot at `angle` around the
+    /// ring's center
+    pub fn get_rotation(&self, angle: f64) -> f64 {
+        let quarter_turn = std::f64::consts::FRAC_PI_2;
+        // turns the glyph's up, (0,-1), to point along the orientation
+        let base = match self.orientation {
+            DotOrientation::Upright => 0.0,
+            DotOrientation::Normal => angle + quarter_turn,
+            DotOrientation::Tangent => angle + quarter_turn * 2.0,
+        };
+        return base + self.rotation_degrees.to_radians();
+    }
+
+    /// Error in the glyph's custom svg markup, if it has any
+    pub fn get_shape_error(&self) -> Option<String> {
+        return match &self.shape {
+            DotShape::Custom { svg, .. } => parse_fragment(svg).err(),
+            _ => None,
+        };
+    }
+
+    /// The glyph's elements in local space, without colors
+    fn get_shape_elements(&self) -> Vec<SvgElement> {
+        let up = -std::f64::consts::FRAC_PI_2;
+        return match &self.shape {
+            DotShape::CircleAndRing => {
+                let mut elements = vec![];
+                if self.circle_radius > 0.0 {
+                    elements.push(
+                        SvgElement::new("circle")
+                            .attr("r", self.circle_radius)
+                            .attr("stroke", "none"),
+                    );
+                }
+                if self.ring_radius > 0.0 && self.ring_stroke_width > 0.0 {
+                    elements.push(
+                        SvgElement::new("circle")
+                            .attr("r", self.ring_radius)
+                            .attr("fill", "none")
+                            .attr("stroke-width", self.ring_stroke_width),
+                    );
+                }
+                elements
+            }
+            DotShape::Square {
+                radius,
+                stroke_width,
+            } => vec![closed_shape(
+                &get_corners(4, &[*radius], up + std::f64::consts::FRAC_PI_4),
+                *stroke_width,
+            )],
+            DotShape::Diamond {
+                radius,
+                stroke_width,
+            } => vec![closed_shape(&get_corners(4, &[*radius], up), *stroke_width)],
+            DotShape::Polygon {
+                sides,
+                radius,
+                stroke_width,
+            } => vec![closed_shape(
+                &get_corners((*sides).max(3), &[*radius], up),
+                *stroke_width,
+            )],
+            DotShape::Star {
+                points,
+                radius,
+                inner_radius,
+                stroke_width,
+            } => vec![closed_shape(
+                &get_corners((*points).max(2) * 2, &[*radius, *inner_radius], up),
+                *stroke_width,
+            )],
+            DotShape::Tick {
+                direction,
+                length,
+                stroke_width,
+            } => {
+                let end = match direction {
+                    TickDirection::Radial => Vector2::new(0.0, length / 2.0),
+                    TickDirection::Tangential => Vector2::new(length / 2.0, 0.0),
+                };
+                vec![SvgElement::new("line")
+                    .attr("x1", -end.x)
+                    .attr("y1", -end.y)
+                    .attr("x2", end.x)
+                    .attr("y2", end.y)
+                    .attr("fill", "none")
+                    .attr("stroke-width", *stroke_width)]
+            }
+            // markup that doesn't parse draws nothing, the editor shows
+            // the error instead
+            DotShape::Custom { svg, .. } => match parse_fragment(svg) {
+                Ok(elements) => elements,
+                Err(_) => vec![],
+            },
+        };
+    }
+
+    /// Builds the dot centered on (0,0), for use in `<defs>`
+    ///
+    /// The shapes carry no colors of their own: filled shapes take their
+    /// fill and outlines take their stroke from the `<use>` placing the dot.
+    /// Ids inside custom glyphs are prefixed with `id`, so a glyph used by
+    /// several definitions doesn't repeat them.
+    pub fn as_definition(&self, id: &str) -> SvgElement {
+        return SvgElement::group()
+            .children(self.get_shape_elements())
+            .prefix_ids(&format!("{}-", id))
+            .attr("id", id);
     }
 }
 
-impl SvgFragment<&str> for Dot {
-    fn as_svg_fragment(&self, style: &&str) -> String {
-        format!(
-            concat!(
-                "<circle r=\"{circle_radius}\" fill=\"{stroke_color}\" />",
-                "<circle r=\"{ring_radius}\" ",
-                "fill=\"transparent\" ",
-                "stroke=\"{stroke_color}\" ",
-                "stroke-width=\"{ring_stroke_width}\" />",
-            ),
-            circle_radius = self.circle_radius,
-            ring_radius = self.ring_radius,
-            ring_stroke_width = self.ring_stroke_width,
-            stroke_color = style,
-        )
+impl DotDefinitions {
+    pub fn new() -> DotDefinitions {
+        return DotDefinitions { dots: vec![] };
+    }
+
+    pub fn add(&mut self, dot: &Dot) {
+        if !self.dots.contains(dot) {
+            self.dots.push(dot.clone());
+        }
+    }
+
+    /// Id of the definition of `dot`, if it has been added
+    pub fn get_id(&self, dot: &Dot) -> Option<String> {
+        return self
+            .dots
+            .iter()
+            .position(|defined| defined == dot)
+            .map(|index| format!("dot-{}", index));
+    }
+
+    /// Builds the `<defs>` element holding every added dot
+    pub fn as_defs_element(&self) -> SvgElement {
+        return SvgElement::new("defs").children(
+            self.dots
+                .iter()
+                .enumerate()
+                .map(|(index, dot)| dot.as_definition(&format!("dot-{}", index))),
+        );
+    }
+}
+
+impl<'a> SvgFragment<DotRenderStyle<'a>> for Dot {
+    fn as_svg_fragment(&self, style: &DotRenderStyle<'a>) -> SvgElement {
+        return SvgElement::group()
+            .attr("transform", style.transform)
+            .attr("fill", style.fill_color)
+            .attr("stroke", style.stroke_color)
+            .children(self.get_shape_elements());
     }
 }
 
 impl<'a> SvgRenderer<&str> for Dot {
-    fn as_standalone_svg(&self, style: &&str) -> String {
-        let radius = self.get_bounding_radius();
-        let bounds: Rect = Rect {
-            x: 0.0,
-            y: 0.0,
-            width: radius * 2.0,
-            height: radius * 2.0,
-        };
-        let center: Vector2 = bounds.center();
-        let base_svg: String = self.as_svg_fragment(style);
-
-        return format!(
-            "<svg xmlns='http://www.w3.org/2000/svg' viewBox='{} {} {} {}'>{}</svg>",
-            bounds.x,
-            bounds.y,
-            bounds.width,
-            bounds.height,
-            translate_svg(&base_svg, center.x, center.y,),
-        );
+    /// Draws the dot as it appears at the top of a ring
+    fn as_standalone_svg(&self, style: &&str) -> SvgElement {
+        let bounds: Rect = Rect::around_point(&Vector2::zero(), self.get_bounding_radius());
+        return svg_root(&bounds).child(self.as_svg_fragment(&DotRenderStyle {
+            fill_color: style,
+            stroke_color: style,
+            transform: Transform2D::rotate(self.get_rotation(-std::f64::consts::FRAC_PI_2)),
+        }));
     }
 }
 
@@ -68,3 +342,133 @@ impl std::fmt::Display for Dot {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::fig::dot::{Dot, DotOrientation, DotRenderStyle, DotShape, TickDirection};
+    use crate::geom::transform::Transform2D;
+    use crate::geom::vector::Vector2;
+    use crate::svg::svg_drawable::SvgFragment;
+
+    #[test]
+    fn test_shape_definitions() {
+        let star = Dot {
+            shape: DotShape::Star {
+                points: 5,
+                radius: 2.0,
+                inner_radius: 1.0,
+                stroke_width: 0.5,
+            },
+            ..Dot::default()
+        };
+        assert_eq!(star.get_bounding_radius(), 2.25);
+        assert!(star
+            .as_definition("dot-0")
+            .to_string()
+            .contains("stroke-linejoin=\"round\""));
+
+        let tick = Dot {
+            shape: DotShape::Tick {
+                direction: TickDirection::Radial,
+                length: 6.0,
+                stroke_width: 8.0,
+            },
+            ..Dot::default()
+        };
+        assert_eq!(tick.get_bounding_radius(), 5.0);
+
+        let custom = Dot {
+            shape: DotShape::Custom {
+                svg: String::from("<rect width=\"1\" height=\"1\"/>"),
+                radius: 1.5,
+            },
+            ..Dot::default()
+        };
+        assert_eq!(
+            custom.as_definition("dot-0").to_string(),
+            "<g id=\"dot-0\"><rect width=\"1\" height=\"1\"/></g>"
+        );
+        let shiny = Dot {
+            shape: DotShape::Custom {
+                svg: String::from(concat!(
+                    "<defs><radialGradient id=\"shine\"/></defs>",
+                    "<circle r=\"1\" fill=\"url(#shine)\"/>"
+                )),
+                radius: 1.0,
+            },
+            ..Dot::default()
+        };
+        assert_eq!(
+            shiny.as_definition("dot-1").to_string(),
+            concat!(
+                "<g id=\"dot-1\"><defs><radialGradient id=\"dot-1-shine\"/></defs>",
+                "<circle r=\"1\" fill=\"url(#dot-1-shine)\"/></g>"
+            )
+        );
+        let broken = Dot {
+            shape: DotShape::Custom {
+                svg: String::from("<rect>"),
+                radius: 1.5,
+            },
+            ..Dot::default()
+        };
+        assert!(broken.get_shape_error().is_some());
+    }
+
+    #[test]
+    fn test_inline_dot_matches_definition() {
+        let dot = Dot {
+            circle_radius: 1.0,
+            ring_radius: 2.0,
+            ring_stroke_width: 0.0,
+            ..Dot::default()
+        };
+        let inline = dot.as_svg_fragment(&DotRenderStyle {
+            fill_color: "#333333",
+            stroke_color: "#333333",
+            transform: Transform2D::translate(1.0, 2.0),
+        });
+        // the ring has no width, so only the circle is drawn
+        assert_eq!(
+            inline.to_string(),
+            concat!(
+                "<g transform=\"translate(1,2)\" fill=\"#333333\" stroke=\"#333333\">",
+                "<circle r=\"1\" stroke=\"none\"/>",
+                "</g>"
+            )
+        );
+        assert_eq!(inline.children, dot.as_definition("dot-0").children);
+    }
+
+    #[test]
+    fn test_orientation() {
+        let up = Vector2::new(0.0, -1.0);
+        let turned =
+            |dot: &Dot, angle: f64| Transform2D::rotate(dot.get_rotation(angle)).apply_vector(&up);
+        let assert_near = |actual: Vector2, expected: Vector2| {
+            assert!(
+                actual.distance(&expected) < 1e-9,
+                "{:?} != {:?}",
+                actual,
+                expected
+            )
+        };
+
+        // a dot on the ring's positive x axis
+        let normal = Dot {
+            orientation: DotOrientation::Normal,
+            ..Dot::default()
+        };
+        assert_near(turned(&normal, 0.0), Vector2::new(1.0, 0.0));
+        let tangent = Dot {
+            orientation: DotOrientation::Tangent,
+            ..Dot::default()
+        };
+        assert_near(turned(&tangent, 0.0), Vector2::new(0.0, 1.0));
+        let offset = Dot {
+            rotation_degrees: 90.0,
+            ..Dot::default()
+        };
+        assert_near(turned(&offset, 0.0), Vector2::new(1.0, 0.0));
+    }
+}
@@ -1,3 +1,7 @@
+pub mod animation;
+pub mod background;
+pub mod color_style;
+pub mod decoration;
 pub mod diagram;
 pub mod dot;
 pub mod text_path;
@@ -1,9 +1,17 @@
 use crate::drawing_style::DrawingStyle;
-use crate::fig::dot::Dot;
+use crate::fig::animation::{AnimationMode, AnimationStyle};
+use crate::fig::color_style::{ColorStyle, DotPosition};
+use crate::fig::decoration::{DecorationRenderStyle, DecorationStyle};
+use crate::fig::dot::{Dot, DotDefinitions, DotRenderStyle};
 use crate::float_utils::fmax;
-use crate::geom::{Rect, Vector2};
+use crate::geom::arc::Arc;
+use crate::geom::rect::{bounding_rect_of_circles, union_all, Rect};
+use crate::geom::transform::Transform2D;
+use crate::geom::vector::Vector2;
+use crate::svg::element::SvgElement;
+use crate::svg::path::PathData;
 use crate::svg::svg_drawable::{SvgFragment, SvgRenderer};
-use crate::svg::util::translate_svg;
+use crate::svg::util::svg_root;
 use crate::utf_to_binary::text_to_binary;
 use serde::{Deserialize, Serialize};
 
@@ -19,69 +27,62 @@ pub struct ArcPreviewStyle<'a> {
     pub color: &'a str,
 }
 
+impl ArcStyle {
+    /// The arc dots are laid out along, centered on (0,0)
+    pub fn get_arc(&self) -> Arc {
+        return Arc {
+            center: Vector2::zero(),
+            radius: self.radius,
+            start_angle: self.arc_offset_percentage * std::f64::consts::PI * 2.0,
+            sweep_angle: self.arc_percentage * std::f64::consts::PI * 2.0,
+        };
+    }
+
+    /// Gets the angle of the point `step` spacings along the arc, when the
+    /// arc is divided into `num_steps` equal spacings
+    pub fn get_step_angle(&self, step: f64, num_steps: usize) -> f64 {
+        return self.get_arc().angle_at(step / num_steps as f64);
+    }
+}
+
 impl<'a> SvgFragment<ArcPreviewStyle<'a>> for ArcStyle {
-    fn as_svg_fragment(&self, style: &ArcPreviewStyle<'a>) -> String {
-        let center_x = 0.0;
-        let center_y = 0.0;
-
-        let start_angle = self.arc_offset_percentage * std::f64::consts::PI * 2.0;
-        let start_x = center_x + style.radius * f64::cos(start_angle);
-        let start_y = center_y + style.radius * f64::sin(start_angle);
-
-        let end_angle =
-            (self.arc_offset_percentage + self.arc_percentage) * std::f64::consts::PI * 2.0;
-        let end_x = center_x + style.radius * f64::cos(end_angle);
-        let end_y = center_y + style.radius * f64::sin(end_angle);
-
-        format!(
-            concat!(
-                "<g stroke=\"{color}\" stroke-width=\"{ring_stroke_width}\" fill=\"none\">",
-                "<path d=\"M{start_x},{start_y} A{radius},{radius} 0 {large_arc},{sweep} {end_x},{end_y}\"/>",
-
-                // "<circle r=\"{point_radius}\" stroke=\"none\" fill=\"{color}\" cx=\"{start_x}\" cy=\"{start_y}\" />",
-                // "<circle r=\"{point_radius}\" stroke=\"none\" fill=\"{color}\" cx=\"{end_x}\" cy=\"{end_y}\" />",
-                // "<circle r=\"{point_radius}\" stroke=\"none\" fill=\"{color}\" cx=\"{center_x}\" cy=\"{center_y}\" />",
-                "</g>",
-            ),
-            color = style.color,
-            ring_stroke_width = 1,
-            start_x = start_x,
-            start_y = start_y,
-            radius = style.radius,
-            // center_x = center_x,
-            // center_y = center_y,
-            // point_radius = 1,
-            end_x = end_x,
-            end_y = end_y,
-            large_arc = if self.arc_percentage >= 0.5 { 1 } else { 0 },
-            sweep = 1,
-        )
+    fn as_svg_fragment(&self, style: &ArcPreviewStyle<'a>) -> SvgElement {
+        return SvgElement::group()
+            .attr("stroke", style.color)
+            .attr("stroke-width", 1.0)
+            .attr("fill", "none")
+            .child(SvgElement::new("path").attr(
+                "d",
+                PathData::from_arc(&Arc {
+                    radius: style.radius,
+                    ..self.get_arc()
+                }),
+            ));
     }
 }
 
 impl<'a> SvgRenderer<ArcPreviewStyle<'a>> for ArcStyle {
-    fn as_standalone_svg(&self, style: &ArcPreviewStyle<'a>) -> String {
-        let bounds: Rect = Rect {
-            x: 0.0,
-            y: 0.0,
-            width: (style.radius + 1.0) * 2.0,
-            height: (style.radius + 1.0) * 2.0,
-        };
-        let center: Vector2 = bounds.center();
-        let base_svg: String = self.as_svg_fragment(style);
-
-        return format!(
-            "<svg xmlns='http://www.w3.org/2000/svg' viewBox='{} {} {} {}'>{}</svg>",
-            bounds.x,
-            bounds.y,
-            bounds.width,
-            bounds.height,
-            translate_svg(&base_svg, center.x, center.y,),
-        );
+    fn as_standalone_svg(&self, style: &ArcPreviewStyle<'a>) -> SvgElement {
+        let bounds: Rect = Rect::around_point(&Vector2::zero(), style.radius + 1.0);
+        return svg_root(&bounds).child(self.as_svg_fragment(style));
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+pub struct TextPathRenderStyle<'a> {
+    pub style: &'a DrawingStyle,
+    /// Dot shapes available to reference with `<use>`. Dots missing from the
+    /// definitions are drawn inline.
+    pub dot_definitions: &'a DotDefinitions,
+    /// Position of the path in the drawing, for palettes that vary by ring
+    pub path_index: usize,
+    pub num_paths: usize,
+    /// Reveal the dots in sequence, for animated exports
+    pub animation: Option<&'a AnimationMode>,
+    /// Label the path with the text it encodes, for screen readers
+    pub describe: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TextPath {
     pub style: TextPathStyle,
     pub text: String,
@@ -92,10 +93,16 @@ pub struct TextPathStyle {
     pub zero_dot_style: Option<Dot>,
     pub one_dot_style: Option<Dot>,
     pub arc_style: Option<ArcStyle>,
+    #[serde(default)]
+    pub decoration_style: Option<DecorationStyle>,
+    #[serde(default)]
+    pub color_style: Option<ColorStyle>,
+    #[serde(default)]
+    pub animation_style: Option<AnimationStyle>,
 }
 
 impl<'style_and_self_lifetime> TextPath {
-    fn get_arc_style(
+    pub fn get_arc_style(
         &'style_and_self_lifetime self,
         style: &'style_and_self_lifetime DrawingStyle,
     ) -> &'style_and_self_lifetime ArcStyle {
@@ -105,7 +112,7 @@ impl<'style_and_self_lifetime> TextPath {
         }
     }
 
-    fn get_zero_dot_style(
+    pub fn get_zero_dot_style(
         &'style_and_self_lifetime self,
         style: &'style_and_self_lifetime DrawingStyle,
     ) -> &'style_and_self_lifetime Dot {
@@ -115,7 +122,7 @@ impl<'style_and_self_lifetime> TextPath {
         }
     }
 
-    fn get_one_dot_style(
+    pub fn get_one_dot_style(
         &'style_and_self_lifetime self,
         style: &'style_and_self_lifetime DrawingStyle,
     ) -> &'style_and_self_lifetime Dot {
@@ -125,60 +132,327 @@ impl<'style_and_self_lifetime> TextPath {
         }
     }
 
+    /// Dot styles this path draws with
+    pub fn get_dot_styles(
+        &'style_and_self_lifetime self,
+        style: &'style_and_self_lifetime DrawingStyle,
+    ) -> Vec<&'style_and_self_lifetime Dot> {
+        return vec![
+            self.get_zero_dot_style(style),
+            self.get_one_dot_style(style),
+        ];
+    }
+
+    fn get_decoration_style(
+        &'style_and_self_lifetime self,
+        style: &'style_and_self_lifetime DrawingStyle,
+    ) -> &'style_and_self_lifetime DecorationStyle {
+        match &self.style.decoration_style {
+            Some(style) => &style,
+            None => &style.default_decoration_style,
+        }
+    }
+
+    fn get_color_style(
+        &'style_and_self_lifetime self,
+        style: &'style_and_self_lifetime DrawingStyle,
+    ) -> &'style_and_self_lifetime ColorStyle {
+        match &self.style.color_style {
+            Some(style) => &style,
+            None => &style.default_color_style,
+        }
+    }
+
+    fn get_animation_style(
+        &'style_and_self_lifetime self,
+        style: &'style_and_self_lifetime DrawingStyle,
+    ) -> &'style_and_self_lifetime AnimationStyle {
+        match &self.style.animation_style {
+            Some(style) => &style,
+            None => &style.default_animation_style,
+        }
+    }
+
     pub fn get_bounding_radius(&self, style: &DrawingStyle) -> f64 {
         let arc_style: &ArcStyle = self.get_arc_style(style);
         let zero_dot_style: &Dot = self.get_zero_dot_style(style);
         let one_dot_style: &Dot = self.get_one_dot_style(style);
+        let decoration_style: &DecorationStyle = self.get_decoration_style(style);
+
+        let dot_extent = fmax(
+            &one_dot_style.get_bounding_radius(),
+            &zero_dot_style.get_bounding_radius(),
+        ) * 2.0;
+
+        return arc_style.radius + fmax(&dot_extent, &decoration_style.get_bounding_extent());
+    }
+
+    /// Center of the `index`th of `num_dots` dots. Dots are spaced evenly,
+    /// with the last dot at the end of the arc
+    fn get_dot_center(&self, arc_style: &ArcStyle, index: usize, num_dots: usize) -> Vector2 {
+        return arc_style
+            .get_arc()
+            .point_at((index + 1) as f64 / num_dots as f64);
+    }
+
+    fn get_text_binary(&self) -> Vec<bool> {
+        match text_to_binary(&self.text) {
+            Some(text_binary) => text_binary,
+            None => vec![],
+        }
+    }
+
+    /// Tight bounds of the dots and decorations of this path, if it draws
+    /// anything
+    pub fn get_bounding_rect(&self, style: &DrawingStyle) -> Option<Rect> {
+        let text_binary = self.get_text_binary();
+        let arc_style = self.get_arc_style(style);
+        let num_dots = text_binary.len();
+
+        let zero_dot_radius = self.get_zero_dot_style(style).get_bounding_radius();
+        let one_dot_radius = self.get_one_dot_style(style).get_bounding_radius();
+
+        let dot_circles: Vec<(Vector2, f64)> = text_binary
+            .iter()
+            .enumerate()
+            .map(|(index, current)| {
+                (
+                    self.get_dot_center(arc_style, index, num_dots),
+                    if *current {
+                        one_dot_radius
+                    } else {
+                        zero_dot_radius
+                    },
+                )
+            })
+            .collect();
+
+        let decoration_rect =
+            self.get_decoration_style(style)
+                .get_bounding_rect(&DecorationRenderStyle {
+                    arc_style: arc_style,
+                    num_dots: num_dots,
+                    stroke_color: &style.color.stroke_color,
+                });
 
-        return arc_style.radius
-            + fmax(
-                &one_dot_style.get_bounding_radius(),
-                &zero_dot_style.get_bounding_radius(),
-            ) * 2.0;
+        return union_all(vec![
+            bounding_rect_of_circles(&dot_circles),
+            decoration_rect,
+        ]);
     }
 }
 
-impl SvgFragment<DrawingStyle> for TextPath {
+impl<'a> SvgFragment<TextPathRenderStyle<'a>> for TextPath {
     /// Builds an svg for the text path
     ///
-    /// The text path is radial and centered on the point (0,0)
-    fn as_svg_fragment(&self, style: &DrawingStyle) -> String {
-        let mut text_binary = match text_to_binary(&self.text) {
-            Some(text_binary) => text_binary,
-            None => vec![],
-        };
+    /// The text path is radial and centered on the point (0,0). Decorations
+    /// are drawn first, so that they sit beneath the dots.
+    fn as_svg_fragment(&self, render_style: &TextPathRenderStyle<'a>) -> SvgElement {
+        let style: &DrawingStyle = render_style.style;
+        let mut text_binary = self.get_text_binary();
 
-        let zero_dot_string: String = self
-            .get_zero_dot_style(style)
-            .as_svg_fragment(&style.color.stroke_color.as_str());
-        let one_dot_string: String = self
-            .get_one_dot_style(style)
-            .as_svg_fragment(&style.color.stroke_color.as_str());
+        let zero_dot_style: &Dot = self.get_zero_dot_style(style);
+        let one_dot_style: &Dot = self.get_one_dot_style(style);
+        let zero_dot_id = render_style.dot_definitions.get_id(zero_dot_style);
+        let one_dot_id = render_style.dot_definitions.get_id(one_dot_style);
 
-        let mut dots: Vec<String> = Vec::with_capacity(text_binary.len());
+        let mut dots: Vec<SvgElement> = Vec::with_capacity(text_binary.len());
         let arc_style = self.get_arc_style(style);
 
-        let initial_angle = arc_style.arc_offset_percentage * std::f64::consts::PI * 2.0;
-        let arc_range_angle = arc_style.arc_percentage * std::f64::consts::PI * 2.0;
         let num_dots = text_binary.len();
 
+        let color_style = self.get_color_style(style);
+        let animation_style = self.get_animation_style(style);
+        let path_color: String = color_style.get_path_color(
+            &style.color.stroke_color,
+            render_style.path_index,
+            render_style.num_paths,
+        );
+
+        let decorations: SvgElement =
+            self.get_decoration_style(style)
+                .as_svg_fragment(&DecorationRenderStyle {
+                    arc_style: arc_style,
+                    num_dots: num_dots,
+                    stroke_color: &path_color,
+                });
+
         for (index, current) in text_binary.iter_mut().enumerate() {
-            let dot: &str = if *current {
-                &one_dot_string
+            let (dot, dot_id): (&Dot, &Option<String>) = if *current {
+                (one_dot_style, &one_dot_id)
             } else {
-                &zero_dot_string
+                (zero_dot_style, &zero_dot_id)
             };
 
-            let arc_percent = (index + 1) as f64 / (num_dots) as f64;
-            let angle = initial_angle + arc_percent * arc_range_angle;
+            // per-bit overrides win over the palette, which wins over the
+            // path color set on the enclosing group
+            let dot_color = color_style.get_dot_color(
+                &style.color.stroke_color,
+                &DotPosition {
+                    dot_index: index,
+                    num_dots: num_dots,
+                    path_index: render_style.path_index,
+                    num_paths: render_style.num_paths,
+                },
+            );
+            let dot_colors = color_style.get_dot_colors(*current);
+            let fill_color: &str = match &dot_colors.fill {
+                Some(fill) => fill,
+                None => &dot_color,
+            };
+            let stroke_color: &str = match &dot_colors.stroke {
+                Some(stroke) => stroke,
+                None => &dot_color,
+            };
 
-            let x = arc_style.radius * f64::cos(angle);
-            let y = arc_style.radius * f64::sin(angle);
+            let center = self.get_dot_center(arc_style, index, num_dots);
+            let rotation = dot.get_rotation(center.angle());
+            let placement =
+                Transform2D::rotate(rotation).then(&Transform2D::translate(center.x, center.y));
+            let dot_element = match dot_id {
+                Some(id) => {
+                    let mut dot_use = SvgElement::new("use").attr("xlink:href", format!("#{}", id));
+                    dot_use = if rotation == 0.0 {
+                        dot_use.attr("x", center.x).attr("y", center.y)
+                    } else {
+                        dot_use.attr("transform", placement)
+                    };
+                    if fill_color != path_color {
+                        dot_use = dot_use.attr("fill", fill_color);
+                    }
+                    if stroke_color != path_color {
+                        dot_use = dot_use.attr("stroke", stroke_color);
+                    }
+                    dot_use
+                }
+                None => dot.as_svg_fragment(&DotRenderStyle {
+                    fill_color: fill_color,
+                    stroke_color: stroke_color,
+                    transform: placement,
+                }),
+            };
+            dots.push(match render_style.animation {
+                Some(mode) => animation_style.animate_dot(dot_element, index, mode),
+                None => dot_element,
+            });
+        }
 
-            let moved_dot = translate_svg(&dot, x, y);
-            dots.push(moved_dot);
+        let mut path_group = SvgElement::group();
+        if render_style.describe {
+            path_group = path_group
+                .attr("role", "group")
+                .child(SvgElement::new("desc").text(&self.text));
         }
+        return path_group.child(decorations).child(
+            SvgElement::group()
+                .attr("fill", path_color.as_str())
+                .attr("stroke", path_color.as_str())
+                .children(dots),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_style::{DrawingColors, DrawingStyle};
+    use crate::fig::animation::AnimationMode;
+    use crate::fig::color_style::{ColorStyle, DotColors, Palette};
+    use crate::fig::dot::{Dot, DotDefinitions};
+    use crate::fig::text_path::{ArcStyle, TextPath, TextPathRenderStyle, TextPathStyle};
+    use crate::svg::output::SvgOutputOptions;
+    use crate::svg::svg_drawable::SvgFragment;
 
-        return dots.join("");
+    fn test_style() -> DrawingStyle {
+        let dot = Dot {
+            circle_radius: 1.0,
+            ring_radius: 2.0,
+            ring_stroke_width: 0.1,
+            ..Dot::default()
+        };
+        return DrawingStyle {
+            color: DrawingColors {
+                stroke_color: "#333333".to_string(),
+                background_color: "#EEEEEE".to_string(),
+            },
+            default_zero_dot_style: dot.clone(),
+            default_one_dot_style: dot,
+            default_arc_style: ArcStyle {
+                radius: 5.0,
+                arc_percentage: 1.0,
+                arc_offset_percentage: 0.0,
+            },
+            default_decoration_style: Default::default(),
+            default_color_style: Default::default(),
+            background: Default::default(),
+            default_animation_style: Default::default(),
+        };
+    }
+
+    #[test]
+    fn test_describe_and_animate_path() {
+        let style = test_style();
+        let definitions = DotDefinitions::new();
+        let path = TextPath {
+            text: "hi".to_string(),
+            style: TextPathStyle::default(),
+        };
+        let svg = SvgOutputOptions::default().render(&path.as_svg_fragment(&TextPathRenderStyle {
+            style: &style,
+            dot_definitions: &definitions,
+            path_index: 0,
+            num_paths: 1,
+            animation: Some(&AnimationMode::Smil),
+            describe: true,
+        }));
+        assert!(svg.starts_with("<g role=\"group\"><desc>hi</desc>"));
+        // one animation per bit of the text
+        assert_eq!(svg.matches("<animate ").count(), 16);
+    }
+
+    #[test]
+    fn test_per_path_and_per_bit_colors() {
+        let mut style = test_style();
+        style.default_color_style = ColorStyle {
+            palette: Palette::ByRing {
+                from: "#ff0000".to_string(),
+                to: "#0000ff".to_string(),
+            },
+            ..ColorStyle::default()
+        };
+        let definitions = DotDefinitions::new();
+        let render_path = |path: &TextPath, path_index: usize| {
+            SvgOutputOptions::default().render(&path.as_svg_fragment(&TextPathRenderStyle {
+                style: &style,
+                dot_definitions: &definitions,
+                path_index: path_index,
+                num_paths: 3,
+                animation: None,
+                describe: false,
+            }))
+        };
+        let plain = TextPath {
+            text: "a".to_string(),
+            style: TextPathStyle::default(),
+        };
+        assert!(render_path(&plain, 0).contains("<g fill=\"#ff0000\" stroke=\"#ff0000\">"));
+        assert!(render_path(&plain, 2).contains("<g fill=\"#0000ff\" stroke=\"#0000ff\">"));
+
+        let overridden = TextPath {
+            text: "a".to_string(),
+            style: TextPathStyle {
+                color_style: Some(ColorStyle {
+                    color: Some("#00ff00".to_string()),
+                    one_dot: DotColors {
+                        fill: Some("#ffff00".to_string()),
+                        stroke: None,
+                    },
+                    ..ColorStyle::default()
+                }),
+                ..TextPathStyle::default()
+            },
+        };
+        let svg = render_path(&overridden, 1);
+        assert!(svg.contains("<g fill=\"#00ff00\" stroke=\"#00ff00\">"));
+        assert!(svg.contains("fill=\"#ffff00\""));
     }
 }
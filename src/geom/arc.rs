@@ -0,0 +1,269 @@
+use crate::geom::rect::{bounding_rect_of_points, Rect};
+use crate::geom::vector::Vector2;
+
+/// A circular arc, starting at `start_angle` and sweeping clockwise (in svg's
+/// y-down space) through `sweep_angle` radians
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Arc {
+    pub center: Vector2,
+    pub radius: f64,
+    pub start_angle: f64,
+    pub sweep_angle: f64,
+}
+
+impl Arc {
+    /// Converts an svg style arc, given by its end points and flags, to a
+    /// centered arc. Radii too small to span the end points are scaled up,
+    /// as svg renderers do. Returns None when the end points coincide.
+    pub fn from_endpoints(
+        from: &Vector2,
+        to: &Vector2,
+        radius: f64,
+        large_arc: bool,
+        sweep: bool,
+    ) -> Option<Arc> {
+        let chord = *to - *from;
+        let chord_length = chord.length();
+        if chord_length == 0.0 {
+            return None;
+        }
+
+        let radius = radius.abs().max(chord_length / 2.0);
+        let offset = f64::sqrt((radius * radius - chord_length * chord_length / 4.0).max(0.0));
+        let normal = chord.perpendicular().normalized();
+        let midpoint = from.lerp(to, 0.5);
+        let center = if large_arc != sweep {
+            midpoint + normal * offset
+        } else {
+            midpoint - normal * offset
+        };
+
+        let full_turn = std::f64::consts::PI * 2.0;
+        let start_angle = (*from - center).angle();
+        let mut sweep_angle = (*to - center).angle() - start_angle;
+        if sweep {
+            while sweep_angle < 0.0 {
+                sweep_angle += full_turn;
+            }
+        } else {
+            while sweep_angle > 0.0 {
+                sweep_angle -= full_turn;
+            }
+        }
+
+        return Some(Arc {
+            center: center,
+            radius: radius,
+            start_angle: start_angle,
+            sweep_angle: sweep_angle,
+        });
+    }
+
+    pub fn get_end_angle(&self) -> f64 {
+        return self.start_angle + self.sweep_angle;
+    }
+
+    pub fn is_full_circle(&self) -> bool {
+        return self.sweep_angle.abs() >= std::f64::consts::PI * 2.0;
+    }
+
+    pub fn point_at_angle(&self, angle: f64) -> Vector2 {
+        return self.center + Vector2::from_polar(self.radius, angle);
+    }
+
+    /// Angle of the point `fraction` of the way along the arc
+    pub fn angle_at(&self, fraction: f64) -> f64 {
+        return self.start_angle + fraction * self.sweep_angle;
+    }
+
+    /// Point `fraction` of the way along the arc
+    pub fn point_at(&self, fraction: f64) -> Vector2 {
+        return self.point_at_angle(self.angle_at(fraction));
+    }
+
+    /// `num_points` points evenly spaced along the arc, including both ends
+    pub fn sample(&self, num_points: usize) -> Vec<Vector2> {
+        if num_points < 2 {
+            return (0..num_points).map(|_| self.point_at(0.0)).collect();
+        }
+        return (0..num_points)
+            .map(|i| self.point_at(i as f64 / (num_points - 1) as f64))
+            .collect();
+    }
+
+    /// Samples the arc so that no point strays more than `tolerance` from the
+    /// true curve
+    pub fn flatten(&self, tolerance: f64) -> Vec<Vector2> {
+        let radius = self.radius.abs();
+        let max_step = if tolerance <= 0.0 || tolerance >= radius {
+            std::f64::consts::PI / 4.0
+        } else {
+            2.0 * f64::acos(1.0 - tolerance / radius)
+        };
+        let segments = f64::ceil(self.sweep_angle.abs() / max_step).max(1.0) as usize;
+        return self.sample(segments + 1);
+    }
+
+    /// Approximates the arc with cubic bezier curves, one per quarter turn
+    /// or less. Each curve is given as its two control points and end point;
+    /// the first starts at `point_at(0.0)`
+    pub fn to_cubic_beziers(&self) -> Vec<[Vector2; 3]> {
+        let quarter_turn = std::f64::consts::PI / 2.0;
+        let num_curves = f64::ceil(self.sweep_angle.abs() / quarter_turn).max(1.0) as usize;
+        let step = self.sweep_angle / num_curves as f64;
+        // distance of the control points from the ends, along the tangents
+        let handle = 4.0 / 3.0 * f64::tan(step / 4.0) * self.radius;
+
+        return (0..num_curves)
+            .map(|i| {
+                let start_angle = self.start_angle + step * i as f64;
+                let end_angle = start_angle + step;
+                let start_tangent = Vector2::from_polar(1.0, start_angle).perpendicular();
+                let end_tangent = Vector2::from_polar(1.0, end_angle).perpendicular();
+                let end = self.point_at_angle(end_angle);
+                [
+                    self.point_at_angle(start_angle) + start_tangent * handle,
+                    end - end_tangent * handle,
+                    end,
+                ]
+            })
+            .collect();
+    }
+
+    pub fn bounding_rect(&self) -> Rect {
+        let quarter_turn = std::f64::consts::PI / 2.0;
+        let (low, high) = if self.sweep_angle < 0.0 {
+            (self.get_end_angle(), self.start_angle)
+        } else {
+            (self.start_angle, self.get_end_angle())
+        };
+        let mut points: Vec<Vector2> = vec![self.point_at_angle(low), self.point_at_angle(high)];
+
+        // the arc reaches its extremes at each axis crossing it passes through
+        let mut crossing = f64::ceil(low / quarter_turn) * quarter_turn;
+        while crossing < high && points.len() < 6 {
+            points.push(self.point_at_angle(crossing));
+            crossing += quarter_turn;
+        }
+
+        return bounding_rect_of_points(&points).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::arc::Arc;
+    use crate::geom::rect::Rect;
+    use crate::geom::vector::Vector2;
+
+    fn assert_rect_near(actual: Rect, expected: Rect) {
+        let epsilon = 1e-9;
+        assert!(
+            (actual.x - expected.x).abs() < epsilon
+                && (actual.y - expected.y).abs() < epsilon
+                && (actual.width - expected.width).abs() < epsilon
+                && (actual.height - expected.height).abs() < epsilon,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_half_circle_arc_bounds() {
+        // lower half of the unit circle (y points down in svg)
+        let arc = Arc {
+            center: Vector2::zero(),
+            radius: 1.0,
+            start_angle: 0.0,
+            sweep_angle: std::f64::consts::PI,
+        };
+        assert_rect_near(
+            arc.bounding_rect(),
+            Rect {
+                x: -1.0,
+                y: 0.0,
+                width: 2.0,
+                height: 1.0,
+            },
+        );
+    }
+
+    #[test]
+    fn test_full_circle_arc_bounds() {
+        let arc = Arc {
+            center: Vector2::new(1.0, 1.0),
+            radius: 2.0,
+            start_angle: 0.3,
+            sweep_angle: std::f64::consts::PI * 2.0,
+        };
+        assert_rect_near(
+            arc.bounding_rect(),
+            Rect {
+                x: -1.0,
+                y: -1.0,
+                width: 4.0,
+                height: 4.0,
+            },
+        );
+    }
+
+    #[test]
+    fn test_from_endpoints() {
+        let arc = Arc::from_endpoints(
+            &Vector2::new(1.0, 0.0),
+            &Vector2::new(0.0, 1.0),
+            1.0,
+            false,
+            true,
+        )
+        .unwrap();
+        assert!(arc.center.distance(&Vector2::zero()) < 1e-9);
+        assert!((arc.sweep_angle - std::f64::consts::PI / 2.0).abs() < 1e-9);
+
+        let large = Arc::from_endpoints(
+            &Vector2::new(1.0, 0.0),
+            &Vector2::new(0.0, 1.0),
+            1.0,
+            true,
+            true,
+        )
+        .unwrap();
+        assert!(large.center.distance(&Vector2::new(1.0, 1.0)) < 1e-9);
+        assert!((large.sweep_angle - std::f64::consts::PI * 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sample_includes_ends() {
+        let arc = Arc {
+            center: Vector2::zero(),
+            radius: 1.0,
+            start_angle: 0.0,
+            sweep_angle: std::f64::consts::PI / 2.0,
+        };
+        let points = arc.sample(3);
+        assert_eq!(points.len(), 3);
+        assert!(points[0].distance(&Vector2::new(1.0, 0.0)) < 1e-9);
+        assert!(points[2].distance(&Vector2::new(0.0, 1.0)) < 1e-9);
+    }
+
+    #[test]
+    fn test_cubic_beziers_follow_arc() {
+        let arc = Arc {
+            center: Vector2::new(1.0, 1.0),
+            radius: 2.0,
+            start_angle: 0.0,
+            sweep_angle: -std::f64::consts::PI * 1.5,
+        };
+        let curves = arc.to_cubic_beziers();
+        assert_eq!(curves.len(), 3);
+
+        let mut start = arc.point_at(0.0);
+        for [control_a, control_b, end] in curves.iter() {
+            let midpoint = (start + *control_a * 3.0 + *control_b * 3.0 + *end) * 0.125;
+            assert!((midpoint.distance(&arc.center) - arc.radius).abs() < 0.001 * arc.radius);
+            start = *end;
+        }
+        assert!(start.distance(&arc.point_at(1.0)) < 1e-9);
+    }
+}
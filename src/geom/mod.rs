@@ -0,0 +1,4 @@
+pub mod arc;
+pub mod rect;
+pub mod transform;
+pub mod vector;
@@ -0,0 +1,168 @@
+use crate::float_utils::{fmax, fmin};
+use crate::geom::vector::Vector2;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn center(&self) -> Vector2 {
+        return Vector2 {
+            x: self.x + self.width / 2.0,
+            y: self.y + self.height / 2.0,
+        };
+    }
+
+    pub fn min(&self) -> Vector2 {
+        return Vector2::new(self.x, self.y);
+    }
+
+    pub fn max(&self) -> Vector2 {
+        return Vector2::new(self.x + self.width, self.y + self.height);
+    }
+
+    /// Rect spanning two opposite corners, in either order
+    pub fn from_corners(a: &Vector2, b: &Vector2) -> Rect {
+        let x = fmin(&a.x, &b.x);
+        let y = fmin(&a.y, &b.y);
+        return Rect {
+            x: x,
+            y: y,
+            width: fmax(&a.x, &b.x) - x,
+            height: fmax(&a.y, &b.y) - y,
+        };
+    }
+
+    /// Square of the given radius, centered on `center`
+    pub fn around_point(center: &Vector2, radius: f64) -> Rect {
+        return Rect {
+            x: center.x - radius,
+            y: center.y - radius,
+            width: radius * 2.0,
+            height: radius * 2.0,
+        };
+    }
+
+    pub fn contains(&self, point: &Vector2) -> bool {
+        return point.x >= self.x
+            && point.y >= self.y
+            && point.x <= self.x + self.width
+            && point.y <= self.y + self.height;
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        return Rect::from_corners(
+            &Vector2::new(fmin(&self.x, &other.x), fmin(&self.y, &other.y)),
+            &Vector2::new(
+                fmax(&self.max().x, &other.max().x),
+                fmax(&self.max().y, &other.max().y),
+            ),
+        );
+    }
+
+    /// The overlapping area of two rects, if they overlap
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let min = Vector2::new(fmax(&self.x, &other.x), fmax(&self.y, &other.y));
+        let max = Vector2::new(
+            fmin(&self.max().x, &other.max().x),
+            fmin(&self.max().y, &other.max().y),
+        );
+        if min.x > max.x || min.y > max.y {
+            return None;
+        }
+        return Some(Rect::from_corners(&min, &max));
+    }
+
+    /// Grows the rect by `amount` on every side
+    pub fn expand(&self, amount: f64) -> Rect {
+        return Rect {
+            x: self.x - amount,
+            y: self.y - amount,
+            width: self.width + amount * 2.0,
+            height: self.height + amount * 2.0,
+        };
+    }
+}
+
+/// Unions a sequence of optional rects, skipping the empty ones
+pub fn union_all<I: IntoIterator<Item = Option<Rect>>>(rects: I) -> Option<Rect> {
+    let mut result: Option<Rect> = None;
+    for rect in rects {
+        result = match (result, rect) {
+            (Some(a), Some(b)) => Some(a.union(&b)),
+            (a, None) => a,
+            (None, b) => b,
+        };
+    }
+    return result;
+}
+
+/// Tight bounds of a set of circles, given as (center, radius) pairs
+pub fn bounding_rect_of_circles(circles: &[(Vector2, f64)]) -> Option<Rect> {
+    return union_all(
+        circles
+            .iter()
+            .map(|(center, radius)| Some(Rect::around_point(center, *radius))),
+    );
+}
+
+pub fn bounding_rect_of_points(points: &[Vector2]) -> Option<Rect> {
+    return union_all(
+        points
+            .iter()
+            .map(|point| Some(Rect::around_point(point, 0.0))),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::rect::{bounding_rect_of_circles, Rect};
+    use crate::geom::vector::Vector2;
+
+    #[test]
+    fn test_circles_bounds() {
+        assert_eq!(bounding_rect_of_circles(&[]), None);
+        assert_eq!(
+            bounding_rect_of_circles(&[
+                (Vector2 { x: 0.0, y: 0.0 }, 1.0),
+                (Vector2 { x: 4.0, y: 1.0 }, 2.0),
+            ]),
+            Some(Rect {
+                x: -1.0,
+                y: -1.0,
+                width: 7.0,
+                height: 4.0,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 4.0,
+            height: 4.0,
+        };
+        let b = Rect {
+            x: 2.0,
+            y: 3.0,
+            width: 4.0,
+            height: 4.0,
+        };
+        assert_eq!(
+            a.intersection(&b),
+            Some(Rect {
+                x: 2.0,
+                y: 3.0,
+                width: 2.0,
+                height: 1.0,
+            }),
+        );
+        assert_eq!(a.intersection(&b.expand(-1.5)), None);
+    }
+}
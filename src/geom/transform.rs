@@ -0,0 +1,165 @@
+use crate::geom::vector::Vector2;
+
+/// 2D affine transform, laid out like an svg `matrix(a, b, c, d, e, f)`:
+///
+/// ```text
+/// | a c e |
+/// | b d f |
+/// | 0 0 1 |
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Transform2D {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Default for Transform2D {
+    fn default() -> Transform2D {
+        Transform2D::identity()
+    }
+}
+
+impl Transform2D {
+    pub fn identity() -> Transform2D {
+        return Transform2D {
+            a: 1.0,
+            b: 0.0,
+            c: 0.0,
+            d: 1.0,
+            e: 0.0,
+            f: 0.0,
+        };
+    }
+
+    pub fn translate(x: f64, y: f64) -> Transform2D {
+        return Transform2D {
+            e: x,
+            f: y,
+            ..Transform2D::identity()
+        };
+    }
+
+    /// Rotation about the origin by `angle` radians (clockwise in svg's
+    /// y-down space)
+    pub fn rotate(angle: f64) -> Transform2D {
+        let cos = f64::cos(angle);
+        let sin = f64::sin(angle);
+        return Transform2D {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            e: 0.0,
+            f: 0.0,
+        };
+    }
+
+    pub fn scale(x: f64, y: f64) -> Transform2D {
+        return Transform2D {
+            a: x,
+            d: y,
+            ..Transform2D::identity()
+        };
+    }
+
+    /// The transform applying `self`, then `next`
+    pub fn then(&self, next: &Transform2D) -> Transform2D {
+        return Transform2D {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f,
+        };
+    }
+
+    pub fn determinant(&self) -> f64 {
+        return self.a * self.d - self.b * self.c;
+    }
+
+    /// The transform undoing this one, or None if it collapses the plane
+    pub fn invert(&self) -> Option<Transform2D> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        return Some(Transform2D {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det,
+        });
+    }
+
+    pub fn apply(&self, point: &Vector2) -> Vector2 {
+        return Vector2 {
+            x: self.a * point.x + self.c * point.y + self.e,
+            y: self.b * point.x + self.d * point.y + self.f,
+        };
+    }
+
+    /// Applies the transform to a direction, ignoring translation
+    pub fn apply_vector(&self, vector: &Vector2) -> Vector2 {
+        return Vector2 {
+            x: self.a * vector.x + self.c * vector.y,
+            y: self.b * vector.x + self.d * vector.y,
+        };
+    }
+
+    /// Factor lengths are scaled by, assuming the transform scales uniformly
+    pub fn get_scale_factor(&self) -> f64 {
+        return f64::sqrt(self.determinant().abs());
+    }
+
+    /// Rotation (radians) the transform applies to the x axis
+    pub fn get_rotation(&self) -> f64 {
+        return f64::atan2(self.b, self.a);
+    }
+
+    pub fn is_identity(&self) -> bool {
+        return *self == Transform2D::identity();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::transform::Transform2D;
+    use crate::geom::vector::Vector2;
+
+    fn assert_near(actual: Vector2, expected: Vector2) {
+        assert!(
+            actual.distance(&expected) < 1e-9,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_compose_applies_in_order() {
+        let transform = Transform2D::rotate(std::f64::consts::PI / 2.0)
+            .then(&Transform2D::translate(10.0, 0.0));
+        assert_near(
+            transform.apply(&Vector2::new(1.0, 0.0)),
+            Vector2::new(10.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn test_invert_round_trips() {
+        let transform = Transform2D::scale(2.0, 3.0)
+            .then(&Transform2D::rotate(0.7))
+            .then(&Transform2D::translate(-4.0, 5.0));
+        let inverse = transform.invert().unwrap();
+        let point = Vector2::new(1.5, -2.5);
+        assert_near(inverse.apply(&transform.apply(&point)), point);
+        assert_eq!(Transform2D::scale(0.0, 1.0).invert(), None);
+    }
+}
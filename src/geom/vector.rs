@@ -0,0 +1,108 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Vector2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vector2 {
+    pub fn new(x: f64, y: f64) -> Vector2 {
+        return Vector2 { x: x, y: y };
+    }
+
+    pub fn zero() -> Vector2 {
+        return Vector2 { x: 0.0, y: 0.0 };
+    }
+
+    /// Builds the point `radius` away from the origin along `angle` (radians,
+    /// clockwise from the positive x axis in svg's y-down space)
+    pub fn from_polar(radius: f64, angle: f64) -> Vector2 {
+        return Vector2 {
+            x: radius * f64::cos(angle),
+            y: radius * f64::sin(angle),
+        };
+    }
+
+    pub fn length(&self) -> f64 {
+        return f64::sqrt(self.x * self.x + self.y * self.y);
+    }
+
+    pub fn angle(&self) -> f64 {
+        return f64::atan2(self.y, self.x);
+    }
+
+    pub fn dot(&self, other: &Vector2) -> f64 {
+        return self.x * other.x + self.y * other.y;
+    }
+
+    pub fn distance(&self, other: &Vector2) -> f64 {
+        return (*self - *other).length();
+    }
+
+    /// Unit vector in the same direction, or the zero vector for the zero
+    /// vector
+    pub fn normalized(&self) -> Vector2 {
+        let length = self.length();
+        if length == 0.0 {
+            return Vector2::zero();
+        }
+        return *self * (1.0 / length);
+    }
+
+    /// This vector rotated a quarter turn clockwise (in svg's y-down space)
+    pub fn perpendicular(&self) -> Vector2 {
+        return Vector2 {
+            x: -self.y,
+            y: self.x,
+        };
+    }
+
+    pub fn lerp(&self, other: &Vector2, t: f64) -> Vector2 {
+        return *self + (*other - *self) * t;
+    }
+}
+
+impl Add for Vector2 {
+    type Output = Vector2;
+
+    fn add(self, other: Vector2) -> Vector2 {
+        return Vector2 {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        };
+    }
+}
+
+impl Sub for Vector2 {
+    type Output = Vector2;
+
+    fn sub(self, other: Vector2) -> Vector2 {
+        return Vector2 {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        };
+    }
+}
+
+impl Mul<f64> for Vector2 {
+    type Output = Vector2;
+
+    fn mul(self, scale: f64) -> Vector2 {
+        return Vector2 {
+            x: self.x * scale,
+            y: self.y * scale,
+        };
+    }
+}
+
+impl Neg for Vector2 {
+    type Output = Vector2;
+
+    fn neg(self) -> Vector2 {
+        return Vector2 {
+            x: -self.x,
+            y: -self.y,
+        };
+    }
+}
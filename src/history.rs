@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+
+/// Longest gap, in milliseconds, between updates to the same field for them
+/// to be undone together
+const COALESCE_WINDOW_MS: f64 = 1000.0;
+
+/// How an edit is recorded in the history
+#[derive(Debug, PartialEq, Clone)]
+pub enum EditKind {
+    /// One of a stream of updates, like a slider drag or typing. Consecutive
+    /// updates with the same key become one history entry
+    Coalescing(String),
+    /// Always its own history entry
+    Single,
+}
+
+/// Undo and redo stacks of snapshots of some state
+///
+/// Only the most recent `limit` snapshots are kept, so a long session can't
+/// grow the history without bound.
+pub struct History<T> {
+    undo_stack: VecDeque<T>,
+    redo_stack: Vec<T>,
+    limit: usize,
+    /// Key and time of the last coalescing edit, while later edits may still
+    /// merge into it
+    last_edit: Option<(String, f64)>,
+}
+
+impl<T> History<T> {
+    pub fn new(limit: usize) -> History<T> {
+        return History {
+            undo_stack: VecDeque::new(),
+            redo_stack: vec![],
+            limit: limit,
+            last_edit: None,
+        };
+    }
+
+    pub fn can_undo(&self) -> bool {
+        return !self.undo_stack.is_empty();
+    }
+
+    pub fn can_redo(&self) -> bool {
+        return !self.redo_stack.is_empty();
+    }
+
+    /// Records `before`, the state from just before an edit made at
+    /// `now_ms`. Edits that continue the previous one are skipped, since
+    /// undoing should jump back past all of them at once
+    pub fn record(&mut self, before: T, kind: EditKind, now_ms: f64) {
+        let continues_last_edit = match (&kind, &self.last_edit) {
+            (EditKind::Coalescing(key), Some((last_key, last_ms))) => {
+                key == last_key && now_ms - last_ms <= COALESCE_WINDOW_MS
+            }
+            _ => false,
+        };
+        self.last_edit = match kind {
+            EditKind::Coalescing(key) => Some((key, now_ms)),
+            EditKind::Single => None,
+        };
+        if continues_last_edit {
+            return;
+        }
+
+        self.redo_stack.clear();
+        self.undo_stack.push_back(before);
+        while self.undo_stack.len() > self.limit {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Steps back, returning the state to restore. `current` becomes
+    /// available to redo
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.undo_stack.pop_back()?;
+        self.redo_stack.push(current);
+        self.last_edit = None;
+        return Some(previous);
+    }
+
+    /// Steps forward again after an undo, returning the state to restore
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo_stack.pop()?;
+        self.undo_stack.push_back(current);
+        self.last_edit = None;
+        return Some(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::{EditKind, History};
+
+    #[test]
+    fn test_coalesces_and_bounds_history() {
+        let mut history: History<u32> = History::new(3);
+        let slider = || EditKind::Coalescing(String::from("radius"));
+
+        // a drag across three values is one step
+        history.record(0, slider(), 0.0);
+        history.record(1, slider(), 100.0);
+        history.record(2, slider(), 200.0);
+        history.record(3, EditKind::Single, 300.0);
+        assert_eq!(history.undo(4), Some(3));
+        assert_eq!(history.undo(3), Some(0));
+        assert!(!history.can_undo());
+        assert_eq!(history.redo(0), Some(3));
+        assert_eq!(history.redo(3), Some(4));
+        assert_eq!(history.redo(4), None);
+
+        // coming back to the slider later is a new step
+        history.record(4, slider(), 5000.0);
+        history.record(5, slider(), 5100.0);
+        assert_eq!(history.undo(6), Some(4));
+
+        // only the newest entries are kept
+        for value in 10..20 {
+            history.record(value, EditKind::Single, 6000.0);
+        }
+        assert_eq!(history.undo(20), Some(19));
+        assert_eq!(history.undo(19), Some(18));
+        assert_eq!(history.undo(18), Some(17));
+        assert_eq!(history.undo(17), None);
+    }
+}
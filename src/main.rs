@@ -1,12 +1,20 @@
 extern crate wee_alloc;
 
+mod color;
 mod components;
 mod drawing_style;
 mod fig;
 mod float_utils;
 mod geom;
+mod history;
+mod migrations;
+mod pdf;
+mod plot;
+mod presets;
+mod raster;
 mod serializable_app_state;
 mod svg;
+mod url_state;
 mod utf_to_binary;
 use crate::components::app::App;
 
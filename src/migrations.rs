@@ -0,0 +1,119 @@
+use crate::serializable_app_state::DocumentError;
+use serde_json::Value;
+
+/// Version of the `dots:config` layout this build writes. Bump it, and add
+/// a migration from the previous version, whenever a change to the
+/// serialized state would stop older documents from loading as they looked
+/// when they were saved
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`
+///
+/// Fields whose `#[serde(default)]` already draws an older document the way
+/// it looked need no migration. At version 1 these are the dot `shape`,
+/// `orientation` and `rotation_degrees`, the decoration, color, background
+/// and animation styles, and the diagram's `accessibility`, which all
+/// default to what unversioned documents drew. `bounding_mode` is migrated
+/// because its default changed what old documents look like.
+const MIGRATIONS: [fn(&mut Value); 1] = [migrate_v0_to_v1];
+
+/// Documents from before versioning were always framed as a square around
+/// the largest ring, and had no `version` field
+fn migrate_v0_to_v1(document: &mut Value) {
+    if let Some(diagram) = document.get_mut("diagram").and_then(Value::as_object_mut) {
+        if !diagram.contains_key("bounding_mode") {
+            diagram.insert(
+                String::from("bounding_mode"),
+                Value::String(String::from("CenteredSquare")),
+            );
+        }
+    }
+}
+
+/// Version a document was saved with. Unversioned documents are version 0
+pub fn get_version(document: &Value) -> Result<u64, DocumentError> {
+    return match document.get("version") {
+        None => Ok(0),
+        Some(version) => match version.as_u64() {
+            Some(version) => Ok(version),
+            None => Err(DocumentError::Json {
+                path: String::from("version"),
+                message: format!("expected a whole number, found {}", version),
+            }),
+        },
+    };
+}
+
+/// Upgrades a serialized app state to the current schema version
+///
+/// Errors if the document comes from a newer version of the app, since
+/// there is no way to know what its fields mean.
+pub fn migrate(mut document: Value) -> Result<Value, DocumentError> {
+    let version = get_version(&document)?;
+    if version > SCHEMA_VERSION {
+        return Err(DocumentError::UnsupportedVersion {
+            version: version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+    for migration in MIGRATIONS[version as usize..].iter() {
+        migration(&mut document);
+    }
+    if let Some(fields) = document.as_object_mut() {
+        fields.insert(String::from("version"), Value::from(SCHEMA_VERSION));
+    }
+    return Ok(document);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fig::animation::AnimationStyle;
+    use crate::fig::background::BackgroundStyle;
+    use crate::fig::color_style::ColorStyle;
+    use crate::fig::decoration::DecorationStyle;
+    use crate::fig::diagram::BoundingMode;
+    use crate::fig::dot::{DotOrientation, DotShape};
+    use crate::migrations::{migrate, SCHEMA_VERSION};
+    use crate::serializable_app_state::{get_state_from_document_string, DocumentError};
+
+    #[test]
+    fn test_loads_fixture_from_each_version() {
+        let v0 = get_state_from_document_string(include_str!("fixtures/v0.svg")).unwrap();
+        assert_eq!(v0.diagram.paths.len(), 2);
+        assert_eq!(v0.diagram.paths[1].text, "world");
+        assert_eq!(v0.diagram.bounding_mode, BoundingMode::CenteredSquare);
+        assert_eq!(v0.style.default_arc_style.radius, 5.0);
+        // fields added since then take defaults that draw it as before
+        let dot = &v0.style.default_zero_dot_style;
+        assert_eq!(dot.shape, DotShape::CircleAndRing);
+        assert_eq!(dot.orientation, DotOrientation::Upright);
+        assert_eq!(dot.rotation_degrees, 0.0);
+        assert_eq!(
+            v0.style.default_decoration_style,
+            DecorationStyle::default()
+        );
+        assert_eq!(v0.style.default_color_style, ColorStyle::default());
+        assert_eq!(v0.style.background, BackgroundStyle::default());
+        assert_eq!(v0.style.default_animation_style, AnimationStyle::default());
+        assert_eq!(v0.diagram.accessibility, None);
+        assert_eq!(v0.diagram.paths[0].style.color_style, None);
+        assert_eq!(v0.diagram.paths[0].style.animation_style, None);
+
+        let v1 = get_state_from_document_string(include_str!("fixtures/v1.svg")).unwrap();
+        assert_eq!(v1.diagram.paths[0].text, "hi");
+        assert_eq!(v1.diagram.bounding_mode, BoundingMode::Tight);
+    }
+
+    #[test]
+    fn test_rejects_newer_documents() {
+        let document = serde_json::json!({ "version": SCHEMA_VERSION + 1 });
+        assert_eq!(
+            migrate(document),
+            Err(DocumentError::UnsupportedVersion {
+                version: SCHEMA_VERSION + 1,
+                supported: SCHEMA_VERSION,
+            })
+        );
+        assert!(migrate(serde_json::json!({ "version": "one" })).is_err());
+    }
+}
@@ -0,0 +1,441 @@
+use crate::color::Rgba;
+use crate::geom::arc::Arc;
+use crate::geom::transform::Transform2D;
+use crate::geom::vector::Vector2;
+use crate::pdf::writer::{encode_text_string, format_pdf_number, PdfWriter};
+use crate::svg::element::SvgElement;
+use crate::svg::walk::{
+    get_view_box, walk_document, DrawItem, Gradient, GradientGeometry, Segment, Shape,
+};
+
+const POINTS_PER_MILLIMETER: f64 = 72.0 / 25.4;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PdfColorMode {
+    Rgb,
+    /// Colors converted to process inks, for print shops that reject rgb
+    Cmyk,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PdfOptions {
+    /// Printed diameter of the largest ring, dots included, in millimeters
+    pub diameter_mm: f64,
+    /// Page width and height in millimeters, with the diagram centered on
+    /// it. None sizes the page to fit the diagram
+    pub page_size_mm: Option<(f64, f64)>,
+    pub color_mode: PdfColorMode,
+    /// Leave out the background color
+    pub transparent_background: bool,
+}
+
+impl Default for PdfOptions {
+    fn default() -> PdfOptions {
+        PdfOptions {
+            diameter_mm: 100.0,
+            page_size_mm: None,
+            color_mode: PdfColorMode::Cmyk,
+            transparent_background: false,
+        }
+    }
+}
+
+fn format_point(point: &Vector2) -> String {
+    return format!(
+        "{} {}",
+        format_pdf_number(point.x),
+        format_pdf_number(point.y)
+    );
+}
+
+/// Operator setting the fill (or stroke) color
+fn color_operator(color: &Rgba, color_mode: &PdfColorMode, stroke: bool) -> String {
+    return match color_mode {
+        PdfColorMode::Rgb => format!(
+            "{} {} {} {}",
+            format_pdf_number(color.r),
+            format_pdf_number(color.g),
+            format_pdf_number(color.b),
+            if stroke { "RG" } else { "rg" }
+        ),
+        PdfColorMode::Cmyk => {
+            let (c, m, y, k) = color.to_cmyk();
+            format!(
+                "{} {} {} {} {}",
+                format_pdf_number(c),
+                format_pdf_number(m),
+                format_pdf_number(y),
+                format_pdf_number(k),
+                if stroke { "K" } else { "k" }
+            )
+        }
+    };
+}
+
+/// Color components, without the operator
+fn color_components(color: &Rgba, color_mode: &PdfColorMode) -> String {
+    let components = match color_mode {
+        PdfColorMode::Rgb => vec![color.r, color.g, color.b],
+        PdfColorMode::Cmyk => {
+            let (c, m, y, k) = color.to_cmyk();
+            vec![c, m, y, k]
+        }
+    };
+    let formatted: Vec<String> = components.into_iter().map(format_pdf_number).collect();
+    return formatted.join(" ");
+}
+
+/// Shading dictionary drawing `gradient`. Stop opacities are not carried
+/// over
+fn shading_dictionary(gradient: &Gradient, color_mode: &PdfColorMode) -> String {
+    // pad the stops out to cover the whole domain of the function
+    let mut stops = gradient.stops.clone();
+    if let Some(first) = stops.first().cloned() {
+        if first.0 > 0.0 {
+            stops.insert(0, (0.0, first.1));
+        }
+    }
+    if let Some(last) = stops.last().cloned() {
+        if last.0 < 1.0 || stops.len() == 1 {
+            stops.push((1.0, last.1));
+        }
+    }
+
+    // one linear interpolation per pair of stops, stitched together
+    let functions: Vec<String> = stops
+        .windows(2)
+        .map(|pair| {
+            format!(
+                "<< /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>",
+                color_components(&pair[0].1, color_mode),
+                color_components(&pair[1].1, color_mode)
+            )
+        })
+        .collect();
+    let bounds: Vec<String> = stops[1..stops.len() - 1]
+        .iter()
+        .map(|stop| format_pdf_number(stop.0))
+        .collect();
+    let encode: Vec<&str> = functions.iter().map(|_| "0 1").collect();
+    let function = format!(
+        "<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds [{}] /Encode [{}] >>",
+        functions.join(" "),
+        bounds.join(" "),
+        encode.join(" ")
+    );
+
+    let color_space = match color_mode {
+        PdfColorMode::Rgb => "/DeviceRGB",
+        PdfColorMode::Cmyk => "/DeviceCMYK",
+    };
+    let (shading_type, coords) = match &gradient.geometry {
+        GradientGeometry::Linear { start, end } => {
+            (2, format!("{} {}", format_point(start), format_point(end)))
+        }
+        GradientGeometry::Radial { center, radius } => (
+            3,
+            format!(
+                "{} 0 {} {}",
+                format_point(center),
+                format_point(center),
+                format_pdf_number(*radius)
+            ),
+        ),
+    };
+    return format!(
+        "<< /ShadingType {} /ColorSpace {} /Coords [{}] /Function {} /Extend [true true] >>",
+        shading_type, color_space, coords, function
+    );
+}
+
+fn write_arc(content: &mut String, arc: &Arc) {
+    for [control_a, control_b, end] in arc.to_cubic_beziers().iter() {
+        content.push_str(&format!(
+            "{} {} {} c\n",
+            format_point(control_a),
+            format_point(control_b),
+            format_point(end)
+        ));
+    }
+}
+
+fn write_shape(content: &mut String, shape: &Shape) {
+    match shape {
+        Shape::Circle { center, radius } => {
+            let circle = Arc {
+                center: *center,
+                radius: *radius,
+                start_angle: 0.0,
+                sweep_angle: std::f64::consts::PI * 2.0,
+            };
+            content.push_str(&format!("{} m\n", format_point(&circle.point_at(0.0))));
+            write_arc(content, &circle);
+            content.push_str("h\n");
+        }
+        Shape::Path(subpaths) => {
+            for subpath in subpaths.iter() {
+                content.push_str(&format!("{} m\n", format_point(&subpath.start)));
+                for segment in subpath.segments.iter() {
+                    match segment {
+                        Segment::Line(point) => {
+                            content.push_str(&format!("{} l\n", format_point(point)))
+                        }
+                        Segment::Arc(arc) => write_arc(content, arc),
+                    }
+                }
+                if subpath.closed {
+                    content.push_str("h\n");
+                }
+            }
+        }
+    }
+}
+
+/// Graphics states for each distinct pair of fill and stroke opacities
+struct OpacityStates {
+    opacities: Vec<(f64, f64)>,
+}
+
+impl OpacityStates {
+    /// Name of the graphics state with the given opacities
+    fn get_name(&mut self, fill_opacity: f64, stroke_opacity: f64) -> String {
+        let index = match self
+            .opacities
+            .iter()
+            .position(|opacities| *opacities == (fill_opacity, stroke_opacity))
+        {
+            Some(index) => index,
+            None => {
+                self.opacities.push((fill_opacity, stroke_opacity));
+                self.opacities.len() - 1
+            }
+        };
+        return format!("GS{}", index);
+    }
+}
+
+fn write_item(
+    content: &mut String,
+    item: &DrawItem,
+    color_mode: &PdfColorMode,
+    opacity_states: &mut OpacityStates,
+    shadings: &mut Vec<String>,
+) {
+    let paint = &item.paint;
+    let stroke = match paint.stroke {
+        Some(stroke) if paint.stroke_width > 0.0 => Some(stroke),
+        _ => None,
+    };
+    let mut fill = paint.fill;
+    match &paint.fill_gradient {
+        // as in svg, a gradient without stops paints nothing
+        Some(gradient) if gradient.stops.is_empty() => fill = None,
+        _ => {}
+    }
+    if let (Some(gradient), Some(_)) = (&paint.fill_gradient, fill) {
+        // gradients are painted through the shape as a clipping path
+        content.push_str("q\n");
+        if paint.opacity < 1.0 {
+            content.push_str(&format!(
+                "/{} gs\n",
+                opacity_states.get_name(paint.opacity, 1.0)
+            ));
+        }
+        write_shape(content, &item.shape);
+        shadings.push(shading_dictionary(gradient, color_mode));
+        content.push_str(&format!("W n\n/Sh{} sh\nQ\n", shadings.len() - 1));
+        fill = None;
+    }
+    let operator = match (fill, stroke) {
+        (Some(_), Some(_)) => "B",
+        (Some(_), None) => "f",
+        (None, Some(_)) => "S",
+        (None, None) => return,
+    };
+
+    content.push_str("q\n");
+    let fill_opacity = fill.map_or(1.0, |fill| fill.a) * paint.opacity;
+    let stroke_opacity = stroke.map_or(1.0, |stroke| stroke.a) * paint.opacity;
+    if fill_opacity < 1.0 || stroke_opacity < 1.0 {
+        content.push_str(&format!(
+            "/{} gs\n",
+            opacity_states.get_name(fill_opacity, stroke_opacity)
+        ));
+    }
+    if let Some(fill) = fill {
+        content.push_str(&color_operator(&fill, color_mode, false));
+        content.push('\n');
+    }
+    if let Some(stroke) = stroke {
+        content.push_str(&color_operator(&stroke, color_mode, true));
+        content.push_str(&format!("\n{} w\n", format_pdf_number(paint.stroke_width)));
+        if !paint.dash_array.is_empty() {
+            let dashes: Vec<String> = paint
+                .dash_array
+                .iter()
+                .map(|length| format_pdf_number(*length))
+                .collect();
+            content.push_str(&format!("[{}] 0 d\n", dashes.join(" ")));
+        }
+    }
+    write_shape(content, &item.shape);
+    content.push_str(operator);
+    content.push_str("\nQ\n");
+}
+
+/// Draws an svg document onto a single pdf page, at `millimeters_per_unit`
+/// millimeters per unit of its view box. `info` entries are added to the
+/// document information dictionary.
+pub fn render_pdf(
+    document: &SvgElement,
+    millimeters_per_unit: f64,
+    options: &PdfOptions,
+    info: &[(&str, &str)],
+) -> Vec<u8> {
+    let view_box = match get_view_box(document) {
+        Some(view_box) => view_box,
+        None => panic!("pdf export requires a document with a viewBox"),
+    };
+    let scale = millimeters_per_unit * POINTS_PER_MILLIMETER;
+    let (page_width, page_height) = match options.page_size_mm {
+        Some((width, height)) => (
+            width * POINTS_PER_MILLIMETER,
+            height * POINTS_PER_MILLIMETER,
+        ),
+        None => (view_box.width * scale, view_box.height * scale),
+    };
+
+    // pdf pages have y pointing up, so the view box is flipped as it is
+    // centered on the page
+    let center = view_box.center();
+    let to_page = Transform2D::translate(-center.x, -center.y)
+        .then(&Transform2D::scale(scale, -scale))
+        .then(&Transform2D::translate(page_width / 2.0, page_height / 2.0));
+
+    let mut content = format!(
+        "{} {} {} {} {} {} cm\n",
+        format_pdf_number(to_page.a),
+        format_pdf_number(to_page.b),
+        format_pdf_number(to_page.c),
+        format_pdf_number(to_page.d),
+        format_pdf_number(to_page.e),
+        format_pdf_number(to_page.f)
+    );
+    let mut opacity_states = OpacityStates { opacities: vec![] };
+    let mut shadings: Vec<String> = vec![];
+    for item in walk_document(document, &Transform2D::identity()).iter() {
+        write_item(
+            &mut content,
+            item,
+            &options.color_mode,
+            &mut opacity_states,
+            &mut shadings,
+        );
+    }
+
+    let mut writer = PdfWriter::new();
+    let catalog = writer.reserve();
+    let pages = writer.reserve();
+    let page = writer.reserve();
+    let contents = writer.reserve();
+    let info_dictionary = writer.reserve();
+
+    writer.write_object(
+        catalog,
+        &format!("<< /Type /Catalog /Pages {} 0 R >>", pages),
+    );
+    writer.write_object(
+        pages,
+        &format!("<< /Type /Pages /Kids [{} 0 R] /Count 1 >>", page),
+    );
+    let graphics_states: Vec<String> = opacity_states
+        .opacities
+        .iter()
+        .enumerate()
+        .map(|(index, (fill_opacity, stroke_opacity))| {
+            format!(
+                "/GS{} << /Type /ExtGState /ca {} /CA {} >>",
+                index,
+                format_pdf_number(*fill_opacity),
+                format_pdf_number(*stroke_opacity)
+            )
+        })
+        .collect();
+    let shading_resources: Vec<String> = shadings
+        .iter()
+        .enumerate()
+        .map(|(index, shading)| format!("/Sh{} {}", index, shading))
+        .collect();
+    writer.write_object(
+        page,
+        &format!(
+            "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Contents {} 0 R /Resources << /ExtGState << {} >> /Shading << {} >> >> >>",
+            pages,
+            format_pdf_number(page_width),
+            format_pdf_number(page_height),
+            contents,
+            graphics_states.join(" "),
+            shading_resources.join(" ")
+        ),
+    );
+    writer.write_stream(contents, content.as_bytes());
+
+    let mut info_entries = String::new();
+    for (key, value) in info.iter() {
+        info_entries.push_str(&format!("/{} {} ", key, encode_text_string(value)));
+    }
+    writer.write_object(info_dictionary, &format!("<< {}>>", info_entries));
+
+    return writer.finish(catalog, info_dictionary);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::rect::Rect;
+    use crate::pdf::render::{render_pdf, PdfOptions};
+    use crate::pdf::writer::encode_text_string;
+    use crate::svg::element::SvgElement;
+    use crate::svg::util::svg_root;
+
+    #[test]
+    fn test_render_pdf() {
+        let gradient = SvgElement::new("radialGradient")
+            .attr("id", "fill")
+            .attr("gradientUnits", "userSpaceOnUse")
+            .attr("r", 5.0)
+            .child(
+                SvgElement::new("stop")
+                    .attr("offset", 0.0)
+                    .attr("stop-color", "#ffffff"),
+            )
+            .child(
+                SvgElement::new("stop")
+                    .attr("offset", 1.0)
+                    .attr("stop-color", "#000000"),
+            );
+        let document = svg_root(&Rect {
+            x: -5.0,
+            y: -5.0,
+            width: 10.0,
+            height: 10.0,
+        })
+        .child(SvgElement::new("defs").child(gradient))
+        .child(
+            SvgElement::new("circle")
+                .attr("r", 5.0)
+                .attr("fill", "url(#fill)"),
+        );
+        let pdf = render_pdf(
+            &document,
+            10.0,
+            &PdfOptions::default(),
+            &[("DotsConfig", "{}")],
+        );
+
+        // 10 units at 10mm each is 100mm in points
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("/MediaBox [0 0 283.4646 283.4646]"));
+        assert!(text.contains("/ShadingType 3"));
+        assert!(text.contains(&format!("/DotsConfig {}", encode_text_string("{}"))));
+    }
+}
@@ -0,0 +1,160 @@
+/// Writes a pdf file object by object, keeping track of where each object
+/// starts for the cross reference table
+pub struct PdfWriter {
+    output: Vec<u8>,
+    /// Byte offset of each object, indexed by object number - 1
+    offsets: Vec<Option<usize>>,
+}
+
+/// Formats a number for a pdf content stream or dictionary. Pdf has no
+/// exponent notation, so numbers are always written out in full
+pub fn format_pdf_number(number: f64) -> String {
+    let mut formatted = format!("{:.4}", number);
+    while formatted.ends_with('0') {
+        formatted.pop();
+    }
+    if formatted.ends_with('.') {
+        formatted.pop();
+    }
+    if formatted == "-0" {
+        formatted = String::from("0");
+    }
+    return formatted;
+}
+
+/// Encodes text as a pdf hex string in UTF-16BE, which can hold any unicode
+/// text and needs no escaping
+pub fn encode_text_string(text: &str) -> String {
+    let mut encoded = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        encoded.push_str(&format!("{:04X}", unit));
+    }
+    encoded.push('>');
+    return encoded;
+}
+
+impl PdfWriter {
+    pub fn new() -> PdfWriter {
+        let mut output: Vec<u8> = b"%PDF-1.4\n".to_vec();
+        // binary comment so transfer tools treat the file as binary
+        output.extend_from_slice(&[b'%', 0xe2, 0xe3, 0xcf, 0xd3, b'\n']);
+        return PdfWriter {
+            output: output,
+            offsets: vec![],
+        };
+    }
+
+    /// Claims an object number, for objects that are referred to before they
+    /// are written
+    pub fn reserve(&mut self) -> usize {
+        self.offsets.push(None);
+        return self.offsets.len();
+    }
+
+    pub fn write_object(&mut self, id: usize, body: &str) {
+        self.begin_object(id);
+        self.output.extend_from_slice(body.as_bytes());
+        self.output.extend_from_slice(b"\nendobj\n");
+    }
+
+    /// Writes a stream object, compressed with deflate
+    pub fn write_stream(&mut self, id: usize, data: &[u8]) {
+        let compressed = miniz_oxide::deflate::compress_to_vec_zlib(data, 9);
+        self.begin_object(id);
+        self.output.extend_from_slice(
+            format!(
+                "<< /Length {} /Filter /FlateDecode >>\nstream\n",
+                compressed.len()
+            )
+            .as_bytes(),
+        );
+        self.output.extend_from_slice(&compressed);
+        self.output.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    fn begin_object(&mut self, id: usize) {
+        self.offsets[id - 1] = Some(self.output.len());
+        self.output
+            .extend_from_slice(format!("{} 0 obj\n", id).as_bytes());
+    }
+
+    /// Writes the cross reference table and trailer. Every reserved object
+    /// must have been written by now
+    pub fn finish(mut self, root: usize, info: usize) -> Vec<u8> {
+        let xref_offset = self.output.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in self.offsets.iter() {
+            match offset {
+                Some(offset) => xref.push_str(&format!("{:010} 00000 n \n", offset)),
+                None => panic!("pdf object reserved but never written"),
+            }
+        }
+        xref.push_str(&format!(
+            "trailer\n<< /Size {} /Root {} 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len() + 1,
+            root,
+            info,
+            xref_offset
+        ));
+        self.output.extend_from_slice(xref.as_bytes());
+        return self.output;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pdf::writer::{encode_text_string, format_pdf_number, PdfWriter};
+
+    /// Reverses `encode_text_string`, given the hex digits between the angle
+    /// brackets
+    fn decode_text_string(hex: &str) -> Option<String> {
+        let digits: Vec<char> = hex.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut units: Vec<u16> = vec![];
+        for chunk in digits.chunks(4) {
+            let chunk: String = chunk.iter().collect();
+            units.push(u16::from_str_radix(&chunk, 16).ok()?);
+        }
+        if units.first() != Some(&0xFEFF) {
+            return None;
+        }
+        return String::from_utf16(&units[1..]).ok();
+    }
+
+    /// Finds the text string stored under `key` in the document information
+    /// dictionary of a pdf written by `PdfWriter`
+    fn read_info_string(pdf: &[u8], key: &str) -> Option<String> {
+        let marker = format!("/{} <", key);
+        let marker = marker.as_bytes();
+        let start = pdf
+            .windows(marker.len())
+            .position(|window| window == marker)?
+            + marker.len();
+        let length = pdf[start..].iter().position(|byte| *byte == b'>')?;
+        let hex = std::str::from_utf8(&pdf[start..start + length]).ok()?;
+        return decode_text_string(hex);
+    }
+
+    #[test]
+    fn test_info_string_round_trip() {
+        let text = "{\"text\":\"ħé(llo)\\\\\"}";
+        let mut writer = PdfWriter::new();
+        let root = writer.reserve();
+        let info = writer.reserve();
+        writer.write_object(root, "<< /Type /Catalog >>");
+        writer.write_object(
+            info,
+            &format!("<< /DotsConfig {} >>", encode_text_string(text)),
+        );
+        let pdf = writer.finish(root, info);
+
+        assert_eq!(read_info_string(&pdf, "DotsConfig"), Some(text.to_string()));
+        assert!(String::from_utf8_lossy(&pdf).contains("xref\n0 3\n"));
+    }
+
+    #[test]
+    fn test_format_pdf_number() {
+        assert_eq!(format_pdf_number(1e-9), "0");
+        assert_eq!(format_pdf_number(-2.5), "-2.5");
+        assert_eq!(format_pdf_number(1e7), "10000000");
+    }
+}
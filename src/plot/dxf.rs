@@ -0,0 +1,134 @@
+use crate::geom::vector::Vector2;
+use crate::svg::walk::{apply_dashes, DrawItem, Segment, Shape};
+
+/// Layer filled shapes are outlined on
+const FILL_LAYER: &str = "FILL";
+/// Layer stroked shapes are drawn on
+const STROKE_LAYER: &str = "STROKE";
+
+fn write_group(output: &mut String, code: u32, value: &str) {
+    output.push_str(&format!("{}\n{}\n", code, value));
+}
+
+fn write_number(output: &mut String, code: u32, value: f64) {
+    write_group(output, code, &format!("{:.4}", value));
+}
+
+fn write_line(output: &mut String, layer: &str, from: &Vector2, to: &Vector2) {
+    write_group(output, 0, "LINE");
+    write_group(output, 8, layer);
+    write_number(output, 10, from.x);
+    write_number(output, 20, from.y);
+    write_number(output, 11, to.x);
+    write_number(output, 21, to.y);
+}
+
+fn write_shape(output: &mut String, layer: &str, shape: &Shape) {
+    match shape {
+        Shape::Circle { center, radius } => {
+            write_group(output, 0, "CIRCLE");
+            write_group(output, 8, layer);
+            write_number(output, 10, center.x);
+            write_number(output, 20, center.y);
+            write_number(output, 40, *radius);
+        }
+        Shape::Path(subpaths) => {
+            for subpath in subpaths.iter() {
+                let mut position = subpath.start;
+                for segment in subpath.segments.iter() {
+                    match segment {
+                        Segment::Line(to) => {
+                            write_line(output, layer, &position, to);
+                            position = *to;
+                        }
+                        Segment::Arc(arc) => {
+                            // dxf arcs run counterclockwise, in degrees
+                            let (start, end) = if arc.sweep_angle < 0.0 {
+                                (arc.get_end_angle(), arc.start_angle)
+                            } else {
+                                (arc.start_angle, arc.get_end_angle())
+                            };
+                            write_group(output, 0, "ARC");
+                            write_group(output, 8, layer);
+                            write_number(output, 10, arc.center.x);
+                            write_number(output, 20, arc.center.y);
+                            write_number(output, 40, arc.radius);
+                            write_number(output, 50, start.to_degrees());
+                            write_number(output, 51, end.to_degrees());
+                            position = arc.point_at(1.0);
+                        }
+                    }
+                }
+                if subpath.closed && position != subpath.start {
+                    write_line(output, layer, &position, &subpath.start);
+                }
+            }
+        }
+    }
+}
+
+/// Writes shapes, in millimeters with y pointing up, as a dxf drawing of
+/// native circles, arcs and lines. Fills become outlines on the FILL layer
+/// and strokes center lines on the STROKE layer, so CAD tools can treat
+/// them differently
+pub fn render_dxf(items: &[DrawItem], tolerance_mm: f64) -> String {
+    let mut output = String::new();
+    write_group(&mut output, 0, "SECTION");
+    write_group(&mut output, 2, "HEADER");
+    write_group(&mut output, 9, "$INSUNITS");
+    // millimeters
+    write_group(&mut output, 70, "4");
+    write_group(&mut output, 0, "ENDSEC");
+
+    write_group(&mut output, 0, "SECTION");
+    write_group(&mut output, 2, "ENTITIES");
+    for item in items.iter() {
+        if item.paint.fill.is_some() {
+            write_shape(&mut output, FILL_LAYER, &item.shape);
+        }
+        if item.paint.stroke.is_none() || item.paint.stroke_width <= 0.0 {
+            continue;
+        }
+        if item.paint.dash_array.is_empty() {
+            write_shape(&mut output, STROKE_LAYER, &item.shape);
+            continue;
+        }
+        for dash in item
+            .shape
+            .flatten(tolerance_mm)
+            .iter()
+            .flat_map(|outline| apply_dashes(outline, &item.paint.dash_array).into_iter())
+        {
+            for pair in dash.windows(2) {
+                write_line(&mut output, STROKE_LAYER, &pair[0], &pair[1]);
+            }
+        }
+    }
+    write_group(&mut output, 0, "ENDSEC");
+    write_group(&mut output, 0, "EOF");
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::transform::Transform2D;
+    use crate::plot::dxf::render_dxf;
+    use crate::svg::element::SvgElement;
+    use crate::svg::walk::walk_document;
+
+    #[test]
+    fn test_render_dxf_layers() {
+        let document = SvgElement::new("svg")
+            .child(SvgElement::new("circle").attr("r", 1.0))
+            .child(
+                SvgElement::new("circle")
+                    .attr("r", 2.0)
+                    .attr("fill", "none")
+                    .attr("stroke", "#000000"),
+            );
+        let dxf = render_dxf(&walk_document(&document, &Transform2D::identity()), 0.05);
+        assert!(dxf.contains("CIRCLE\n8\nFILL"));
+        assert!(dxf.contains("CIRCLE\n8\nSTROKE"));
+        assert!(dxf.ends_with("ENDSEC\n0\nEOF\n"));
+    }
+}
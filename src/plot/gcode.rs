@@ -0,0 +1,87 @@
+use crate::plot::toolpath::Toolpath;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct GcodeOptions {
+    /// Speed of moves made with the tool on, in millimeters per minute
+    pub feed_rate: f64,
+    /// Speed of moves between paths, in millimeters per minute
+    pub travel_rate: f64,
+    /// Commands that start cutting, e.g. turning a laser on or lowering a pen
+    pub tool_on: String,
+    /// Commands that stop cutting
+    pub tool_off: String,
+}
+
+impl Default for GcodeOptions {
+    fn default() -> GcodeOptions {
+        GcodeOptions {
+            feed_rate: 1000.0,
+            travel_rate: 3000.0,
+            tool_on: String::from("M3 S1000"),
+            tool_off: String::from("M5"),
+        }
+    }
+}
+
+fn format_coordinate(value: f64) -> String {
+    let mut formatted = format!("{:.3}", value);
+    while formatted.ends_with('0') {
+        formatted.pop();
+    }
+    if formatted.ends_with('.') {
+        formatted.pop();
+    }
+    if formatted == "-0" {
+        formatted = String::from("0");
+    }
+    return formatted;
+}
+
+/// Writes toolpaths, in millimeters, as g-code, returning to the origin
+/// when done
+pub fn render_gcode(toolpaths: &[Toolpath], options: &GcodeOptions) -> String {
+    let mut output = String::from("G21\nG90\n");
+    output.push_str(&format!("{}\n", options.tool_off));
+    for toolpath in toolpaths.iter() {
+        let start = toolpath.points[0];
+        output.push_str(&format!(
+            "G0 X{} Y{} F{}\n",
+            format_coordinate(start.x),
+            format_coordinate(start.y),
+            format_coordinate(options.travel_rate)
+        ));
+        output.push_str(&format!("{}\n", options.tool_on));
+        output.push_str(&format!("F{}\n", format_coordinate(options.feed_rate)));
+        for point in toolpath.points[1..].iter() {
+            output.push_str(&format!(
+                "G1 X{} Y{}\n",
+                format_coordinate(point.x),
+                format_coordinate(point.y)
+            ));
+        }
+        output.push_str(&format!("{}\n", options.tool_off));
+    }
+    output.push_str("G0 X0 Y0\n");
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::vector::Vector2;
+    use crate::plot::gcode::{render_gcode, GcodeOptions};
+    use crate::plot::toolpath::Toolpath;
+
+    #[test]
+    fn test_render_gcode() {
+        let gcode = render_gcode(
+            &[Toolpath {
+                points: vec![Vector2::new(1.0, 2.5), Vector2::new(-0.0001, 3.0)],
+            }],
+            &GcodeOptions::default(),
+        );
+        assert_eq!(
+            gcode,
+            "G21\nG90\nM5\nG0 X1 Y2.5 F3000\nM3 S1000\nF1000\nG1 X0 Y3\nM5\nG0 X0 Y0\n"
+        );
+    }
+}
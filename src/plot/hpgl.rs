@@ -0,0 +1,41 @@
+use crate::plot::toolpath::Toolpath;
+
+/// Plotter units per millimeter
+const UNITS_PER_MILLIMETER: f64 = 40.0;
+
+/// Writes toolpaths, in millimeters, as hpgl for pen plotters, drawing
+/// everything with pen 1
+pub fn render_hpgl(toolpaths: &[Toolpath]) -> String {
+    let to_units = |value: f64| (value * UNITS_PER_MILLIMETER).round() as i64;
+    let mut output = String::from("IN;SP1;\n");
+    for toolpath in toolpaths.iter() {
+        let start = toolpath.points[0];
+        output.push_str(&format!("PU{},{};\n", to_units(start.x), to_units(start.y)));
+        let moves: Vec<String> = toolpath.points[1..]
+            .iter()
+            .map(|point| format!("{},{}", to_units(point.x), to_units(point.y)))
+            .collect();
+        output.push_str(&format!("PD{};\n", moves.join(",")));
+    }
+    output.push_str("PU;SP0;\n");
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::vector::Vector2;
+    use crate::plot::hpgl::render_hpgl;
+    use crate::plot::toolpath::Toolpath;
+
+    #[test]
+    fn test_render_hpgl() {
+        let hpgl = render_hpgl(&[Toolpath {
+            points: vec![
+                Vector2::new(0.0, 0.0),
+                Vector2::new(1.0, 0.0),
+                Vector2::new(1.0, 2.5),
+            ],
+        }]);
+        assert_eq!(hpgl, "IN;SP1;\nPU0,0;\nPD40,0,40,100;\nPU;SP0;\n");
+    }
+}
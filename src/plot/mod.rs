@@ -0,0 +1,4 @@
+pub mod dxf;
+pub mod gcode;
+pub mod hpgl;
+pub mod toolpath;
@@ -0,0 +1,224 @@
+use crate::geom::vector::Vector2;
+use crate::svg::walk::{apply_dashes, DrawItem};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PlotOptions {
+    /// Plotted diameter of the largest ring, dots included, in millimeters
+    pub diameter_mm: f64,
+    /// How far (in millimeters) curves broken into straight moves may stray
+    /// from the true curve
+    pub tolerance_mm: f64,
+    /// Distance between the lines filled shapes are hatched with. None
+    /// plots filled shapes as outlines only
+    pub hatch_spacing_mm: Option<f64>,
+    /// Reorder paths so the tool travels as little as possible between them
+    pub optimize_order: bool,
+}
+
+impl Default for PlotOptions {
+    fn default() -> PlotOptions {
+        PlotOptions {
+            diameter_mm: 100.0,
+            tolerance_mm: 0.05,
+            hatch_spacing_mm: Some(0.3),
+            optimize_order: true,
+        }
+    }
+}
+
+/// A run of connected moves made with the tool down
+#[derive(Debug, PartialEq, Clone)]
+pub struct Toolpath {
+    pub points: Vec<Vector2>,
+}
+
+/// Lines across the area enclosed by `polygons` (by the nonzero winding
+/// rule), `spacing` apart. Alternate lines run in opposite directions, so
+/// the tool can zigzag between them
+fn hatch(polygons: &[Vec<Vector2>], spacing: f64) -> Vec<Toolpath> {
+    let mut min_y = std::f64::INFINITY;
+    let mut max_y = std::f64::NEG_INFINITY;
+    for point in polygons.iter().flat_map(|polygon| polygon.iter()) {
+        min_y = min_y.min(point.y);
+        max_y = max_y.max(point.y);
+    }
+    if spacing <= 0.0 || !(max_y > min_y) {
+        return vec![];
+    }
+
+    let mut lines: Vec<Toolpath> = vec![];
+    let mut row_index = 0;
+    let mut y = min_y + spacing / 2.0;
+    while y < max_y {
+        let mut crossings: Vec<(f64, i32)> = vec![];
+        for polygon in polygons.iter() {
+            for pair in polygon.windows(2) {
+                let (from, to) = (pair[0], pair[1]);
+                if (from.y <= y) != (to.y <= y) {
+                    let t = (y - from.y) / (to.y - from.y);
+                    crossings.push((
+                        from.x + (to.x - from.x) * t,
+                        if to.y > from.y { 1 } else { -1 },
+                    ));
+                }
+            }
+        }
+        crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut row: Vec<Toolpath> = vec![];
+        let mut winding = 0;
+        for i in 0..crossings.len() {
+            let was_inside = winding != 0;
+            winding += crossings[i].1;
+            if !was_inside && winding != 0 && i + 1 < crossings.len() {
+                // find where this span leaves the shape
+                let mut span_winding = winding;
+                let mut end = i + 1;
+                while end < crossings.len() {
+                    span_winding += crossings[end].1;
+                    if span_winding == 0 {
+                        break;
+                    }
+                    end += 1;
+                }
+                let end_x = crossings[end.min(crossings.len() - 1)].0;
+                row.push(Toolpath {
+                    points: vec![Vector2::new(crossings[i].0, y), Vector2::new(end_x, y)],
+                });
+            }
+        }
+        if row_index % 2 == 1 {
+            row.reverse();
+            for line in row.iter_mut() {
+                line.points.reverse();
+            }
+        }
+        lines.extend(row);
+        row_index += 1;
+        y += spacing;
+    }
+    return lines;
+}
+
+/// Converts drawn shapes to toolpaths, grouped by the shape they came
+/// from. Strokes are followed along their center line, whatever their width;
+/// fills are hatched, if enabled, then outlined
+pub fn get_toolpaths(items: &[DrawItem], options: &PlotOptions) -> Vec<Vec<Toolpath>> {
+    let mut groups: Vec<Vec<Toolpath>> = vec![];
+    for item in items.iter() {
+        let mut toolpaths: Vec<Toolpath> = vec![];
+        let outlines: Vec<Vec<Vector2>> = item.shape.flatten(options.tolerance_mm);
+
+        if item.paint.fill.is_some() {
+            let closed: Vec<Vec<Vector2>> = outlines
+                .iter()
+                .map(|outline| {
+                    let mut closed = outline.clone();
+                    if closed.len() > 1 && closed.first() != closed.last() {
+                        closed.push(closed[0]);
+                    }
+                    closed
+                })
+                .collect();
+            if let Some(spacing) = options.hatch_spacing_mm {
+                toolpaths.extend(hatch(&closed, spacing));
+            }
+            toolpaths.extend(closed.into_iter().map(|points| Toolpath { points: points }));
+        }
+        if item.paint.stroke.is_some() && item.paint.stroke_width > 0.0 {
+            for outline in outlines.iter() {
+                if item.paint.dash_array.is_empty() {
+                    toolpaths.push(Toolpath {
+                        points: outline.clone(),
+                    });
+                } else {
+                    toolpaths.extend(
+                        apply_dashes(outline, &item.paint.dash_array)
+                            .into_iter()
+                            .map(|points| Toolpath { points: points }),
+                    );
+                }
+            }
+        }
+
+        toolpaths.retain(|toolpath| toolpath.points.len() > 1);
+        if !toolpaths.is_empty() {
+            groups.push(toolpaths);
+        }
+    }
+    return groups;
+}
+
+/// Orders groups of toolpaths greedily, always moving to the closest end of
+/// an unvisited group next. Groups entered from their last point are drawn
+/// in reverse; the paths within a group otherwise keep their order
+pub fn order_toolpaths(groups: Vec<Vec<Toolpath>>, start: &Vector2) -> Vec<Toolpath> {
+    let mut remaining = groups;
+    let mut ordered: Vec<Toolpath> = vec![];
+    let mut position = *start;
+
+    while !remaining.is_empty() {
+        // (index of group, whether to reverse it, distance)
+        let mut best: (usize, bool, f64) = (0, false, std::f64::INFINITY);
+        for (index, group) in remaining.iter().enumerate() {
+            let first = group[0].points[0];
+            let last = *group[group.len() - 1].points.last().unwrap_or(&first);
+            if position.distance(&first) < best.2 {
+                best = (index, false, position.distance(&first));
+            }
+            if position.distance(&last) < best.2 {
+                best = (index, true, position.distance(&last));
+            }
+        }
+
+        let mut group = remaining.swap_remove(best.0);
+        if best.1 {
+            group.reverse();
+            for toolpath in group.iter_mut() {
+                toolpath.points.reverse();
+            }
+        }
+        position = *group[group.len() - 1].points.last().unwrap_or(&position);
+        ordered.extend(group);
+    }
+    return ordered;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::vector::Vector2;
+    use crate::plot::toolpath::{hatch, order_toolpaths, Toolpath};
+
+    #[test]
+    fn test_hatch_square() {
+        let square = vec![
+            Vector2::new(0.0, 0.0),
+            Vector2::new(1.0, 0.0),
+            Vector2::new(1.0, 1.0),
+            Vector2::new(0.0, 1.0),
+            Vector2::new(0.0, 0.0),
+        ];
+        let lines = hatch(&[square], 0.25);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0].points,
+            vec![Vector2::new(0.0, 0.125), Vector2::new(1.0, 0.125)]
+        );
+        assert_eq!(
+            lines[1].points,
+            vec![Vector2::new(1.0, 0.375), Vector2::new(0.0, 0.375)]
+        );
+    }
+
+    #[test]
+    fn test_order_toolpaths_by_nearest_end() {
+        let line = |x1: f64, x2: f64| Toolpath {
+            points: vec![Vector2::new(x1, 0.0), Vector2::new(x2, 0.0)],
+        };
+        let ordered = order_toolpaths(
+            vec![vec![line(10.0, 11.0)], vec![line(3.0, 1.0)]],
+            &Vector2::zero(),
+        );
+        assert_eq!(ordered, vec![line(1.0, 3.0), line(10.0, 11.0)]);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::drawing_style::{DrawingColors, DrawingStyle};
+use crate::fig::dot::{Dot, DotOrientation, DotShape, TickDirection};
+use crate::fig::text_path::ArcStyle;
+use serde::{Deserialize, Serialize};
+
+/// A style saved under a name, so it can be applied again later
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Preset<T> {
+    pub name: String,
+    pub value: T,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PresetKind {
+    Dot,
+    Arc,
+    DrawingStyle,
+}
+
+/// Every saved preset. This is also the layout of exported preset files
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PresetLibrary {
+    #[serde(default)]
+    pub dots: Vec<Preset<Dot>>,
+    #[serde(default)]
+    pub arcs: Vec<Preset<ArcStyle>>,
+    #[serde(default)]
+    pub drawing_styles: Vec<Preset<DrawingStyle>>,
+}
+
+fn preset<T>(name: &str, value: T) -> Preset<T> {
+    return Preset {
+        name: name.to_string(),
+        value: value,
+    };
+}
+
+fn circle_and_ring(circle_radius: f64, ring_radius: f64, ring_stroke_width: f64) -> Dot {
+    return Dot {
+        circle_radius: circle_radius,
+        ring_radius: ring_radius,
+        ring_stroke_width: ring_stroke_width,
+        shape: DotShape::CircleAndRing,
+        orientation: DotOrientation::Upright,
+        rotation_degrees: 0.0,
+    };
+}
+
+fn arc(arc_percentage: f64, arc_offset_percentage: f64) -> ArcStyle {
+    return ArcStyle {
+        radius: 5.0,
+        arc_percentage: arc_percentage,
+        arc_offset_percentage: arc_offset_percentage,
+    };
+}
+
+/// Built in dots on a full circle, in the given colors
+fn drawing_style(stroke_color: &str, background_color: &str) -> DrawingStyle {
+    return DrawingStyle {
+        color: DrawingColors {
+            stroke_color: stroke_color.to_string(),
+            background_color: background_color.to_string(),
+        },
+        default_zero_dot_style: circle_and_ring(1.0, 2.0, 0.1),
+        default_one_dot_style: circle_and_ring(0.5, 2.0, 0.0),
+        default_arc_style: arc(1.0, 0.0),
+        default_decoration_style: Default::default(),
+        default_color_style: Default::default(),
+        background: Default::default(),
+        default_animation_style: Default::default(),
+    };
+}
+
+/// Replaces the preset with the same name, or adds it to the end
+pub fn save_preset<T>(presets: &mut Vec<Preset<T>>, name: &str, value: T) {
+    match presets.iter_mut().find(|preset| preset.name == name) {
+        Some(existing) => existing.value = value,
+        None => presets.push(preset(name, value)),
+    }
+}
+
+impl PresetLibrary {
+    /// Presets available before anything has been saved
+    pub fn built_in() -> PresetLibrary {
+        return PresetLibrary {
+            dots: vec![
+                preset("Ring", circle_and_ring(1.0, 2.0, 0.1)),
+                preset("Dot", circle_and_ring(0.5, 2.0, 0.0)),
+                preset("Bold Ring", circle_and_ring(0.8, 1.6, 0.4)),
+                preset(
+                    "Star",
+                    Dot {
+                        shape: DotShape::Star {
+                            points: 5,
+                            radius: 1.5,
+                            inner_radius: 0.7,
+                            stroke_width: 0.0,
+                        },
+                        orientation: DotOrientation::Normal,
+                        ..circle_and_ring(1.0, 2.0, 0.1)
+                    },
+                ),
+                preset(
+                    "Tick",
+                    Dot {
+                        shape: DotShape::Tick {
+                            direction: TickDirection::Radial,
+                            length: 2.0,
+                            stroke_width: 0.3,
+                        },
+                        orientation: DotOrientation::Normal,
+                        ..circle_and_ring(1.0, 2.0, 0.1)
+                    },
+                ),
+            ],
+            arcs: vec![
+                preset("Full Circle", arc(1.0, 0.0)),
+                preset("Top Half", arc(0.5, 0.5)),
+                preset("Horseshoe", arc(0.75, 0.375)),
+            ],
+            drawing_styles: vec![
+                preset("Ink", drawing_style("#333333", "#EEEEEE")),
+                preset("Night", drawing_style("#F5F1E6", "#1B1F2A")),
+                preset("Blueprint", drawing_style("#FFFFFF", "#1D4E89")),
+            ],
+        };
+    }
+
+    pub fn remove(&mut self, kind: &PresetKind, index: usize) {
+        match kind {
+            PresetKind::Dot if index < self.dots.len() => {
+                self.dots.remove(index);
+            }
+            PresetKind::Arc if index < self.arcs.len() => {
+                self.arcs.remove(index);
+            }
+            PresetKind::DrawingStyle if index < self.drawing_styles.len() => {
+                self.drawing_styles.remove(index);
+            }
+            _ => {}
+        }
+    }
+
+    /// The library as a json file to share
+    pub fn export(&self) -> String {
+        return match serde_json::to_string_pretty(self) {
+            Ok(json) => json,
+            Err(e) => panic!("error serializing presets {:?}", e),
+        };
+    }
+
+    /// Adds the presets from an exported library. Presets with names that
+    /// are already taken replace the existing ones. Returns how many
+    /// presets were read
+    pub fn import(&mut self, json: &str) -> Result<usize, String> {
+        let imported: PresetLibrary = match serde_json::from_str(json) {
+            Ok(imported) => imported,
+            Err(e) => return Err(e.to_string()),
+        };
+        let count = imported.dots.len() + imported.arcs.len() + imported.drawing_styles.len();
+        for p in imported.dots {
+            save_preset(&mut self.dots, &p.name, p.value);
+        }
+        for p in imported.arcs {
+            save_preset(&mut self.arcs, &p.name, p.value);
+        }
+        for p in imported.drawing_styles {
+            save_preset(&mut self.drawing_styles, &p.name, p.value);
+        }
+        return Ok(count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::presets::{save_preset, PresetKind, PresetLibrary};
+
+    #[test]
+    fn test_export_and_import_presets() {
+        let mut shared = PresetLibrary::default();
+        let mut ring = PresetLibrary::built_in().dots[0].value.clone();
+        ring.ring_stroke_width = 0.3;
+        save_preset(&mut shared.dots, "Ring", ring.clone());
+        save_preset(&mut shared.dots, "Brand Dot", ring.clone());
+
+        let mut library = PresetLibrary::built_in();
+        let num_dots = library.dots.len();
+        assert_eq!(library.import(&shared.export()), Ok(2));
+
+        // the same name replaces, a new name is added
+        assert_eq!(library.dots.len(), num_dots + 1);
+        assert_eq!(library.dots[0].value, ring);
+        assert_eq!(library.dots[num_dots].name, "Brand Dot");
+
+        library.remove(&PresetKind::Dot, 0);
+        assert_eq!(library.dots.len(), num_dots);
+        assert!(library.import("{\"dots\": 3}").is_err());
+    }
+}
@@ -0,0 +1,439 @@
+use crate::color::Rgba;
+use crate::geom::vector::Vector2;
+use crate::svg::walk::{apply_dashes, DrawItem, Gradient, Paint, Shape};
+
+/// How far (in pixels) flattened curves may stray from the true curve
+const FLATTEN_TOLERANCE: f64 = 0.1;
+/// Sub-rows sampled per pixel row when filling paths
+const FILL_SUBROWS: usize = 4;
+
+/// Coverage of a pixel whose center is `distance` inside an edge
+fn edge_coverage(distance: f64) -> f64 {
+    return (distance + 0.5).max(0.0).min(1.0);
+}
+
+/// Shapes thinner than a pixel are drawn a pixel wide and faded instead,
+/// so they don't flicker in and out with their position. Returns the
+/// half width to draw and the factor to fade by.
+fn thin_half_width(half_width: f64) -> (f64, f64) {
+    if half_width < 0.5 {
+        return (0.5, half_width * 2.0);
+    }
+    return (half_width, 1.0);
+}
+
+/// A coverage mask over a rectangle of canvas pixels
+struct Mask {
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    coverage: Vec<f64>,
+}
+
+impl Mask {
+    /// Mask over the pixels touching the area between `min` and `max`,
+    /// clipped to the canvas. None if nothing is left after clipping.
+    fn covering(
+        min: &Vector2,
+        max: &Vector2,
+        canvas_width: usize,
+        canvas_height: usize,
+    ) -> Option<Mask> {
+        let left = min.x.floor().max(0.0);
+        let top = min.y.floor().max(0.0);
+        let right = max.x.ceil().min(canvas_width as f64);
+        let bottom = max.y.ceil().min(canvas_height as f64);
+        if !(right > left && bottom > top) {
+            return None;
+        }
+        let (width, height) = ((right - left) as usize, (bottom - top) as usize);
+        return Some(Mask {
+            left: left as usize,
+            top: top as usize,
+            width: width,
+            height: height,
+            coverage: vec![0.0; width * height],
+        });
+    }
+
+    /// Raises the coverage of each pixel in the area between `min` and
+    /// `max` to `coverage(pixel center)`
+    fn cover<F: Fn(&Vector2) -> f64>(&mut self, min: &Vector2, max: &Vector2, coverage: F) {
+        let x_start = min.x.floor().max(self.left as f64) as usize;
+        let y_start = min.y.floor().max(self.top as f64) as usize;
+        let x_end = (max.x.ceil().max(0.0) as usize).min(self.left + self.width);
+        let y_end = (max.y.ceil().max(0.0) as usize).min(self.top + self.height);
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let value = coverage(&Vector2::new(x as f64 + 0.5, y as f64 + 0.5));
+                let index = (y - self.top) * self.width + (x - self.left);
+                if value > self.coverage[index] {
+                    self.coverage[index] = value;
+                }
+            }
+        }
+    }
+}
+
+/// Coverage of a pixel centered at `point` by a stroke from `from` to `to`.
+/// Ends marked butt are cut square at the end point, the others are rounded
+/// so consecutive segments join without gaps.
+fn stroke_segment_coverage(
+    point: &Vector2,
+    from: &Vector2,
+    to: &Vector2,
+    half_width: f64,
+    butt_start: bool,
+    butt_end: bool,
+) -> f64 {
+    let segment = *to - *from;
+    let length = segment.length();
+    if length == 0.0 {
+        if butt_start || butt_end {
+            return 0.0;
+        }
+        return edge_coverage(half_width - point.distance(from));
+    }
+
+    let direction = segment * (1.0 / length);
+    let along = (*point - *from).dot(&direction);
+    let across = (*point - *from).dot(&direction.perpendicular()).abs();
+
+    let distance = if along < 0.0 && !butt_start {
+        point.distance(from)
+    } else if along > length && !butt_end {
+        point.distance(to)
+    } else {
+        across
+    };
+    let mut coverage = edge_coverage(half_width - distance);
+    if butt_start {
+        coverage *= edge_coverage(along);
+    }
+    if butt_end {
+        coverage *= edge_coverage(length - along);
+    }
+    return coverage;
+}
+
+/// An RGBA image that shapes are drawn onto with anti-aliasing
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    /// Premultiplied colors, row by row
+    pixels: Vec<Rgba>,
+}
+
+impl Canvas {
+    /// A fully transparent canvas
+    pub fn new(width: usize, height: usize) -> Canvas {
+        return Canvas {
+            width: width,
+            height: height,
+            pixels: vec![Rgba::transparent(); width * height],
+        };
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> Rgba {
+        let pixel = self.pixels[y * self.width + x];
+        if pixel.a == 0.0 {
+            return Rgba::transparent();
+        }
+        return Rgba::new(
+            pixel.r / pixel.a,
+            pixel.g / pixel.a,
+            pixel.b / pixel.a,
+            pixel.a,
+        );
+    }
+
+    /// Draws `color`, or `gradient` where given, over the pixels under the
+    /// mask
+    fn composite(&mut self, mask: &Mask, color: &Rgba, gradient: Option<&Gradient>, opacity: f64) {
+        if gradient.is_none() && color.a * opacity <= 0.0 {
+            return;
+        }
+        for y in 0..mask.height {
+            for x in 0..mask.width {
+                let color = match gradient {
+                    Some(gradient) => gradient.color_at(&Vector2::new(
+                        (mask.left + x) as f64 + 0.5,
+                        (mask.top + y) as f64 + 0.5,
+                    )),
+                    None => *color,
+                };
+                let coverage = mask.coverage[y * mask.width + x] * color.a * opacity;
+                if coverage <= 0.0 {
+                    continue;
+                }
+                let pixel = &mut self.pixels[(mask.top + y) * self.width + mask.left + x];
+                pixel.r = color.r * coverage + pixel.r * (1.0 - coverage);
+                pixel.g = color.g * coverage + pixel.g * (1.0 - coverage);
+                pixel.b = color.b * coverage + pixel.b * (1.0 - coverage);
+                pixel.a = coverage + pixel.a * (1.0 - coverage);
+            }
+        }
+    }
+
+    fn fill_circle(&mut self, center: &Vector2, radius: f64, paint: &Paint, color: &Rgba) {
+        let reach = Vector2::new(radius + 1.0, radius + 1.0);
+        let (min, max) = (*center - reach, *center + reach);
+        if let Some(mut mask) = Mask::covering(&min, &max, self.width, self.height) {
+            let (radius, fade) = thin_half_width(radius);
+            mask.cover(&min, &max, |point| {
+                // small dots fade by area rather than by width
+                fade * fade * edge_coverage(radius - point.distance(center))
+            });
+            self.composite(&mask, color, paint.fill_gradient.as_ref(), paint.opacity);
+        }
+    }
+
+    fn stroke_circle(
+        &mut self,
+        center: &Vector2,
+        radius: f64,
+        width: f64,
+        color: &Rgba,
+        opacity: f64,
+    ) {
+        let reach = Vector2::new(radius + width / 2.0 + 1.0, radius + width / 2.0 + 1.0);
+        let (min, max) = (*center - reach, *center + reach);
+        if let Some(mut mask) = Mask::covering(&min, &max, self.width, self.height) {
+            let (half_width, fade) = thin_half_width(width / 2.0);
+            mask.cover(&min, &max, |point| {
+                fade * edge_coverage(half_width - (point.distance(center) - radius).abs())
+            });
+            self.composite(&mask, color, None, opacity);
+        }
+    }
+
+    /// Fills the area enclosed by the polygons, by the nonzero winding rule
+    fn fill_polygons(&mut self, polygons: &[Vec<Vector2>], paint: &Paint, color: &Rgba) {
+        let mut min = Vector2::new(std::f64::INFINITY, std::f64::INFINITY);
+        let mut max = Vector2::new(std::f64::NEG_INFINITY, std::f64::NEG_INFINITY);
+        for point in polygons.iter().flat_map(|polygon| polygon.iter()) {
+            min = Vector2::new(min.x.min(point.x), min.y.min(point.y));
+            max = Vector2::new(max.x.max(point.x), max.y.max(point.y));
+        }
+        let mut mask = match Mask::covering(&min, &max, self.width, self.height) {
+            Some(mask) => mask,
+            None => return,
+        };
+
+        let mut edges: Vec<(Vector2, Vector2)> = vec![];
+        for polygon in polygons.iter() {
+            for i in 0..polygon.len() {
+                let (from, to) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+                if from.y != to.y {
+                    edges.push((from, to));
+                }
+            }
+        }
+
+        let subrow_weight = 1.0 / FILL_SUBROWS as f64;
+        let mut crossings: Vec<(f64, i32)> = vec![];
+        for row in 0..mask.height {
+            for subrow in 0..FILL_SUBROWS {
+                let y = (mask.top + row) as f64 + (subrow as f64 + 0.5) * subrow_weight;
+                crossings.clear();
+                for (from, to) in edges.iter() {
+                    if (from.y <= y) != (to.y <= y) {
+                        let t = (y - from.y) / (to.y - from.y);
+                        let winding = if to.y > from.y { 1 } else { -1 };
+                        crossings.push((from.x + (to.x - from.x) * t, winding));
+                    }
+                }
+                crossings
+                    .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+                let mut winding = 0;
+                for i in 0..crossings.len() {
+                    winding += crossings[i].1;
+                    if winding == 0 || i + 1 == crossings.len() {
+                        continue;
+                    }
+                    // the span from this crossing to the next is inside
+                    let span_start = (crossings[i].0 - mask.left as f64).max(0.0);
+                    let span_end = (crossings[i + 1].0 - mask.left as f64).min(mask.width as f64);
+                    if span_end <= span_start {
+                        continue;
+                    }
+                    let row_coverage = &mut mask.coverage[row * mask.width..(row + 1) * mask.width];
+                    let first = span_start.floor() as usize;
+                    let last = (span_end.ceil() as usize).min(mask.width);
+                    for x in first..last {
+                        let pixel_start = span_start.max(x as f64);
+                        let pixel_end = span_end.min(x as f64 + 1.0);
+                        row_coverage[x] += (pixel_end - pixel_start) * subrow_weight;
+                    }
+                }
+            }
+        }
+        for value in mask.coverage.iter_mut() {
+            *value = value.min(1.0);
+        }
+        self.composite(&mask, color, paint.fill_gradient.as_ref(), paint.opacity);
+    }
+
+    fn stroke_polylines(
+        &mut self,
+        polylines: &[(Vec<Vector2>, bool)],
+        width: f64,
+        color: &Rgba,
+        opacity: f64,
+    ) {
+        let reach = Vector2::new(width / 2.0 + 1.0, width / 2.0 + 1.0);
+        let mut min = Vector2::new(std::f64::INFINITY, std::f64::INFINITY);
+        let mut max = Vector2::new(std::f64::NEG_INFINITY, std::f64::NEG_INFINITY);
+        for point in polylines.iter().flat_map(|(points, _)| points.iter()) {
+            min = Vector2::new(min.x.min(point.x), min.y.min(point.y));
+            max = Vector2::new(max.x.max(point.x), max.y.max(point.y));
+        }
+        let mut mask = match Mask::covering(&(min - reach), &(max + reach), self.width, self.height)
+        {
+            Some(mask) => mask,
+            None => return,
+        };
+
+        let (half_width, fade) = thin_half_width(width / 2.0);
+        for (points, closed) in polylines.iter() {
+            let num_segments = points.len().saturating_sub(1);
+            for (index, pair) in points.windows(2).enumerate() {
+                let (from, to) = (pair[0], pair[1]);
+                let butt_start = index == 0 && !closed;
+                let butt_end = index + 1 == num_segments && !closed;
+                let segment_min = Vector2::new(from.x.min(to.x), from.y.min(to.y)) - reach;
+                let segment_max = Vector2::new(from.x.max(to.x), from.y.max(to.y)) + reach;
+                mask.cover(&segment_min, &segment_max, |point| {
+                    fade * stroke_segment_coverage(
+                        point, &from, &to, half_width, butt_start, butt_end,
+                    )
+                });
+            }
+        }
+        self.composite(&mask, color, None, opacity);
+    }
+
+    fn stroke_dashed(&mut self, polylines: &[(Vec<Vector2>, bool)], paint: &Paint, color: &Rgba) {
+        if paint.dash_array.is_empty() {
+            self.stroke_polylines(polylines, paint.stroke_width, color, paint.opacity);
+            return;
+        }
+        let dashes: Vec<(Vec<Vector2>, bool)> = polylines
+            .iter()
+            .flat_map(|(points, _)| apply_dashes(points, &paint.dash_array).into_iter())
+            .map(|dash| (dash, false))
+            .collect();
+        self.stroke_polylines(&dashes, paint.stroke_width, color, paint.opacity);
+    }
+
+    /// Draws a shape whose coordinates are in pixels
+    pub fn draw(&mut self, item: &DrawItem) {
+        let paint = &item.paint;
+        match &item.shape {
+            Shape::Circle { center, radius } => {
+                if let Some(fill) = paint.fill {
+                    self.fill_circle(center, *radius, paint, &fill);
+                }
+                if let Some(stroke) = paint.stroke {
+                    if paint.stroke_width <= 0.0 {
+                        return;
+                    }
+                    if paint.dash_array.is_empty() {
+                        self.stroke_circle(
+                            center,
+                            *radius,
+                            paint.stroke_width,
+                            &stroke,
+                            paint.opacity,
+                        );
+                    } else {
+                        let outlines: Vec<(Vec<Vector2>, bool)> = item
+                            .shape
+                            .flatten(FLATTEN_TOLERANCE)
+                            .into_iter()
+                            .map(|outline| (outline, true))
+                            .collect();
+                        self.stroke_dashed(&outlines, paint, &stroke);
+                    }
+                }
+            }
+            Shape::Path(subpaths) => {
+                let polylines: Vec<(Vec<Vector2>, bool)> = subpaths
+                    .iter()
+                    .map(|subpath| (subpath.flatten(FLATTEN_TOLERANCE), subpath.closed))
+                    .collect();
+                if let Some(fill) = paint.fill {
+                    let polygons: Vec<Vec<Vector2>> =
+                        polylines.iter().map(|(points, _)| points.clone()).collect();
+                    self.fill_polygons(&polygons, paint, &fill);
+                }
+                if let Some(stroke) = paint.stroke {
+                    if paint.stroke_width > 0.0 {
+                        self.stroke_dashed(&polylines, paint, &stroke);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pixels as straight-alpha RGBA bytes, row by row
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let to_byte = |value: f64| (value.max(0.0).min(1.0) * 255.0).round() as u8;
+        let mut bytes: Vec<u8> = Vec::with_capacity(self.pixels.len() * 4);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = self.get_pixel(x, y);
+                bytes.push(to_byte(pixel.r));
+                bytes.push(to_byte(pixel.g));
+                bytes.push(to_byte(pixel.b));
+                bytes.push(to_byte(pixel.a));
+            }
+        }
+        return bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Rgba;
+    use crate::geom::vector::Vector2;
+    use crate::raster::canvas::Canvas;
+    use crate::svg::walk::{DrawItem, Paint, Segment, Shape, Subpath};
+
+    #[test]
+    fn test_draws_antialiased_shapes() {
+        let mut canvas = Canvas::new(20, 10);
+        let red = Rgba::new(1.0, 0.0, 0.0, 1.0);
+        canvas.draw(&DrawItem {
+            shape: Shape::Circle {
+                center: Vector2::new(5.5, 5.5),
+                radius: 3.0,
+            },
+            paint: Paint {
+                fill: Some(red),
+                ..Paint::default()
+            },
+        });
+        canvas.draw(&DrawItem {
+            shape: Shape::Path(vec![Subpath {
+                start: Vector2::new(10.0, 0.0),
+                segments: vec![
+                    Segment::Line(Vector2::new(20.0, 0.0)),
+                    Segment::Line(Vector2::new(20.0, 10.0)),
+                    Segment::Line(Vector2::new(10.5, 10.0)),
+                ],
+                closed: true,
+            }]),
+            paint: Paint::default(),
+        });
+
+        assert_eq!(canvas.get_pixel(5, 5), red);
+        assert_eq!(canvas.get_pixel(0, 0).a, 0.0);
+        assert_eq!(canvas.get_pixel(2, 5).a, 0.5);
+        assert_eq!(canvas.get_pixel(15, 5), Rgba::new(0.0, 0.0, 0.0, 1.0));
+        let slanted_edge = canvas.get_pixel(10, 9).a;
+        assert!(slanted_edge > 0.4 && slanted_edge < 0.6);
+    }
+}
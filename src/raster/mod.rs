@@ -0,0 +1,3 @@
+pub mod canvas;
+pub mod png;
+pub mod render;
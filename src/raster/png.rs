@@ -0,0 +1,197 @@
+use crate::raster::canvas::Canvas;
+
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+/// Extra information written alongside the pixels
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PngMetadata {
+    /// Pixel density, written to the `pHYs` chunk so print tools pick up the
+    /// intended physical size
+    pub dpi: Option<f64>,
+    /// Keyword / text pairs, written as uncompressed `iTXt` chunks
+    pub text: Vec<(String, String)>,
+}
+
+fn crc32_table() -> Vec<u32> {
+    return (0..256u32)
+        .map(|n| {
+            let mut c = n;
+            for _ in 0..8 {
+                c = if c & 1 != 0 {
+                    0xedb8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            c
+        })
+        .collect();
+}
+
+pub fn crc32(table: &[u32], bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in bytes {
+        crc = table[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    return crc ^ 0xffff_ffff;
+}
+
+fn push_u32(output: &mut Vec<u8>, value: u32) {
+    output.extend_from_slice(&[
+        (value >> 24) as u8,
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    ]);
+}
+
+fn write_chunk(output: &mut Vec<u8>, crc_table: &[u32], kind: &[u8; 4], data: &[u8]) {
+    push_u32(output, data.len() as u32);
+    let start = output.len();
+    output.extend_from_slice(kind);
+    output.extend_from_slice(data);
+    let crc = crc32(crc_table, &output[start..]);
+    push_u32(output, crc);
+}
+
+/// Finds the text stored under `keyword` in a png's `iTXt`, `tEXt` or
+/// `zTXt` chunks. Returns `None` if the bytes aren't a png or there is no
+/// such chunk
+pub fn read_png_text(png: &[u8], keyword: &str) -> Option<String> {
+    if png.len() < PNG_SIGNATURE.len() || png[..PNG_SIGNATURE.len()] != PNG_SIGNATURE {
+        return None;
+    }
+    let mut position = PNG_SIGNATURE.len();
+    // length, kind, data and crc
+    while position + 12 <= png.len() {
+        let length = ((png[position] as usize) << 24)
+            | ((png[position + 1] as usize) << 16)
+            | ((png[position + 2] as usize) << 8)
+            | png[position + 3] as usize;
+        let kind = &png[position + 4..position + 8];
+        let data_start = position + 8;
+        if data_start + length + 4 > png.len() {
+            return None;
+        }
+        let data = &png[data_start..data_start + length];
+        position = data_start + length + 4;
+
+        let separator = match data.iter().position(|byte| *byte == 0) {
+            Some(separator) => separator,
+            None => continue,
+        };
+        if &data[..separator] != keyword.as_bytes() {
+            continue;
+        }
+        let body = &data[separator + 1..];
+        match kind {
+            b"iTXt" => {
+                if body.len() < 2 {
+                    return None;
+                }
+                let compressed = body[0] == 1;
+                // skip the language tag and translated keyword
+                let mut rest = &body[2..];
+                for _ in 0..2 {
+                    let end = rest.iter().position(|byte| *byte == 0)?;
+                    rest = &rest[end + 1..];
+                }
+                let text = if compressed {
+                    miniz_oxide::inflate::decompress_to_vec_zlib(rest).ok()?
+                } else {
+                    rest.to_vec()
+                };
+                return String::from_utf8(text).ok();
+            }
+            // latin-1, which maps byte for byte onto the first unicode block
+            b"tEXt" => return Some(body.iter().map(|byte| *byte as char).collect()),
+            b"zTXt" => {
+                let text = miniz_oxide::inflate::decompress_to_vec_zlib(body.get(1..)?).ok()?;
+                return Some(text.iter().map(|byte| *byte as char).collect());
+            }
+            _ => {}
+        }
+    }
+    return None;
+}
+
+/// Encodes the canvas as an 8 bit RGBA png
+pub fn encode_png(canvas: &Canvas, metadata: &PngMetadata) -> Vec<u8> {
+    let crc_table = crc32_table();
+    let mut output: Vec<u8> = PNG_SIGNATURE.to_vec();
+
+    let mut header: Vec<u8> = vec![];
+    push_u32(&mut header, canvas.width as u32);
+    push_u32(&mut header, canvas.height as u32);
+    // bit depth, color type (truecolor with alpha), compression, filter, interlace
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    write_chunk(&mut output, &crc_table, b"IHDR", &header);
+
+    if let Some(dpi) = metadata.dpi {
+        let pixels_per_meter = (dpi / 0.0254).round() as u32;
+        let mut physical: Vec<u8> = vec![];
+        push_u32(&mut physical, pixels_per_meter);
+        push_u32(&mut physical, pixels_per_meter);
+        // unit: meters
+        physical.push(1);
+        write_chunk(&mut output, &crc_table, b"pHYs", &physical);
+    }
+
+    for (keyword, text) in metadata.text.iter() {
+        let mut international_text: Vec<u8> = keyword.as_bytes().to_vec();
+        // null separator, uncompressed, compression method, empty language
+        // tag and translated keyword
+        international_text.extend_from_slice(&[0, 0, 0, 0, 0]);
+        international_text.extend_from_slice(text.as_bytes());
+        write_chunk(&mut output, &crc_table, b"iTXt", &international_text);
+    }
+
+    // each row is preceded by its filter type; "Sub" suits the flat runs of
+    // color in a diagram
+    let pixels = canvas.to_rgba8();
+    let row_length = canvas.width * 4;
+    let mut filtered: Vec<u8> = Vec::with_capacity((row_length + 1) * canvas.height);
+    for row in pixels.chunks(row_length.max(1)) {
+        filtered.push(1);
+        for (index, byte) in row.iter().enumerate() {
+            let left = if index >= 4 { row[index - 4] } else { 0 };
+            filtered.push(byte.wrapping_sub(left));
+        }
+    }
+    let compressed = miniz_oxide::deflate::compress_to_vec_zlib(&filtered, 9);
+    write_chunk(&mut output, &crc_table, b"IDAT", &compressed);
+
+    write_chunk(&mut output, &crc_table, b"IEND", &[]);
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raster::canvas::Canvas;
+    use crate::raster::png::{crc32, crc32_table, encode_png, read_png_text, PngMetadata};
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(&crc32_table(), b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn test_encode_png() {
+        let png = encode_png(
+            &Canvas::new(3, 2),
+            &PngMetadata {
+                dpi: Some(300.0),
+                text: vec![("dots:config".to_string(), "{}".to_string())],
+            },
+        );
+        assert_eq!(&png[..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        assert!(png.windows(4).any(|window| window == b"pHYs"));
+        assert!(png.windows(13).any(|window| window == b"dots:config\0\0"));
+        assert_eq!(read_png_text(&png, "dots:config"), Some(String::from("{}")));
+        assert_eq!(read_png_text(&png, "dots"), None);
+        assert_eq!(read_png_text(b"<svg/>", "dots:config"), None);
+    }
+}
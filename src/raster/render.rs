@@ -0,0 +1,234 @@
+use crate::geom::rect::Rect;
+use crate::geom::transform::Transform2D;
+use crate::raster::canvas::Canvas;
+use crate::svg::element::SvgElement;
+use crate::svg::walk::{get_view_box, walk_document};
+
+/// Largest width or height a raster export may have, in pixels
+pub const MAX_RASTER_DIMENSION: u32 = 4096;
+
+/// Largest number of pixels a raster export may have. The canvas keeps four
+/// f64 channels per pixel, so this caps it at 128MB
+pub const MAX_RASTER_PIXELS: u64 = 4_194_304;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum RasterSize {
+    /// Width in pixels. The height follows the diagram's aspect ratio
+    Pixels(u32),
+    /// Printed width in millimeters, at the given pixel density
+    Physical { width_mm: f64, dpi: f64 },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RasterOptions {
+    pub size: RasterSize,
+    /// Leave out the background color, so the dots can be placed over
+    /// something else
+    pub transparent_background: bool,
+}
+
+impl Default for RasterOptions {
+    fn default() -> RasterOptions {
+        RasterOptions {
+            size: RasterSize::Pixels(1024),
+            transparent_background: false,
+        }
+    }
+}
+
+impl RasterOptions {
+    /// Width in pixels the options ask for, before any limits apply
+    fn get_requested_width(&self) -> f64 {
+        let width = match self.size {
+            RasterSize::Pixels(width) => width as f64,
+            RasterSize::Physical { width_mm, dpi } => (width_mm / 25.4 * dpi).round(),
+        };
+        return width.max(1.0);
+    }
+
+    pub fn get_pixel_width(&self) -> u32 {
+        return self.get_requested_width().min(MAX_RASTER_DIMENSION as f64) as u32;
+    }
+
+    /// Factor both sides of an image of `view_box` are shrunk by to stay
+    /// within the raster limits. 1 when the requested size already fits
+    pub fn get_shrink_factor(&self, view_box: &Rect) -> f64 {
+        let width = self.get_requested_width();
+        let height = view_box.height * width / view_box.width;
+        let max_dimension = MAX_RASTER_DIMENSION as f64;
+        return (max_dimension / width)
+            .min(max_dimension / height)
+            .min((MAX_RASTER_PIXELS as f64 / (width * height)).sqrt())
+            .min(1.0);
+    }
+
+    /// The pixel density to record in the image, if it was chosen
+    pub fn get_dpi(&self) -> Option<f64> {
+        return match self.size {
+            RasterSize::Pixels(_) => None,
+            RasterSize::Physical { dpi, .. } => Some(dpi),
+        };
+    }
+
+    /// The pixel density to record in an image of `view_box`. It drops by
+    /// as much as the image was shrunk, so the image still prints at the
+    /// chosen size
+    pub fn get_image_dpi(&self, view_box: &Rect) -> Option<f64> {
+        return self
+            .get_dpi()
+            .map(|dpi| dpi * self.get_shrink_factor(view_box));
+    }
+}
+
+/// Draws an svg document onto a canvas `options` wide, keeping the aspect
+/// ratio of its view box
+pub fn rasterize(document: &SvgElement, options: &RasterOptions) -> Canvas {
+    let view_box = match get_view_box(document) {
+        Some(view_box) if view_box.width > 0.0 && view_box.height > 0.0 => view_box,
+        _ => return Canvas::new(1, 1),
+    };
+    // both sides shrink evenly, rather than cropping, when the image
+    // would be too large
+    let scale =
+        options.get_requested_width() * options.get_shrink_factor(&view_box) / view_box.width;
+    let get_pixels = |length: f64| {
+        (length * scale)
+            .round()
+            .max(1.0)
+            .min(MAX_RASTER_DIMENSION as f64) as u32
+    };
+    let (width, height) = (get_pixels(view_box.width), get_pixels(view_box.height));
+
+    let to_pixels =
+        Transform2D::translate(-view_box.x, -view_box.y).then(&Transform2D::scale(scale, scale));
+    let mut canvas = Canvas::new(width as usize, height as usize);
+    for item in walk_document(document, &to_pixels).iter() {
+        canvas.draw(item);
+    }
+    return canvas;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::rect::Rect;
+    use crate::raster::render::{
+        rasterize, RasterOptions, RasterSize, MAX_RASTER_DIMENSION, MAX_RASTER_PIXELS,
+    };
+    use crate::svg::element::SvgElement;
+    use crate::svg::util::svg_root;
+
+    #[test]
+    fn test_physical_size() {
+        let options = RasterOptions {
+            size: RasterSize::Physical {
+                width_mm: 50.8,
+                dpi: 100.0,
+            },
+            transparent_background: true,
+        };
+        // 2 inches at 100 dpi
+        assert_eq!(options.get_pixel_width(), 200);
+        assert_eq!(options.get_dpi(), Some(100.0));
+    }
+
+    #[test]
+    fn test_rasterizes_gradient_disk() {
+        let document = svg_root(&Rect {
+            x: -5.0,
+            y: -5.0,
+            width: 10.0,
+            height: 10.0,
+        })
+        .child(
+            SvgElement::new("defs").child(
+                SvgElement::new("radialGradient")
+                    .attr("id", "fill")
+                    .attr("gradientUnits", "userSpaceOnUse")
+                    .attr("cx", 0.0)
+                    .attr("cy", 0.0)
+                    .attr("r", 5.0)
+                    .child(
+                        SvgElement::new("stop")
+                            .attr("offset", 0.0)
+                            .attr("stop-color", "#ffffff"),
+                    )
+                    .child(
+                        SvgElement::new("stop")
+                            .attr("offset", 1.0)
+                            .attr("stop-color", "#000000"),
+                    ),
+            ),
+        )
+        .child(
+            SvgElement::new("circle")
+                .attr("r", 5.0)
+                .attr("fill", "url(#fill)"),
+        );
+        let canvas = rasterize(
+            &document,
+            &RasterOptions {
+                size: RasterSize::Pixels(101),
+                transparent_background: false,
+            },
+        );
+        assert_eq!(canvas.get_pixel(0, 0).a, 0.0);
+        assert!(canvas.get_pixel(50, 50).r > 0.9);
+    }
+
+    #[test]
+    fn test_limits_raster_size() {
+        let document = SvgElement::new("svg").attr("viewBox", vec![0.0, 0.0, 10.0, 5.0]);
+        let canvas = rasterize(
+            &document,
+            &RasterOptions {
+                size: RasterSize::Pixels(100_000),
+                transparent_background: true,
+            },
+        );
+        assert!(canvas.width as u32 <= MAX_RASTER_DIMENSION);
+        assert!((canvas.width * canvas.height) as u64 <= MAX_RASTER_PIXELS);
+        // the aspect ratio is kept
+        assert_eq!(canvas.width, canvas.height * 2);
+
+        // tall images shrink rather than losing their bottom
+        let tall = SvgElement::new("svg")
+            .attr("viewBox", vec![0.0, 0.0, 5.0, 10.0])
+            .child(
+                SvgElement::new("rect")
+                    .attr("y", 9.0)
+                    .attr("width", 5.0)
+                    .attr("height", 1.0),
+            );
+        let canvas = rasterize(
+            &tall,
+            &RasterOptions {
+                size: RasterSize::Pixels(MAX_RASTER_DIMENSION),
+                transparent_background: true,
+            },
+        );
+        assert!(canvas.height as u32 <= MAX_RASTER_DIMENSION);
+        assert!((canvas.width * canvas.height) as u64 <= MAX_RASTER_PIXELS);
+        assert_eq!(canvas.height, canvas.width * 2);
+        assert_eq!(canvas.get_pixel(0, canvas.height - 1).a, 1.0);
+    }
+
+    #[test]
+    fn test_shrinking_lowers_dpi() {
+        let view_box = Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 10.0,
+            height: 10.0,
+        };
+        let options = RasterOptions {
+            size: RasterSize::Physical {
+                width_mm: 254.0,
+                dpi: 1000.0,
+            },
+            transparent_background: true,
+        };
+        // 10000 pixels square is shrunk to 2048, so each pixel is larger
+        assert_eq!(options.get_shrink_factor(&view_box), 0.2048);
+        assert_eq!(options.get_image_dpi(&view_box), Some(204.8));
+    }
+}
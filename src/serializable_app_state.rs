@@ -1,10 +1,30 @@
 use crate::drawing_style::DrawingStyle;
 use crate::fig::diagram::Diagram;
+use crate::migrations::migrate;
+use crate::raster::png::read_png_text;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use xml::common::Position;
 use xml::reader::{EventReader, XmlEvent};
 
+/// Namespace of the `config` element holding the app state
+pub const DOTS_NAMESPACE: &str = "https://github.com/Adjective-Object/radial-dots-yew#dots";
+
+/// Namespace older exports declared, which wasn't a uri
+const LEGACY_DOTS_NAMESPACE: &str = "dots";
+
+fn is_dots_namespace(namespace: &Option<String>) -> bool {
+    return match namespace {
+        Some(namespace) => namespace == DOTS_NAMESPACE || namespace == LEGACY_DOTS_NAMESPACE,
+        None => false,
+    };
+}
+
 #[derive(Debug, Serialize)]
 pub struct SerializableAppState<'a> {
+    /// Schema version the state is written with, `SCHEMA_VERSION`
+    pub version: u64,
     pub diagram: &'a Diagram,
     pub style: &'a DrawingStyle,
 }
@@ -23,49 +43,411 @@ pub struct DeserializedAppState {
     pub style: DrawingStyle,
 }
 
-fn get_dots_config_string(document_content: &str) -> Result<String, String> {
-    let parser = EventReader::from_str(document_content);
-    for e in parser {
-        match e {
-            Ok(XmlEvent::StartElement { attributes, .. }) => {
+/// Kinds of file app state can be loaded from
+#[derive(Debug, PartialEq, Clone)]
+pub enum DocumentFormat {
+    /// An svg download, with the state in its metadata
+    Svg,
+    /// A png download, with the state in a `dots:config` text chunk
+    Png,
+    /// The bare state, as `serialize` writes it
+    Json,
+}
+
+impl DocumentFormat {
+    /// Picks the format from a file's mime type, or from its extension as
+    /// some systems don't report a mime type for every file
+    pub fn from_file(mime_type: &str, file_name: &str) -> Option<DocumentFormat> {
+        let extension = match file_name.rfind('.') {
+            Some(index) => file_name[index + 1..].to_lowercase(),
+            None => String::new(),
+        };
+        return match (mime_type, extension.as_str()) {
+            ("image/svg+xml", _) | (_, "svg") => Some(DocumentFormat::Svg),
+            ("image/png", _) | (_, "png") => Some(DocumentFormat::Png),
+            ("application/json", _) | (_, "json") => Some(DocumentFormat::Json),
+            _ => None,
+        };
+    }
+}
+
+/// Why a document could not be loaded
+#[derive(Debug, PartialEq, Clone)]
+pub enum DocumentError {
+    /// The file itself could not be read
+    Read(String),
+    /// The document is not well formed xml. `row` and `column` count from 1
+    Xml {
+        message: String,
+        row: u64,
+        column: u64,
+    },
+    /// There is no saved app state in the document, like a `dots:config`
+    /// element or text chunk, so it was not saved by this app
+    MissingConfig,
+    /// There is a `config` element or attribute under the `dots` prefix, but
+    /// the prefix is bound to some other namespace
+    WrongNamespace { namespace: String },
+    /// The config is not json, or doesn't match the app state. `path` points
+    /// at the part that failed, like `diagram.paths[1].style`, and is empty
+    /// when the json is malformed
+    Json { path: String, message: String },
+    /// The document was saved by a newer version of the app
+    UnsupportedVersion { version: u64, supported: u64 },
+    /// A share link's state could not be unpacked, usually because the link
+    /// was cut short
+    InvalidLink(String),
+}
+
+impl fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            DocumentError::Read(message) => write!(f, "Could not read the file: {}", message),
+            DocumentError::Xml {
+                message,
+                row,
+                column,
+            } => write!(
+                f,
+                "The file is not a valid svg (line {}, column {}): {}",
+                row, column, message
+            ),
+            DocumentError::MissingConfig => write!(
+                f,
+                "The file has no saved drawing. Only files downloaded from this app can be opened"
+            ),
+            DocumentError::WrongNamespace { namespace } => write!(
+                f,
+                "The saved drawing uses the namespace \"{}\", expected \"{}\"",
+                namespace, DOTS_NAMESPACE
+            ),
+            DocumentError::Json { path, message } => {
+                if path.is_empty() {
+                    write!(f, "The saved drawing is corrupt: {}", message)
+                } else {
+                    write!(f, "The saved drawing is invalid at {}: {}", path, message)
+                }
+            }
+            DocumentError::UnsupportedVersion { version, supported } => write!(
+                f,
+                "The drawing was saved by a newer version of the app (version {}, this app reads up to {}). Reload the page to update",
+                version, supported
+            ),
+            DocumentError::InvalidLink(message) => {
+                write!(f, "The link is damaged, check it was copied in full: {}", message)
+            }
+        };
+    }
+}
+
+impl std::error::Error for DocumentError {}
+
+/// Finds the serialized app state in an svg. It is read from a `config`
+/// element, normally inside `<metadata>`, or from a `config` attribute as
+/// older exports wrote it. Both are matched by namespace, since editors
+/// that re-save the file may pick a different prefix
+fn get_dots_config_string(document_content: &str) -> Result<String, DocumentError> {
+    let mut parser = EventReader::from_str(document_content);
+    // text of the config element, once it has been opened
+    let mut config_element: Option<String> = None;
+    let mut wrong_namespace: Option<String> = None;
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                if name.local_name == "config" {
+                    if is_dots_namespace(&name.namespace) {
+                        config_element = Some(String::new());
+                        continue;
+                    }
+                    if name.prefix == Some(String::from("dots")) {
+                        wrong_namespace = name.namespace;
+                    }
+                }
                 for attr in attributes {
-                    if attr.name.local_name == "config" {
-                        match attr.name.prefix {
-                            Some(x) => {
-                                if x == "dots" {
-                                    return Ok(attr.value);
-                                }
-                            }
-                            None => {}
-                        }
+                    if attr.name.local_name != "config" {
+                        continue;
+                    }
+                    if is_dots_namespace(&attr.name.namespace) {
+                        return Ok(attr.value);
+                    }
+                    if attr.name.prefix == Some(String::from("dots")) {
+                        wrong_namespace = attr.name.namespace;
                     }
                 }
             }
+            Ok(XmlEvent::Characters(text)) | Ok(XmlEvent::CData(text)) => {
+                if let Some(config) = &mut config_element {
+                    config.push_str(&text);
+                }
+            }
+            Ok(XmlEvent::EndElement { .. }) => {
+                if let Some(config) = config_element {
+                    return Ok(config);
+                }
+            }
+            Ok(XmlEvent::EndDocument) => {
+                return Err(match wrong_namespace {
+                    Some(namespace) => DocumentError::WrongNamespace {
+                        namespace: namespace,
+                    },
+                    None => DocumentError::MissingConfig,
+                });
+            }
             Err(e) => {
-                return Err(e.to_string());
+                let position = e.position();
+                return Err(DocumentError::Xml {
+                    message: e.msg().to_string(),
+                    row: position.row + 1,
+                    column: position.column + 1,
+                });
             }
             _ => {}
         }
     }
-    return Err(String::from(
-        "did not encounter xml attribute dots:config in string",
-    ));
 }
 
-pub fn get_state_from_document_string(
-    document_content: &str,
-) -> Result<DeserializedAppState, String> {
-    let app_state: DeserializedAppState = match get_dots_config_string(document_content) {
-        Ok(config_string) => match serde_json::from_str(&config_string) {
-            Ok(app_state) => app_state,
-            Err(e) => {
-                return Err(e.to_string());
+/// Appends `value` as json with one field or element per line, alongside
+/// the path to the value each line belongs to
+fn push_json_lines(
+    value: &Value,
+    path: &str,
+    prefix: String,
+    suffix: &str,
+    lines: &mut Vec<(String, String)>,
+) {
+    let child_path = |key: String| {
+        if path.is_empty() || key.starts_with('[') {
+            format!("{}{}", path, key)
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match value {
+        Value::Object(fields) if !fields.is_empty() => {
+            lines.push((format!("{}{{", prefix), path.to_string()));
+            for (index, (key, field)) in fields.iter().enumerate() {
+                push_json_lines(
+                    field,
+                    &child_path(key.clone()),
+                    format!("{}: ", Value::String(key.clone())),
+                    if index + 1 < fields.len() { "," } else { "" },
+                    lines,
+                );
+            }
+            lines.push((format!("}}{}", suffix), path.to_string()));
+        }
+        Value::Array(elements) if !elements.is_empty() => {
+            lines.push((format!("{}[", prefix), path.to_string()));
+            for (index, element) in elements.iter().enumerate() {
+                push_json_lines(
+                    element,
+                    &child_path(format!("[{}]", index)),
+                    String::new(),
+                    if index + 1 < elements.len() { "," } else { "" },
+                    lines,
+                );
             }
+            lines.push((format!("]{}", suffix), path.to_string()));
+        }
+        _ => lines.push((format!("{}{}{}", prefix, value, suffix), path.to_string())),
+    }
+}
+
+/// Path to the part of the app state that fails to deserialize, like
+/// `diagram.paths[1].style`, since serde only says what went wrong, not
+/// where. The document is written out with one value per line and parsed
+/// again, so the line serde stops at gives the path
+fn get_invalid_path(document: &Value) -> String {
+    let mut lines: Vec<(String, String)> = vec![];
+    push_json_lines(document, "", String::new(), "", &mut lines);
+    let text: Vec<&str> = lines.iter().map(|(line, _)| line.as_str()).collect();
+    return match serde_json::from_str::<DeserializedAppState>(&text.join("\n")) {
+        Ok(_) => String::new(),
+        Err(e) => match lines.get(e.line().saturating_sub(1)) {
+            Some((_, path)) => path.clone(),
+            None => String::new(),
         },
+    };
+}
+
+/// Reads app state from any of the files the app saves
+pub fn get_state_from_document_bytes(
+    document: &[u8],
+    format: &DocumentFormat,
+) -> Result<DeserializedAppState, DocumentError> {
+    let config_string = match format {
+        DocumentFormat::Png => read_png_text(document, "dots:config"),
+        DocumentFormat::Svg | DocumentFormat::Json => {
+            let text = match std::str::from_utf8(document) {
+                Ok(text) => text,
+                Err(e) => return Err(DocumentError::Read(e.to_string())),
+            };
+            if *format == DocumentFormat::Svg {
+                return get_state_from_document_string(text);
+            }
+            Some(text.to_string())
+        }
+    };
+    return match config_string {
+        Some(config_string) => get_state_from_config_string(&config_string),
+        None => Err(DocumentError::MissingConfig),
+    };
+}
+
+pub fn get_state_from_document_string(
+    document_content: &str,
+) -> Result<DeserializedAppState, DocumentError> {
+    return get_state_from_config_string(&get_dots_config_string(document_content)?);
+}
+
+/// Reads app state serialized by `serialize`, from any schema version
+pub fn get_state_from_config_string(
+    config_string: &str,
+) -> Result<DeserializedAppState, DocumentError> {
+    let document: Value = match serde_json::from_str(config_string) {
+        Ok(document) => document,
         Err(e) => {
-            return Err(e);
+            return Err(DocumentError::Json {
+                path: String::new(),
+                message: e.to_string(),
+            });
+        }
+    };
+
+    // older documents are upgraded to the current layout before they are
+    // read into the app's types
+    let document = migrate(document)?;
+    let app_state: DeserializedAppState = match serde_json::from_value(document.clone()) {
+        Ok(app_state) => app_state,
+        Err(e) => {
+            return Err(DocumentError::Json {
+                path: get_invalid_path(&document),
+                message: e.to_string(),
+            });
         }
     };
 
     return Ok(app_state);
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::raster::canvas::Canvas;
+    use crate::raster::png::{encode_png, PngMetadata};
+    use crate::serializable_app_state::{
+        get_dots_config_string, get_state_from_document_bytes, get_state_from_document_string,
+        DocumentError, DocumentFormat, DOTS_NAMESPACE,
+    };
+    use xml::escape::escape_str_pcdata;
+
+    #[test]
+    fn test_reads_config_under_any_prefix() {
+        let config = get_dots_config_string(include_str!("fixtures/v1.svg")).unwrap();
+        // as an editor might re-save it, with its own prefix and without the
+        // root attribute
+        let resaved = format!(
+            concat!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:ns1=\"{}\">",
+                "<metadata><ns1:config>{}</ns1:config></metadata>",
+                "</svg>"
+            ),
+            DOTS_NAMESPACE,
+            escape_str_pcdata(&config)
+        );
+        let state = get_state_from_document_string(&resaved).unwrap();
+        assert_eq!(state.diagram.paths[0].text, "hi");
+    }
+
+    #[test]
+    fn test_reads_each_document_format() {
+        let svg = include_str!("fixtures/v1.svg");
+        let config = get_dots_config_string(svg).unwrap();
+        let png = encode_png(
+            &Canvas::new(1, 1),
+            &PngMetadata {
+                dpi: None,
+                text: vec![("dots:config".to_string(), config.clone())],
+            },
+        );
+        let documents = [
+            (svg.as_bytes().to_vec(), DocumentFormat::Svg),
+            (config.as_bytes().to_vec(), DocumentFormat::Json),
+            (png, DocumentFormat::Png),
+        ];
+        for (document, format) in documents.iter() {
+            let state = get_state_from_document_bytes(document, format).unwrap();
+            assert_eq!(state.diagram.paths[0].text, "hi");
+        }
+
+        let bare_png = encode_png(&Canvas::new(1, 1), &PngMetadata::default());
+        assert_eq!(
+            get_state_from_document_bytes(&bare_png, &DocumentFormat::Png).err(),
+            Some(DocumentError::MissingConfig)
+        );
+        assert_eq!(
+            DocumentFormat::from_file("", "Design.PNG"),
+            Some(DocumentFormat::Png)
+        );
+        assert_eq!(
+            DocumentFormat::from_file("application/json", "state"),
+            Some(DocumentFormat::Json)
+        );
+        assert_eq!(DocumentFormat::from_file("text/plain", "notes.txt"), None);
+    }
+
+    #[test]
+    fn test_document_errors() {
+        match get_state_from_document_string("<svg>\n<g></svg>") {
+            Err(DocumentError::Xml { row, .. }) => assert_eq!(row, 2),
+            other => panic!("expected an xml error, got {:?}", other),
+        }
+        assert_eq!(
+            get_state_from_document_string("<svg xmlns=\"http://www.w3.org/2000/svg\"/>").err(),
+            Some(DocumentError::MissingConfig)
+        );
+        assert_eq!(
+            get_state_from_document_string("<svg xmlns:dots=\"other\" dots:config=\"{}\"/>").err(),
+            Some(DocumentError::WrongNamespace {
+                namespace: String::from("other"),
+            })
+        );
+        assert_eq!(
+            get_state_from_document_string(concat!(
+                "<svg xmlns:dots=\"other\">",
+                "<metadata><dots:config>{}</dots:config></metadata>",
+                "</svg>"
+            ))
+            .err(),
+            Some(DocumentError::WrongNamespace {
+                namespace: String::from("other"),
+            })
+        );
+
+        let with_config = |config: &str| {
+            format!(
+                "<svg xmlns:dots=\"dots\" dots:config=\"{}\"/>",
+                config.replace('"', "&quot;")
+            )
+        };
+        match get_state_from_document_string(&with_config("{\"diagram\":")) {
+            Err(DocumentError::Json { path, .. }) => assert_eq!(path, ""),
+            other => panic!("expected a json error, got {:?}", other),
+        }
+        let fixture = include_str!("fixtures/v1.svg");
+        let bad_arc = fixture.replacen("\"arc_style\":null", "\"arc_style\":{}", 1);
+        match get_state_from_document_string(&bad_arc) {
+            Err(DocumentError::Json { path, message }) => {
+                assert_eq!(path, "diagram.paths[0].style.arc_style");
+                assert!(message.contains("radius"));
+            }
+            other => panic!("expected a json error, got {:?}", other),
+        }
+        let bad_mode = fixture.replacen("\"Tight\"", "\"Wide\"", 1);
+        match get_state_from_document_string(&bad_mode) {
+            Err(DocumentError::Json { path, .. }) => assert_eq!(path, "diagram.bounding_mode"),
+            other => panic!("expected a json error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,437 @@
+use crate::geom::transform::Transform2D;
+use crate::svg::output::SvgOutputOptions;
+use crate::svg::path::PathData;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result};
+
+/// Value of an attribute on an svg element
+///
+/// Numeric values are kept as numbers, so that backends walking the tree
+/// don't have to parse them back out of strings.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AttributeValue {
+    Text(String),
+    Number(f64),
+    /// Whitespace separated list, e.g. for `stroke-dasharray`
+    Numbers(Vec<f64>),
+    Path(PathData),
+    Transform(Transform2D),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SvgNode {
+    Element(SvgElement),
+    Text(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SvgElement {
+    pub name: String,
+    pub attributes: Vec<(String, AttributeValue)>,
+    pub children: Vec<SvgNode>,
+}
+
+impl From<f64> for AttributeValue {
+    fn from(value: f64) -> AttributeValue {
+        AttributeValue::Number(value)
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> AttributeValue {
+        AttributeValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> AttributeValue {
+        AttributeValue::Text(value)
+    }
+}
+
+impl From<&String> for AttributeValue {
+    fn from(value: &String) -> AttributeValue {
+        AttributeValue::Text(value.clone())
+    }
+}
+
+impl From<Vec<f64>> for AttributeValue {
+    fn from(value: Vec<f64>) -> AttributeValue {
+        AttributeValue::Numbers(value)
+    }
+}
+
+impl From<PathData> for AttributeValue {
+    fn from(value: PathData) -> AttributeValue {
+        AttributeValue::Path(value)
+    }
+}
+
+impl From<Transform2D> for AttributeValue {
+    fn from(value: Transform2D) -> AttributeValue {
+        AttributeValue::Transform(value)
+    }
+}
+
+impl From<SvgElement> for SvgNode {
+    fn from(element: SvgElement) -> SvgNode {
+        SvgNode::Element(element)
+    }
+}
+
+/// Adds `prefix` to the ids in each `url(#id)` of `text` that are in `ids`
+fn prefix_url_references(text: &str, prefix: &str, ids: &[String]) -> String {
+    let mut parts = text.split("url(#");
+    let mut prefixed = parts.next().unwrap_or("").to_string();
+    for part in parts {
+        prefixed.push_str("url(#");
+        let end = part.find(')').unwrap_or_else(|| part.len());
+        if ids.iter().any(|id| *id == part[..end]) {
+            prefixed.push_str(prefix);
+        }
+        prefixed.push_str(part);
+    }
+    return prefixed;
+}
+
+impl SvgElement {
+    pub fn new(name: &str) -> SvgElement {
+        return SvgElement {
+            name: name.to_string(),
+            attributes: vec![],
+            children: vec![],
+        };
+    }
+
+    /// Empty `<g>` element
+    pub fn group() -> SvgElement {
+        return SvgElement::new("g");
+    }
+
+    /// Sets an attribute, replacing any earlier value for the same name
+    pub fn attr<T: Into<AttributeValue>>(mut self, name: &str, value: T) -> SvgElement {
+        self.set_attr(name, value);
+        return self;
+    }
+
+    pub fn set_attr<T: Into<AttributeValue>>(&mut self, name: &str, value: T) {
+        let value = value.into();
+        for attribute in self.attributes.iter_mut() {
+            if attribute.0 == name {
+                attribute.1 = value;
+                return;
+            }
+        }
+        self.attributes.push((name.to_string(), value));
+    }
+
+    pub fn get_attr(&self, name: &str) -> Option<&AttributeValue> {
+        return self
+            .attributes
+            .iter()
+            .find(|attribute| attribute.0 == name)
+            .map(|attribute| &attribute.1);
+    }
+
+    pub fn child<T: Into<SvgNode>>(mut self, child: T) -> SvgElement {
+        self.children.push(child.into());
+        return self;
+    }
+
+    pub fn children<I: IntoIterator<Item = SvgElement>>(mut self, children: I) -> SvgElement {
+        for child in children {
+            self.children.push(SvgNode::Element(child));
+        }
+        return self;
+    }
+
+    pub fn text(mut self, text: &str) -> SvgElement {
+        self.children.push(SvgNode::Text(text.to_string()));
+        return self;
+    }
+
+    pub fn get_number_attr(&self, name: &str) -> Option<f64> {
+        return match self.get_attr(name) {
+            Some(AttributeValue::Number(number)) => Some(*number),
+            Some(AttributeValue::Text(text)) => text.trim().parse().ok(),
+            _ => None,
+        };
+    }
+
+    /// Target id of a `<use>` element, from either `href` or `xlink:href`
+    pub fn get_href_id(&self) -> Option<String> {
+        let href = match self.get_attr("href") {
+            Some(href) => href,
+            None => self.get_attr("xlink:href")?,
+        };
+        let href = href.to_string();
+        if href.starts_with('#') {
+            return Some(href[1..].to_string());
+        }
+        return None;
+    }
+
+    /// Copy of the tree with `prefix` added to every id defined in it, and
+    /// references to those ids updated to match, so several copies of it
+    /// can sit in one document
+    pub fn prefix_ids(&self, prefix: &str) -> SvgElement {
+        let mut ids: Vec<String> = vec![];
+        self.collect_ids(&mut ids);
+        return self.with_prefixed_ids(prefix, &ids);
+    }
+
+    fn collect_ids(&self, ids: &mut Vec<String>) {
+        if let Some(AttributeValue::Text(id)) = self.get_attr("id") {
+            ids.push(id.clone());
+        }
+        for child in self.child_elements() {
+            child.collect_ids(ids);
+        }
+    }
+
+    fn with_prefixed_ids(&self, prefix: &str, ids: &[String]) -> SvgElement {
+        let is_prefixed = |id: &str| ids.iter().any(|prefixed| prefixed == id);
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    AttributeValue::Text(text) => AttributeValue::Text(match name.as_str() {
+                        "id" if is_prefixed(text) => format!("{}{}", prefix, text),
+                        "href" | "xlink:href"
+                            if text.starts_with('#') && is_prefixed(&text[1..]) =>
+                        {
+                            format!("#{}{}", prefix, &text[1..])
+                        }
+                        _ => prefix_url_references(text, prefix, ids),
+                    }),
+                    value => value.clone(),
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        return SvgElement {
+            name: self.name.clone(),
+            attributes: attributes,
+            children: self
+                .children
+                .iter()
+                .map(|child| match child {
+                    SvgNode::Element(element) => {
+                        SvgNode::Element(element.with_prefixed_ids(prefix, ids))
+                    }
+                    SvgNode::Text(text) => SvgNode::Text(text.clone()),
+                })
+                .collect(),
+        };
+    }
+
+    /// Copy of the tree with each `<use>` of an element in `<defs>` replaced
+    /// by a group holding a copy of that element, for tools that mishandle
+    /// `<use>`. Ids inside each copy are prefixed to keep them unique.
+    /// Definitions that end up unused are dropped.
+    pub fn inline_uses(&self) -> SvgElement {
+        let mut definitions: HashMap<String, SvgElement> = HashMap::new();
+        self.collect_definitions(&mut definitions);
+        let mut used_ids: Vec<String> = vec![];
+        let inlined = self.inline_uses_with(&definitions, &mut used_ids);
+        return inlined.without_definitions(&used_ids);
+    }
+
+    fn collect_definitions(&self, definitions: &mut HashMap<String, SvgElement>) {
+        for child in self.child_elements() {
+            if self.name == "defs" {
+                if let Some(AttributeValue::Text(id)) = child.get_attr("id") {
+                    definitions.insert(id.clone(), child.clone());
+                }
+            }
+            child.collect_definitions(definitions);
+        }
+    }
+
+    fn inline_uses_with(
+        &self,
+        definitions: &HashMap<String, SvgElement>,
+        used_ids: &mut Vec<String>,
+    ) -> SvgElement {
+        if self.name == "use" {
+            let definition = match self.get_href_id() {
+                Some(id) => match definitions.get(&id) {
+                    Some(definition) => {
+                        used_ids.push(id);
+                        definition
+                    }
+                    None => return self.clone(),
+                },
+                None => return self.clone(),
+            };
+
+            // per the svg spec, x and y translate in the use's own space
+            let offset = Transform2D::translate(
+                self.get_number_attr("x").unwrap_or(0.0),
+                self.get_number_attr("y").unwrap_or(0.0),
+            );
+            let transform = match self.get_attr("transform") {
+                Some(AttributeValue::Transform(transform)) => offset.then(transform),
+                _ => offset,
+            };
+
+            let mut group = SvgElement::group();
+            for (name, value) in self.attributes.iter() {
+                match name.as_str() {
+                    "href" | "xlink:href" | "x" | "y" | "width" | "height" | "transform" => {}
+                    _ => group.set_attr(name, value.clone()),
+                }
+            }
+            if !transform.is_identity() {
+                group.set_attr("transform", transform);
+            }
+
+            let copy_prefix = format!("use-{}-", used_ids.len());
+            let mut copy = definition.inline_uses_with(definitions, used_ids);
+            copy.attributes.retain(|attribute| attribute.0 != "id");
+            let copy = copy.prefix_ids(&copy_prefix);
+            // anything inside the use, like animations, applies to the group
+            group = group.child(copy);
+            group.children.extend(self.children.iter().cloned());
+            return group;
+        }
+
+        return SvgElement {
+            name: self.name.clone(),
+            attributes: self.attributes.clone(),
+            children: self
+                .children
+                .iter()
+                .map(|child| match child {
+                    SvgNode::Element(element) => {
+                        SvgNode::Element(element.inline_uses_with(definitions, used_ids))
+                    }
+                    SvgNode::Text(text) => SvgNode::Text(text.clone()),
+                })
+                .collect(),
+        };
+    }
+
+    fn without_definitions(mut self, ids: &[String]) -> SvgElement {
+        let is_defs = self.name == "defs";
+        self.children = self
+            .children
+            .into_iter()
+            .filter_map(|child| match child {
+                SvgNode::Element(element) => {
+                    let inlined = is_defs
+                        && match element.get_attr("id") {
+                            Some(AttributeValue::Text(id)) => ids.contains(id),
+                            _ => false,
+                        };
+                    if inlined {
+                        return None;
+                    }
+                    let element = element.without_definitions(ids);
+                    if element.name == "defs" && element.children.is_empty() {
+                        return None;
+                    }
+                    Some(SvgNode::Element(element))
+                }
+                SvgNode::Text(text) => Some(SvgNode::Text(text)),
+            })
+            .collect();
+        return self;
+    }
+
+    /// Child elements, skipping text nodes
+    pub fn child_elements(&self) -> impl Iterator<Item = &SvgElement> {
+        return self.children.iter().filter_map(|child| match child {
+            SvgNode::Element(element) => Some(element),
+            SvgNode::Text(_) => None,
+        });
+    }
+}
+
+impl Display for AttributeValue {
+    /// Writes the unescaped attribute value, with default output options
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.write_str(&SvgOutputOptions::default().format_attribute_value("", self))
+    }
+}
+
+impl Display for SvgElement {
+    /// Serializes the element and its children as xml, with default output
+    /// options
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.write_str(&SvgOutputOptions::default().render(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::transform::Transform2D;
+    use crate::svg::element::SvgElement;
+
+    #[test]
+    fn test_serializes_nested_elements() {
+        let element = SvgElement::group()
+            .attr("transform", Transform2D::translate(1.0, 2.5))
+            .child(SvgElement::new("circle").attr("r", 1.0))
+            .child(SvgElement::new("title").text("a < b"));
+        assert_eq!(
+            element.to_string(),
+            "<g transform=\"translate(1,2.5)\"><circle r=\"1\"/><title>a &lt; b</title></g>",
+        );
+    }
+
+    #[test]
+    fn test_inline_uses() {
+        let document = SvgElement::new("svg")
+            .child(
+                SvgElement::new("defs")
+                    .child(SvgElement::new("circle").attr("id", "c").attr("r", 2.0)),
+            )
+            .child(
+                SvgElement::new("use")
+                    .attr("xlink:href", "#c")
+                    .attr("x", 3.0)
+                    .attr("y", 4.0)
+                    .attr("fill", "red"),
+            );
+        assert_eq!(
+            document.inline_uses().to_string(),
+            concat!(
+                "<svg><g fill=\"red\" transform=\"translate(3,4)\">",
+                "<circle r=\"2\"/>",
+                "</g></svg>",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_inline_uses_keeps_ids_unique() {
+        let glyph = SvgElement::group()
+            .attr("id", "glyph")
+            .child(SvgElement::new("linearGradient").attr("id", "shine"))
+            .child(SvgElement::new("circle").attr("fill", "url(#shine)"));
+        let document = SvgElement::new("svg")
+            .child(SvgElement::new("defs").child(glyph))
+            .child(SvgElement::new("use").attr("href", "#glyph"))
+            .child(SvgElement::new("use").attr("href", "#glyph"));
+        assert_eq!(
+            document.inline_uses().to_string(),
+            concat!(
+                "<svg>",
+                "<g><g><linearGradient id=\"use-1-shine\"/><circle fill=\"url(#use-1-shine)\"/></g></g>",
+                "<g><g><linearGradient id=\"use-2-shine\"/><circle fill=\"url(#use-2-shine)\"/></g></g>",
+                "</svg>",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_escapes_attribute_values() {
+        let element = SvgElement::new("rect").attr("fill", "\"/><script>");
+        assert_eq!(
+            element.to_string(),
+            "<rect fill=\"&quot;/&gt;&lt;script&gt;\"/>",
+        );
+    }
+}
@@ -1,2 +1,7 @@
+pub mod element;
+pub mod output;
+pub mod parse;
+pub mod path;
 pub mod svg_drawable;
 pub mod util;
+pub mod walk;
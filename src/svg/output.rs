@@ -0,0 +1,366 @@
+use crate::svg::element::{AttributeValue, SvgElement, SvgNode};
+use crate::svg::path::PathCommand;
+
+/// Controls how an svg tree is written out
+#[derive(Debug, PartialEq, Clone)]
+pub struct SvgOutputOptions {
+    /// Replace `<use>` references with copies of the shapes they point at,
+    /// for tools that mishandle `<use>`
+    pub inline_uses: bool,
+    /// Number of decimal places numbers are rounded to. None writes numbers
+    /// at full precision
+    pub precision: Option<usize>,
+    /// Drop groups that carry no attributes and write attributes in their
+    /// shortest form
+    pub minify: bool,
+    /// Put each element on its own, indented line. Useful for diffing
+    pub pretty: bool,
+}
+
+impl Default for SvgOutputOptions {
+    fn default() -> SvgOutputOptions {
+        SvgOutputOptions {
+            inline_uses: false,
+            precision: Some(3),
+            minify: false,
+            pretty: false,
+        }
+    }
+}
+
+/// Elements whose children can be hoisted into their parent when they have
+/// no attributes
+fn is_transparent_container(element: &SvgElement) -> bool {
+    return element.name == "g" && element.attributes.is_empty();
+}
+
+/// Shortens `#rrggbb` colors that repeat each digit to `#rgb`
+fn shorten_color(color: &str) -> Option<String> {
+    let bytes = color.as_bytes();
+    if bytes.len() == 7
+        && bytes[0] == b'#'
+        && bytes[1..].iter().all(|b| b.is_ascii_hexdigit())
+        && bytes[1] == bytes[2]
+        && bytes[3] == bytes[4]
+        && bytes[5] == bytes[6]
+    {
+        return Some(format!(
+            "#{}{}{}",
+            bytes[1] as char, bytes[3] as char, bytes[5] as char
+        ));
+    }
+    return None;
+}
+
+impl SvgOutputOptions {
+    pub fn render(&self, document: &SvgElement) -> String {
+        let inlined;
+        let mut document = if self.inline_uses {
+            inlined = document.inline_uses();
+            &inlined
+        } else {
+            document
+        };
+
+        let minified;
+        if self.minify {
+            minified = self.minify_element(document);
+            document = &minified;
+        }
+
+        let mut output = String::new();
+        self.write_element(&mut output, document, 0);
+        if self.pretty {
+            output.push('\n');
+        }
+        return output;
+    }
+
+    /// Copy of the tree without groups that have no effect on rendering
+    fn minify_element(&self, element: &SvgElement) -> SvgElement {
+        let mut children: Vec<SvgNode> = vec![];
+        for child in element.children.iter() {
+            match child {
+                SvgNode::Element(child_element) => {
+                    let minified = self.minify_element(child_element);
+                    if is_transparent_container(&minified) {
+                        children.extend(minified.children.into_iter());
+                    } else if (minified.name == "g" || minified.name == "defs")
+                        && minified.children.is_empty()
+                    {
+                        // empty groups draw nothing
+                    } else {
+                        children.push(SvgNode::Element(minified));
+                    }
+                }
+                SvgNode::Text(text) => children.push(SvgNode::Text(text.clone())),
+            }
+        }
+        return SvgElement {
+            name: element.name.clone(),
+            attributes: element.attributes.clone(),
+            children: children,
+        };
+    }
+
+    pub fn format_number(&self, number: f64) -> String {
+        let mut formatted = match self.precision {
+            Some(precision) => format!("{:.*}", precision, number),
+            None => number.to_string(),
+        };
+        if formatted.contains('.') {
+            while formatted.ends_with('0') {
+                formatted.pop();
+            }
+            if formatted.ends_with('.') {
+                formatted.pop();
+            }
+        }
+        if formatted == "-0" {
+            formatted = String::from("0");
+        }
+        if self.minify {
+            if formatted.starts_with("0.") {
+                formatted.remove(0);
+            } else if formatted.starts_with("-0.") {
+                formatted.remove(1);
+            }
+        }
+        return formatted;
+    }
+
+    /// Formats a list of numbers, like a `viewBox`. Every value is
+    /// separated, as the list grammar requires
+    fn format_numbers(&self, numbers: &[f64], separator: &str) -> String {
+        let formatted: Vec<String> = numbers.iter().map(|n| self.format_number(*n)).collect();
+        return formatted.join(if self.minify { " " } else { separator });
+    }
+
+    /// Formats the arguments of a path command. Minified path data drops the
+    /// separator before negative numbers, where the sign already separates
+    /// them
+    fn format_path_numbers(&self, numbers: &[f64]) -> String {
+        let mut formatted = String::new();
+        for (index, number) in numbers.iter().enumerate() {
+            let number = self.format_number(*number);
+            if index != 0 && !(self.minify && number.starts_with('-')) {
+                formatted.push(if self.minify { ' ' } else { ',' });
+            }
+            formatted.push_str(&number);
+        }
+        return formatted;
+    }
+
+    /// Formats the unescaped value of the attribute `name`
+    pub fn format_attribute_value(&self, name: &str, value: &AttributeValue) -> String {
+        match value {
+            AttributeValue::Text(text) => {
+                if self.minify && (name == "fill" || name == "stroke" || name == "stop-color") {
+                    if text == "transparent" {
+                        return String::from("none");
+                    }
+                    if let Some(short) = shorten_color(text) {
+                        return short;
+                    }
+                }
+                return text.clone();
+            }
+            AttributeValue::Number(number) => self.format_number(*number),
+            AttributeValue::Numbers(numbers) => self.format_numbers(numbers, " "),
+            AttributeValue::Path(path) => {
+                let mut formatted = String::new();
+                for command in path.commands.iter() {
+                    if !formatted.is_empty() && !self.minify {
+                        formatted.push(' ');
+                    }
+                    match command {
+                        PathCommand::MoveTo(point) => {
+                            formatted.push('M');
+                            formatted.push_str(&self.format_path_numbers(&[point.x, point.y]));
+                        }
+                        PathCommand::LineTo(point) => {
+                            formatted.push('L');
+                            formatted.push_str(&self.format_path_numbers(&[point.x, point.y]));
+                        }
+                        PathCommand::ArcTo {
+                            radius,
+                            large_arc,
+                            sweep,
+                            to,
+                        } => {
+                            formatted.push('A');
+                            formatted.push_str(&self.format_path_numbers(&[*radius, *radius]));
+                            formatted.push_str(&format!(
+                                " 0 {}{}{}",
+                                if *large_arc { 1 } else { 0 },
+                                if self.minify { " " } else { "," },
+                                if *sweep { 1 } else { 0 }
+                            ));
+                            let end = self.format_path_numbers(&[to.x, to.y]);
+                            if !(self.minify && end.starts_with('-')) {
+                                formatted.push(' ');
+                            }
+                            formatted.push_str(&end);
+                        }
+                        PathCommand::Close => formatted.push('Z'),
+                    }
+                }
+                return formatted;
+            }
+            AttributeValue::Transform(transform) => {
+                if transform.a == 1.0
+                    && transform.b == 0.0
+                    && transform.c == 0.0
+                    && transform.d == 1.0
+                {
+                    return format!(
+                        "translate({})",
+                        self.format_numbers(&[transform.e, transform.f], ",")
+                    );
+                }
+                return format!(
+                    "matrix({})",
+                    self.format_numbers(
+                        &[
+                            transform.a,
+                            transform.b,
+                            transform.c,
+                            transform.d,
+                            transform.e,
+                            transform.f,
+                        ],
+                        ",",
+                    )
+                );
+            }
+        }
+    }
+
+    fn write_indent(&self, output: &mut String, depth: usize) {
+        if self.pretty {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            for _ in 0..depth {
+                output.push_str("  ");
+            }
+        }
+    }
+
+    /// Writes the element as xml, with attribute values in double quotes
+    fn write_element(&self, output: &mut String, element: &SvgElement, depth: usize) {
+        self.write_indent(output, depth);
+        output.push('<');
+        output.push_str(&element.name);
+        for (name, value) in element.attributes.iter() {
+            output.push(' ');
+            output.push_str(name);
+            output.push_str("=\"");
+            output.push_str(&xml::escape::escape_str_attribute(
+                &self.format_attribute_value(name, value),
+            ));
+            output.push('"');
+        }
+
+        if element.children.is_empty() {
+            output.push_str("/>");
+            return;
+        }
+        output.push('>');
+
+        // whitespace around text would change its content, so elements
+        // holding text are kept on one line
+        let has_text = element.children.iter().any(|child| match child {
+            SvgNode::Text(_) => true,
+            SvgNode::Element(_) => false,
+        });
+        let inline_children = SvgOutputOptions {
+            pretty: self.pretty && !has_text,
+            ..self.clone()
+        };
+        for child in element.children.iter() {
+            match child {
+                SvgNode::Element(child_element) => {
+                    inline_children.write_element(output, child_element, depth + 1)
+                }
+                SvgNode::Text(text) => output.push_str(&xml::escape::escape_str_pcdata(text)),
+            }
+        }
+
+        if inline_children.pretty {
+            self.write_indent(output, depth);
+        }
+        output.push_str("</");
+        output.push_str(&element.name);
+        output.push('>');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::vector::Vector2;
+    use crate::svg::element::SvgElement;
+    use crate::svg::output::SvgOutputOptions;
+    use crate::svg::path::PathData;
+
+    #[test]
+    fn test_rounds_numbers() {
+        let options = SvgOutputOptions::default();
+        assert_eq!(options.format_number(3.0616169978683836e-16), "0");
+        assert_eq!(options.format_number(-0.0001), "0");
+        assert_eq!(options.format_number(2.5), "2.5");
+        assert_eq!(options.format_number(1.23456), "1.235");
+
+        let exact = SvgOutputOptions {
+            precision: None,
+            ..SvgOutputOptions::default()
+        };
+        assert_eq!(exact.format_number(1.23456), "1.23456");
+    }
+
+    #[test]
+    fn test_minify() {
+        let document = SvgElement::new("svg")
+            .attr("viewBox", vec![-12.05, -12.05, 24.1, 24.1])
+            .child(
+                SvgElement::group().child(SvgElement::group()).child(
+                    SvgElement::new("path")
+                        .attr(
+                            "d",
+                            PathData::new()
+                                .move_to(Vector2::new(0.5, -1.0))
+                                .line_to(Vector2::new(-0.25, 2.0)),
+                        )
+                        .attr("fill", "#aabbcc")
+                        .attr("stroke", "transparent"),
+                ),
+            );
+        let options = SvgOutputOptions {
+            minify: true,
+            ..SvgOutputOptions::default()
+        };
+        assert_eq!(
+            options.render(&document),
+            concat!(
+                "<svg viewBox=\"-12.05 -12.05 24.1 24.1\">",
+                "<path d=\"M.5-1L-.25 2\" fill=\"#abc\" stroke=\"none\"/>",
+                "</svg>"
+            ),
+        );
+    }
+
+    #[test]
+    fn test_pretty() {
+        let document = SvgElement::new("svg")
+            .child(SvgElement::new("title").text("hi"))
+            .child(SvgElement::group().child(SvgElement::new("circle")));
+        let options = SvgOutputOptions {
+            pretty: true,
+            ..SvgOutputOptions::default()
+        };
+        assert_eq!(
+            options.render(&document),
+            "<svg>\n  <title>hi</title>\n  <g>\n    <circle/>\n  </g>\n</svg>\n",
+        );
+    }
+}
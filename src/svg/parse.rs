@@ -0,0 +1,410 @@
+use crate::geom::transform::Transform2D;
+use crate::geom::vector::Vector2;
+use crate::svg::element::{AttributeValue, SvgElement, SvgNode};
+use crate::svg::path::PathData;
+use xml::reader::{EventReader, XmlEvent};
+
+/// Elements a custom glyph may use. Only shapes, grouping and paint
+/// servers that every export backend can draw are allowed, so nothing can
+/// run code, animate links or load other documents when the svg is opened
+const ALLOWED_ELEMENTS: [&str; 14] = [
+    "g",
+    "defs",
+    "use",
+    "path",
+    "circle",
+    "rect",
+    "line",
+    "polyline",
+    "polygon",
+    "title",
+    "desc",
+    "linearGradient",
+    "radialGradient",
+    "stop",
+];
+
+/// Geometry and presentation attributes a custom glyph may use. `style` is
+/// left out, since css can import other documents
+const ALLOWED_ATTRIBUTES: [&str; 32] = [
+    "id",
+    "class",
+    "transform",
+    "d",
+    "points",
+    "x",
+    "y",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "cx",
+    "cy",
+    "r",
+    "width",
+    "height",
+    "href",
+    "xlink:href",
+    "fill",
+    "fill-opacity",
+    "stroke",
+    "stroke-width",
+    "stroke-opacity",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-dasharray",
+    "opacity",
+    "offset",
+    "stop-color",
+    "stop-opacity",
+    "gradientUnits",
+];
+
+/// Whether every `url(...)` in `value` points at an element in the same
+/// document. Css escapes could spell out `url` in other ways, so values
+/// with backslashes are refused too
+fn has_only_local_urls(value: &str) -> bool {
+    if value.contains('\\') {
+        return false;
+    }
+    let lowercase = value.to_lowercase();
+    return lowercase.split("url(").skip(1).all(|reference| {
+        reference
+            .trim_start()
+            .trim_start_matches(|c| c == '"' || c == '\'')
+            .starts_with('#')
+    });
+}
+
+fn parse_numbers(text: &str) -> Option<Vec<f64>> {
+    return text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<f64>().ok())
+        .collect();
+}
+
+/// Splits path data into its command letters and numbers
+fn tokenize_path(d: &str) -> Option<Vec<(char, Vec<f64>)>> {
+    let mut commands: Vec<(char, Vec<f64>)> = vec![];
+    let mut numbers = String::new();
+    for c in d.chars() {
+        if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            if let Some(command) = commands.last_mut() {
+                command.1 = parse_numbers(&numbers.replace('-', " -").replace("e -", "e-"))?;
+            } else if !numbers.trim().is_empty() {
+                return None;
+            }
+            numbers.clear();
+            commands.push((c, vec![]));
+        } else {
+            numbers.push(c);
+        }
+    }
+    if let Some(command) = commands.last_mut() {
+        command.1 = parse_numbers(&numbers.replace('-', " -").replace("e -", "e-"))?;
+    }
+    return Some(commands);
+}
+
+/// Parses the subset of path data `PathData` can hold: moves, straight
+/// lines and circular arcs
+fn parse_path_data(d: &str) -> Option<PathData> {
+    let mut path = PathData::new();
+    let mut current = Vector2::zero();
+    let mut subpath_start = Vector2::zero();
+    for (command, numbers) in tokenize_path(d)? {
+        let relative = command.is_ascii_lowercase();
+        let offset = if relative { current } else { Vector2::zero() };
+        match command.to_ascii_uppercase() {
+            'M' | 'L' => {
+                if numbers.is_empty() || numbers.len() % 2 != 0 {
+                    return None;
+                }
+                for (index, pair) in numbers.chunks(2).enumerate() {
+                    let base = if relative { current } else { Vector2::zero() };
+                    let to = base + Vector2::new(pair[0], pair[1]);
+                    // extra pairs after a move are implicit line-tos
+                    if index == 0 && command.to_ascii_uppercase() == 'M' {
+                        path = path.move_to(to);
+                        subpath_start = to;
+                    } else {
+                        path = path.line_to(to);
+                    }
+                    current = to;
+                }
+            }
+            'H' => {
+                for x in numbers.iter() {
+                    current = Vector2::new(offset.x + x, current.y);
+                    path = path.line_to(current);
+                }
+            }
+            'V' => {
+                for y in numbers.iter() {
+                    current = Vector2::new(current.x, offset.y + y);
+                    path = path.line_to(current);
+                }
+            }
+            'A' => {
+                if numbers.is_empty() || numbers.len() % 7 != 0 {
+                    return None;
+                }
+                for arc in numbers.chunks(7) {
+                    // only circular, unrotated arcs can be represented
+                    if arc[0] != arc[1] {
+                        return None;
+                    }
+                    let base = if relative { current } else { Vector2::zero() };
+                    let to = base + Vector2::new(arc[5], arc[6]);
+                    path = path.arc_to(arc[0], arc[3] != 0.0, arc[4] != 0.0, to);
+                    current = to;
+                }
+            }
+            'Z' => {
+                path = path.close();
+                current = subpath_start;
+            }
+            _ => return None,
+        }
+    }
+    return Some(path);
+}
+
+/// Parses a transform list. Skews are left out, since they would distort
+/// circles and arcs
+fn parse_transform(text: &str) -> Option<Transform2D> {
+    let mut transform = Transform2D::identity();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let open = rest.find('(')?;
+        let close = rest.find(')')?;
+        if close < open {
+            return None;
+        }
+        let args = parse_numbers(&rest[open + 1..close])?;
+        let next = match (rest[..open].trim(), args.len()) {
+            ("matrix", 6) => Transform2D {
+                a: args[0],
+                b: args[1],
+                c: args[2],
+                d: args[3],
+                e: args[4],
+                f: args[5],
+            },
+            ("translate", 1) => Transform2D::translate(args[0], 0.0),
+            ("translate", 2) => Transform2D::translate(args[0], args[1]),
+            ("scale", 1) => Transform2D::scale(args[0], args[0]),
+            ("scale", 2) => Transform2D::scale(args[0], args[1]),
+            ("rotate", 1) => Transform2D::rotate(args[0].to_radians()),
+            ("rotate", 3) => Transform2D::translate(-args[1], -args[2])
+                .then(&Transform2D::rotate(args[0].to_radians()))
+                .then(&Transform2D::translate(args[1], args[2])),
+            _ => return None,
+        };
+        // the rightmost transform in the list applies first
+        transform = next.then(&transform);
+        rest = rest[close + 1..].trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    }
+    return Some(transform);
+}
+
+/// Whether `transform` scales every direction by the same amount, as the
+/// export backends expect
+fn is_uniform(transform: &Transform2D) -> bool {
+    let tolerance = 1e-9 * transform.get_scale_factor().max(1.0);
+    let rotates = (transform.a - transform.d).abs() <= tolerance
+        && (transform.b + transform.c).abs() <= tolerance;
+    let reflects = (transform.a + transform.d).abs() <= tolerance
+        && (transform.b - transform.c).abs() <= tolerance;
+    return rotates || reflects;
+}
+
+/// Attribute value in the form backends expect. Errors for geometry that
+/// only a browser could draw, so the glyph looks the same in every export
+fn parse_attribute_value(name: &str, value: String) -> Result<AttributeValue, String> {
+    return match name {
+        "points" => match parse_numbers(&value) {
+            Some(numbers) => Ok(AttributeValue::Numbers(numbers)),
+            None => Err(format!("points \"{}\" are not a list of numbers", value)),
+        },
+        "stroke-dasharray" => match parse_numbers(&value) {
+            Some(numbers) => Ok(AttributeValue::Numbers(numbers)),
+            None => Ok(AttributeValue::Text(value)),
+        },
+        "d" => match parse_path_data(&value) {
+            Some(path) => Ok(AttributeValue::Path(path)),
+            None => Err(String::from(
+                "path data may only use M, L, H, V, Z and circular A commands",
+            )),
+        },
+        "transform" => match parse_transform(&value) {
+            Some(transform) if is_uniform(&transform) => Ok(AttributeValue::Transform(transform)),
+            _ => Err(format!(
+                "transform \"{}\" may only translate, rotate and scale evenly",
+                value
+            )),
+        },
+        _ => Ok(AttributeValue::Text(value)),
+    };
+}
+
+/// Parses a snippet of svg markup, such as a user supplied dot glyph, into
+/// elements
+///
+/// Errors if the snippet is not well formed xml, if it uses elements or
+/// attributes outside of the allowed shapes and paint, if it has geometry
+/// the export backends can't draw, or if it refers to other documents.
+pub fn parse_fragment(snippet: &str) -> Result<Vec<SvgElement>, String> {
+    let document = format!(
+        "<g xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\">{}</g>",
+        snippet
+    );
+    // the wrapping group sits at the bottom of the stack
+    let mut stack: Vec<SvgElement> = vec![];
+    let mut elements: Vec<SvgElement> = vec![];
+    for event in EventReader::from_str(&document) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                if !ALLOWED_ELEMENTS.contains(&name.local_name.as_str()) {
+                    return Err(format!("<{}> is not allowed", name.local_name));
+                }
+                let mut element = SvgElement::new(&name.local_name);
+                for attribute in attributes {
+                    let attribute_name = match attribute.name.prefix {
+                        Some(prefix) => format!("{}:{}", prefix, attribute.name.local_name),
+                        None => attribute.name.local_name,
+                    };
+                    if !ALLOWED_ATTRIBUTES.contains(&attribute_name.as_str()) {
+                        return Err(format!("attribute {} is not allowed", attribute_name));
+                    }
+                    let is_local_link =
+                        !attribute_name.ends_with("href") || attribute.value.starts_with('#');
+                    if !is_local_link || !has_only_local_urls(&attribute.value) {
+                        return Err(String::from(
+                            "links may only refer to elements in the drawing",
+                        ));
+                    }
+                    let value = parse_attribute_value(&attribute_name, attribute.value)?;
+                    element.set_attr(&attribute_name, value);
+                }
+                stack.push(element);
+            }
+            Ok(XmlEvent::EndElement { .. }) => {
+                let element = match stack.pop() {
+                    Some(element) => element,
+                    None => return Err(String::from("unbalanced end tag")),
+                };
+                // elements directly inside the wrapping group are the result
+                if stack.len() == 1 {
+                    elements.push(element);
+                } else if let Some(parent) = stack.last_mut() {
+                    parent.children.push(SvgNode::Element(element));
+                }
+            }
+            Ok(XmlEvent::Characters(text)) | Ok(XmlEvent::CData(text)) => {
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(SvgNode::Text(text));
+                }
+            }
+            Err(e) => return Err(e.to_string()),
+            _ => {}
+        }
+    }
+    return Ok(elements);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::transform::Transform2D;
+    use crate::geom::vector::Vector2;
+    use crate::svg::element::AttributeValue;
+    use crate::svg::parse::parse_fragment;
+    use crate::svg::path::PathData;
+
+    #[test]
+    fn test_parse_fragment() {
+        let elements = parse_fragment("<path d=\"M0 -2L2,2 l-4 0z\"/><circle r=\"1\"/>").unwrap();
+        assert_eq!(elements.len(), 2);
+        assert_eq!(
+            elements[0].get_attr("d"),
+            Some(&AttributeValue::Path(
+                PathData::new()
+                    .move_to(Vector2::new(0.0, -2.0))
+                    .line_to(Vector2::new(2.0, 2.0))
+                    .line_to(Vector2::new(-2.0, 2.0))
+                    .close()
+            ))
+        );
+        assert_eq!(elements[1].get_number_attr("r"), Some(1.0));
+    }
+
+    #[test]
+    fn test_rejects_unsafe_fragments() {
+        assert!(parse_fragment("<circle r=\"1\">").is_err());
+        assert!(parse_fragment("<script>alert(1)</script>").is_err());
+        assert!(parse_fragment("<circle r=\"1\" onclick=\"alert(1)\"/>").is_err());
+        assert!(parse_fragment("<use xlink:href=\"http://example.com/a.svg#b\"/>").is_err());
+    }
+
+    #[test]
+    fn test_only_accepts_geometry_every_backend_draws() {
+        assert!(parse_fragment("<path d=\"M0 0C1 1 2 2 3 3\"/>").is_err());
+        assert!(parse_fragment("<path d=\"M0 0A1 2 0 0 1 3 3\"/>").is_err());
+        assert!(parse_fragment("<ellipse rx=\"1\" ry=\"2\"/>").is_err());
+        assert!(parse_fragment("<text>hi</text>").is_err());
+        assert!(parse_fragment("<clipPath><rect width=\"1\" height=\"1\"/></clipPath>").is_err());
+        assert!(parse_fragment("<circle r=\"1\" transform=\"skewX(30)\"/>").is_err());
+        assert!(parse_fragment("<circle r=\"1\" transform=\"scale(1 2)\"/>").is_err());
+
+        let rotated =
+            parse_fragment("<rect width=\"1\" height=\"1\" transform=\"rotate(90)\"/>").unwrap();
+        assert_eq!(
+            rotated[0].get_attr("transform"),
+            Some(&AttributeValue::Transform(Transform2D::rotate(
+                std::f64::consts::FRAC_PI_2
+            )))
+        );
+        let moved =
+            parse_fragment("<circle r=\"1\" transform=\"translate(2) scale(-3, 3)\"/>").unwrap();
+        assert_eq!(
+            moved[0].get_attr("transform"),
+            Some(&AttributeValue::Transform(
+                Transform2D::scale(-3.0, 3.0).then(&Transform2D::translate(2.0, 0.0))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_rejects_animated_and_styled_links() {
+        assert!(parse_fragment(concat!(
+            "<a href=\"#x\">",
+            "<set attributeName=\"href\" to=\"javascript:alert(1)\"/>",
+            "</a>"
+        ))
+        .is_err());
+        assert!(parse_fragment(concat!(
+            "<use href=\"#x\">",
+            "<animate attributeName=\"href\" values=\"javascript:alert(1)\"/>",
+            "</use>"
+        ))
+        .is_err());
+        assert!(parse_fragment("<style>@import url(http://example.com/a.css);</style>").is_err());
+        assert!(parse_fragment("<circle r=\"1\" style=\"fill: red\"/>").is_err());
+        assert!(
+            parse_fragment("<circle r=\"1\" fill=\"url(http://example.com/a.svg#b)\"/>").is_err()
+        );
+        assert!(parse_fragment("<circle r=\"1\" fill=\"URL( 'http://example.com')\"/>").is_err());
+        assert!(parse_fragment("<circle r=\"1\" fill=\"u\\72l(http://example.com)\"/>").is_err());
+
+        // references within the drawing are still fine
+        let gradient = concat!(
+            "<defs><linearGradient id=\"a\"><stop offset=\"0\" stop-color=\"red\"/></linearGradient></defs>",
+            "<circle r=\"1\" fill=\"url(#a)\"/>"
+        );
+        assert_eq!(parse_fragment(gradient).unwrap().len(), 2);
+    }
+}
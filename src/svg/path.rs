@@ -0,0 +1,98 @@
+use crate::geom::arc::Arc;
+use crate::geom::vector::Vector2;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PathCommand {
+    MoveTo(Vector2),
+    LineTo(Vector2),
+    /// Elliptical arc command with equal radii, as in svg's `A`
+    ArcTo {
+        radius: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Vector2,
+    },
+    Close,
+}
+
+/// Contents of a path's `d` attribute
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PathData {
+    pub commands: Vec<PathCommand>,
+}
+
+impl PathData {
+    pub fn new() -> PathData {
+        return PathData { commands: vec![] };
+    }
+
+    pub fn move_to(mut self, point: Vector2) -> PathData {
+        self.commands.push(PathCommand::MoveTo(point));
+        return self;
+    }
+
+    pub fn line_to(mut self, point: Vector2) -> PathData {
+        self.commands.push(PathCommand::LineTo(point));
+        return self;
+    }
+
+    pub fn arc_to(mut self, radius: f64, large_arc: bool, sweep: bool, to: Vector2) -> PathData {
+        self.commands.push(PathCommand::ArcTo {
+            radius: radius,
+            large_arc: large_arc,
+            sweep: sweep,
+            to: to,
+        });
+        return self;
+    }
+
+    pub fn close(mut self) -> PathData {
+        self.commands.push(PathCommand::Close);
+        return self;
+    }
+
+    /// Path following a circular arc
+    ///
+    /// Arcs sweeping a full turn or more are split in two, since an svg arc
+    /// command whose start and end points coincide draws nothing.
+    pub fn from_arc(arc: &Arc) -> PathData {
+        let path = PathData::new().move_to(arc.point_at(0.0));
+        let sweep = arc.sweep_angle >= 0.0;
+
+        if arc.is_full_circle() {
+            let half_turn = if sweep {
+                std::f64::consts::PI
+            } else {
+                -std::f64::consts::PI
+            };
+            return path
+                .arc_to(
+                    arc.radius,
+                    true,
+                    sweep,
+                    arc.point_at_angle(arc.start_angle + half_turn),
+                )
+                .arc_to(arc.radius, true, sweep, arc.point_at(0.0));
+        }
+
+        return path.arc_to(
+            arc.radius,
+            arc.sweep_angle.abs() >= std::f64::consts::PI,
+            sweep,
+            arc.point_at(1.0),
+        );
+    }
+
+    /// Closed path through the given points
+    pub fn polygon(points: &[Vector2]) -> PathData {
+        let mut path = PathData::new();
+        for (index, point) in points.iter().enumerate() {
+            path = if index == 0 {
+                path.move_to(*point)
+            } else {
+                path.line_to(*point)
+            };
+        }
+        return path.close();
+    }
+}
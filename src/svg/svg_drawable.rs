@@ -1,9 +1,16 @@
+use crate::svg::element::SvgElement;
+use crate::svg::output::SvgOutputOptions;
+
 /// Render SVG fragment
 pub trait SvgFragment<TStyle> {
-    fn as_svg_fragment(&self, stroke_color: &TStyle) -> String;
+    fn as_svg_fragment(&self, style: &TStyle) -> SvgElement;
 }
 
-/// Render to
+/// Render to a standalone `<svg>` document
 pub trait SvgRenderer<TStyle> {
-    fn as_standalone_svg(&self, stroke_color: &TStyle) -> String;
+    fn as_standalone_svg(&self, style: &TStyle) -> SvgElement;
+
+    fn as_svg_string(&self, style: &TStyle, options: &SvgOutputOptions) -> String {
+        return options.render(&self.as_standalone_svg(style));
+    }
 }
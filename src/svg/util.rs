@@ -1,22 +1,32 @@
-use crate::geom::Rect;
+use crate::geom::rect::Rect;
+use crate::geom::vector::Vector2;
+use crate::svg::element::SvgElement;
 
-pub fn translate_svg(svg_string: &str, x: f64, y: f64) -> String {
-    format!(
-        "<g transform=\"translate({x}, {y})\">{content}</g>",
-        x = x,
-        y = y,
-        content = svg_string
-    )
+/// Root `<svg>` element, showing the given area
+pub fn svg_root(view_box: &Rect) -> SvgElement {
+    return SvgElement::new("svg")
+        .attr("xmlns", "http://www.w3.org/2000/svg")
+        .attr(
+            "viewBox",
+            vec![view_box.x, view_box.y, view_box.width, view_box.height],
+        );
 }
 
-pub fn rect_svg(rect: &Rect, fill: &str) -> String {
-    return format!(
-        concat!(
-            "<rect ",
-            "x=\"{}\" y=\"{}\" ",
-            "width=\"{}\" height=\"{}\" ",
-            "fill=\"{}\"/>",
-        ),
-        rect.x, rect.y, rect.width, rect.height, fill
-    );
+pub fn rect_svg(rect: &Rect, fill: &str) -> SvgElement {
+    return SvgElement::new("rect")
+        .attr("x", rect.x)
+        .attr("y", rect.y)
+        .attr("width", rect.width)
+        .attr("height", rect.height)
+        .attr("fill", fill);
+}
+
+pub fn line_svg(from: &Vector2, to: &Vector2, stroke_width: f64, color: &str) -> SvgElement {
+    return SvgElement::new("line")
+        .attr("x1", from.x)
+        .attr("y1", from.y)
+        .attr("x2", to.x)
+        .attr("y2", to.y)
+        .attr("stroke", color)
+        .attr("stroke-width", stroke_width);
 }
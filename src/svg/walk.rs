@@ -0,0 +1,657 @@
+use crate::color::{parse_color, Rgba};
+use crate::geom::arc::Arc;
+use crate::geom::rect::Rect;
+use crate::geom::transform::Transform2D;
+use crate::geom::vector::Vector2;
+use crate::svg::element::{AttributeValue, SvgElement};
+use crate::svg::path::PathCommand;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum GradientGeometry {
+    /// Colors vary along the line from `start` to `end`
+    Linear { start: Vector2, end: Vector2 },
+    /// Colors vary with distance from `center`, reaching the last stop at
+    /// `radius`
+    Radial { center: Vector2, radius: f64 },
+}
+
+/// A gradient fill, in document space. Colors are padded past the first
+/// and last stops
+#[derive(Debug, PartialEq, Clone)]
+pub struct Gradient {
+    pub geometry: GradientGeometry,
+    /// Offsets from 0 to 1, in increasing order, and their colors
+    pub stops: Vec<(f64, Rgba)>,
+}
+
+/// Presentation attributes in effect for a shape, after inheritance
+#[derive(Debug, PartialEq, Clone)]
+pub struct Paint {
+    /// Fill color. For gradient fills, the gradient's average color, for
+    /// backends that can only fill with flat colors
+    pub fill: Option<Rgba>,
+    pub fill_gradient: Option<Gradient>,
+    pub stroke: Option<Rgba>,
+    pub stroke_width: f64,
+    pub dash_array: Vec<f64>,
+    pub opacity: f64,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Segment {
+    Line(Vector2),
+    Arc(Arc),
+}
+
+/// A connected run of segments within a path
+#[derive(Debug, PartialEq, Clone)]
+pub struct Subpath {
+    pub start: Vector2,
+    pub segments: Vec<Segment>,
+    pub closed: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Shape {
+    Circle { center: Vector2, radius: f64 },
+    Path(Vec<Subpath>),
+}
+
+/// A shape in document space, with the paint it is drawn with
+#[derive(Debug, PartialEq, Clone)]
+pub struct DrawItem {
+    pub shape: Shape,
+    pub paint: Paint,
+}
+
+impl Default for Paint {
+    /// The svg defaults: black fill, no stroke
+    fn default() -> Paint {
+        Paint {
+            fill: Some(Rgba::new(0.0, 0.0, 0.0, 1.0)),
+            fill_gradient: None,
+            stroke: None,
+            stroke_width: 1.0,
+            dash_array: vec![],
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Subpath {
+    pub fn get_end(&self) -> Vector2 {
+        return match self.segments.last() {
+            Some(Segment::Line(point)) => *point,
+            Some(Segment::Arc(arc)) => arc.point_at(1.0),
+            None => self.start,
+        };
+    }
+
+    /// Approximates the subpath with straight lines no further than
+    /// `tolerance` from the true curve. Closed subpaths end on their start.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Vector2> {
+        let mut points: Vec<Vector2> = vec![self.start];
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Line(point) => points.push(*point),
+                Segment::Arc(arc) => points.extend(arc.flatten(tolerance).into_iter().skip(1)),
+            }
+        }
+        if self.closed && points.last() != Some(&self.start) {
+            points.push(self.start);
+        }
+        return points;
+    }
+}
+
+impl Shape {
+    /// Outline of the shape as polylines, one per subpath. Circles and
+    /// closed subpaths end on the point they start from
+    pub fn flatten(&self, tolerance: f64) -> Vec<Vec<Vector2>> {
+        return match self {
+            Shape::Circle { center, radius } => vec![Arc {
+                center: *center,
+                radius: *radius,
+                start_angle: 0.0,
+                sweep_angle: std::f64::consts::PI * 2.0,
+            }
+            .flatten(tolerance)],
+            Shape::Path(subpaths) => subpaths
+                .iter()
+                .map(|subpath| subpath.flatten(tolerance))
+                .collect(),
+        };
+    }
+
+    /// Bounds of the geometry, not counting stroke width
+    pub fn get_bounding_rect(&self) -> Option<Rect> {
+        match self {
+            Shape::Circle { center, radius } => Some(Rect::around_point(center, *radius)),
+            Shape::Path(subpaths) => {
+                let mut bounds: Option<Rect> = None;
+                for subpath in subpaths.iter() {
+                    let mut subpath_bounds = Rect::around_point(&subpath.start, 0.0);
+                    for segment in subpath.segments.iter() {
+                        subpath_bounds = subpath_bounds.union(&match segment {
+                            Segment::Line(point) => Rect::around_point(point, 0.0),
+                            Segment::Arc(arc) => arc.bounding_rect(),
+                        });
+                    }
+                    bounds = Some(match bounds {
+                        Some(bounds) => bounds.union(&subpath_bounds),
+                        None => subpath_bounds,
+                    });
+                }
+                bounds
+            }
+        }
+    }
+}
+
+/// Color at `offset` along gradient stops, padded past the first and last
+fn get_stop_color(stops: &[(f64, Rgba)], offset: f64) -> Rgba {
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Rgba::transparent(),
+    };
+    if offset <= first.0 {
+        return first.1;
+    }
+    for pair in stops.windows(2) {
+        let ((from_offset, from), (to_offset, to)) = (pair[0], pair[1]);
+        if offset <= to_offset {
+            if to_offset <= from_offset {
+                return to;
+            }
+            return from.lerp(&to, (offset - from_offset) / (to_offset - from_offset));
+        }
+    }
+    return last.1;
+}
+
+/// Color of gradient stops averaged over offsets from 0 to 1
+fn get_average_color(stops: &[(f64, Rgba)]) -> Rgba {
+    const SAMPLES: usize = 16;
+    let mut sum = Rgba::transparent();
+    for index in 0..SAMPLES {
+        let color = get_stop_color(stops, (index as f64 + 0.5) / SAMPLES as f64);
+        sum = Rgba::new(
+            sum.r + color.r,
+            sum.g + color.g,
+            sum.b + color.b,
+            sum.a + color.a,
+        );
+    }
+    let scale = 1.0 / SAMPLES as f64;
+    return Rgba::new(sum.r * scale, sum.g * scale, sum.b * scale, sum.a * scale);
+}
+
+impl Gradient {
+    /// Color of the gradient at `point`
+    pub fn color_at(&self, point: &Vector2) -> Rgba {
+        let offset = match &self.geometry {
+            GradientGeometry::Linear { start, end } => {
+                let direction = *end - *start;
+                let length_squared = direction.dot(&direction);
+                if length_squared == 0.0 {
+                    1.0
+                } else {
+                    (*point - *start).dot(&direction) / length_squared
+                }
+            }
+            GradientGeometry::Radial { center, radius } => {
+                if *radius <= 0.0 {
+                    1.0
+                } else {
+                    point.distance(center) / radius
+                }
+            }
+        };
+        return get_stop_color(&self.stops, offset);
+    }
+}
+
+/// Splits a polyline into the dashes of a dash pattern
+pub fn apply_dashes(points: &[Vector2], dash_array: &[f64]) -> Vec<Vec<Vector2>> {
+    let pattern_length: f64 = dash_array.iter().sum();
+    if dash_array.iter().any(|length| *length < 0.0) || pattern_length <= 0.0 {
+        return vec![points.to_vec()];
+    }
+    // odd patterns repeat to make an even one, as in svg
+    let pattern: Vec<f64> = if dash_array.len() % 2 == 1 {
+        dash_array
+            .iter()
+            .chain(dash_array.iter())
+            .cloned()
+            .collect()
+    } else {
+        dash_array.to_vec()
+    };
+
+    let mut dashes: Vec<Vec<Vector2>> = vec![];
+    let mut current: Vec<Vector2> = vec![];
+    let mut pattern_index = 0;
+    let mut remaining = pattern[0];
+    let mut drawing = true;
+    if let Some(first) = points.first() {
+        current.push(*first);
+    }
+    for pair in points.windows(2) {
+        let (mut from, to) = (pair[0], pair[1]);
+        let mut segment_left = from.distance(&to);
+        while segment_left > remaining {
+            let split = from.lerp(&to, remaining / segment_left);
+            segment_left -= remaining;
+            from = split;
+            if drawing {
+                current.push(split);
+                dashes.push(current);
+                current = vec![];
+            } else {
+                current.push(split);
+            }
+            drawing = !drawing;
+            pattern_index = (pattern_index + 1) % pattern.len();
+            remaining = pattern[pattern_index];
+        }
+        remaining -= segment_left;
+        if drawing {
+            current.push(to);
+        }
+    }
+    if drawing && current.len() > 1 {
+        dashes.push(current);
+    }
+    return dashes;
+}
+
+/// The area the document's `viewBox` shows, if it has one
+pub fn get_view_box(document: &SvgElement) -> Option<Rect> {
+    return match document.get_attr("viewBox") {
+        Some(AttributeValue::Numbers(numbers)) if numbers.len() == 4 => Some(Rect {
+            x: numbers[0],
+            y: numbers[1],
+            width: numbers[2],
+            height: numbers[3],
+        }),
+        _ => None,
+    };
+}
+
+/// Flattens the document into the shapes it draws, in document order,
+/// mapped from the root element's coordinate space by `transform`
+///
+/// `<use>` references are resolved, invisible elements (`<defs>`,
+/// `<title>`, ...) are skipped, and only uniform scales and rotations are
+/// expected in transforms. Gradient fills are resolved when their
+/// `gradientUnits` is `userSpaceOnUse`, and fall back to their average
+/// color otherwise.
+pub fn walk_document(document: &SvgElement, transform: &Transform2D) -> Vec<DrawItem> {
+    let document = document.inline_uses();
+    let mut gradients: HashMap<String, SvgElement> = HashMap::new();
+    collect_gradients(&document, &mut gradients);
+
+    let mut items: Vec<DrawItem> = vec![];
+    walk_element(
+        &document,
+        transform,
+        &Paint {
+            stroke_width: transform.get_scale_factor(),
+            ..Paint::default()
+        },
+        &gradients,
+        &mut items,
+    );
+    return items;
+}
+
+fn collect_gradients(element: &SvgElement, gradients: &mut HashMap<String, SvgElement>) {
+    for child in element.child_elements() {
+        if child.name == "linearGradient" || child.name == "radialGradient" {
+            if let Some(AttributeValue::Text(id)) = child.get_attr("id") {
+                gradients.insert(id.clone(), child.clone());
+            }
+        }
+        collect_gradients(child, gradients);
+    }
+}
+
+/// Id referenced by a paint of the form `url(#id)`
+fn get_paint_reference(paint: &str) -> Option<&str> {
+    let paint = paint.trim();
+    if paint.starts_with("url(#") && paint.ends_with(')') {
+        return Some(&paint[5..paint.len() - 1]);
+    }
+    return None;
+}
+
+/// Stops of a gradient element, as offsets and colors
+fn get_gradient_stops(gradient: &SvgElement) -> Vec<(f64, Rgba)> {
+    let mut stops: Vec<(f64, Rgba)> = vec![];
+    for stop in gradient
+        .child_elements()
+        .filter(|child| child.name == "stop")
+    {
+        let offset = match stop.get_attr("offset") {
+            Some(AttributeValue::Text(text)) if text.trim().ends_with('%') => text
+                .trim()
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .ok()
+                .map(|percent| percent / 100.0),
+            _ => stop.get_number_attr("offset"),
+        };
+        let color = match stop.get_attr("stop-color") {
+            Some(AttributeValue::Text(text)) => parse_color(text),
+            _ => Some(Rgba::new(0.0, 0.0, 0.0, 1.0)),
+        };
+        if let (Some(offset), Some(color)) = (offset, color) {
+            let opacity = stop.get_number_attr("stop-opacity").unwrap_or(1.0);
+            // offsets never decrease, as in svg
+            let previous = stops.last().map_or(0.0, |previous| previous.0);
+            stops.push((
+                offset.max(0.0).min(1.0).max(previous),
+                color.with_alpha(color.a * opacity),
+            ));
+        }
+    }
+    return stops;
+}
+
+/// The gradient `gradient` draws for an element with the given transform.
+/// None if its geometry depends on the element's bounds, which isn't
+/// supported
+fn get_gradient(gradient: &SvgElement, transform: &Transform2D) -> Option<GradientGeometry> {
+    match gradient.get_attr("gradientUnits") {
+        Some(AttributeValue::Text(units)) if units == "userSpaceOnUse" => {}
+        _ => return None,
+    }
+    let number = |name: &str| gradient.get_number_attr(name).unwrap_or(0.0);
+    return match gradient.name.as_str() {
+        "linearGradient" => Some(GradientGeometry::Linear {
+            start: transform.apply(&Vector2::new(number("x1"), number("y1"))),
+            end: transform.apply(&Vector2::new(number("x2"), number("y2"))),
+        }),
+        "radialGradient" => Some(GradientGeometry::Radial {
+            center: transform.apply(&Vector2::new(number("cx"), number("cy"))),
+            radius: number("r") * transform.get_scale_factor(),
+        }),
+        _ => None,
+    };
+}
+
+fn get_color_attr(element: &SvgElement, name: &str, inherited: &Option<Rgba>) -> Option<Rgba> {
+    return match element.get_attr(name) {
+        Some(AttributeValue::Text(text)) => match text.trim() {
+            "none" | "transparent" => None,
+            "inherit" => *inherited,
+            text => parse_color(text),
+        },
+        _ => *inherited,
+    };
+}
+
+/// Paint for `element`, given what it inherits. Lengths set on the element
+/// are scaled from its own space by `transform`
+fn get_paint(
+    element: &SvgElement,
+    inherited: &Paint,
+    transform: &Transform2D,
+    gradients: &HashMap<String, SvgElement>,
+) -> Paint {
+    let scale = transform.get_scale_factor();
+    let gradient_fill = match element.get_attr("fill") {
+        Some(AttributeValue::Text(text)) => get_paint_reference(text)
+            .and_then(|id| gradients.get(id))
+            .map(|gradient| {
+                let stops = get_gradient_stops(gradient);
+                (get_gradient(gradient, transform), stops)
+            }),
+        _ => None,
+    };
+    let (fill, fill_gradient) = match gradient_fill {
+        Some((Some(geometry), stops)) => (
+            Some(get_average_color(&stops)),
+            Some(Gradient {
+                geometry: geometry,
+                stops: stops,
+            }),
+        ),
+        Some((None, stops)) => (Some(get_average_color(&stops)), None),
+        None => match element.get_attr("fill") {
+            Some(_) => (get_color_attr(element, "fill", &inherited.fill), None),
+            None => (inherited.fill, inherited.fill_gradient.clone()),
+        },
+    };
+    let mut paint = Paint {
+        fill: fill,
+        fill_gradient: fill_gradient,
+        stroke: get_color_attr(element, "stroke", &inherited.stroke),
+        stroke_width: match element.get_number_attr("stroke-width") {
+            Some(width) => width * scale,
+            None => inherited.stroke_width,
+        },
+        dash_array: match element.get_attr("stroke-dasharray") {
+            Some(AttributeValue::Numbers(numbers)) => {
+                numbers.iter().map(|length| length * scale).collect()
+            }
+            Some(AttributeValue::Text(text)) if text == "none" => vec![],
+            _ => inherited.dash_array.clone(),
+        },
+        opacity: inherited.opacity * element.get_number_attr("opacity").unwrap_or(1.0),
+    };
+    if let Some(fill_opacity) = element.get_number_attr("fill-opacity") {
+        paint.fill = paint
+            .fill
+            .map(|fill| fill.with_alpha(fill.a * fill_opacity));
+        if let Some(gradient) = paint.fill_gradient.as_mut() {
+            for stop in gradient.stops.iter_mut() {
+                stop.1 = stop.1.with_alpha(stop.1.a * fill_opacity);
+            }
+        }
+    }
+    if let Some(stroke_opacity) = element.get_number_attr("stroke-opacity") {
+        paint.stroke = paint
+            .stroke
+            .map(|stroke| stroke.with_alpha(stroke.a * stroke_opacity));
+    }
+    return paint;
+}
+
+fn transform_arc(arc: &Arc, transform: &Transform2D) -> Arc {
+    let center = transform.apply(&arc.center);
+    return Arc {
+        center: center,
+        radius: arc.radius * transform.get_scale_factor(),
+        start_angle: (transform.apply(&arc.point_at(0.0)) - center).angle(),
+        // reflections reverse the direction the arc turns in
+        sweep_angle: if transform.determinant() < 0.0 {
+            -arc.sweep_angle
+        } else {
+            arc.sweep_angle
+        },
+    };
+}
+
+fn get_shape(element: &SvgElement, transform: &Transform2D) -> Option<Shape> {
+    let number = |name: &str| element.get_number_attr(name).unwrap_or(0.0);
+    let point = |x: &str, y: &str| transform.apply(&Vector2::new(number(x), number(y)));
+
+    match element.name.as_str() {
+        "circle" => Some(Shape::Circle {
+            center: point("cx", "cy"),
+            radius: number("r") * transform.get_scale_factor(),
+        }),
+        "rect" => {
+            let (x, y, width, height) =
+                (number("x"), number("y"), number("width"), number("height"));
+            let corner = |dx: f64, dy: f64| transform.apply(&Vector2::new(x + dx, y + dy));
+            Some(Shape::Path(vec![Subpath {
+                start: corner(0.0, 0.0),
+                segments: vec![
+                    Segment::Line(corner(width, 0.0)),
+                    Segment::Line(corner(width, height)),
+                    Segment::Line(corner(0.0, height)),
+                ],
+                closed: true,
+            }]))
+        }
+        "line" => Some(Shape::Path(vec![Subpath {
+            start: point("x1", "y1"),
+            segments: vec![Segment::Line(point("x2", "y2"))],
+            closed: false,
+        }])),
+        "polygon" | "polyline" => match element.get_attr("points") {
+            Some(AttributeValue::Numbers(numbers)) if numbers.len() >= 2 => {
+                let points: Vec<Vector2> = numbers
+                    .chunks(2)
+                    .filter(|pair| pair.len() == 2)
+                    .map(|pair| transform.apply(&Vector2::new(pair[0], pair[1])))
+                    .collect();
+                Some(Shape::Path(vec![Subpath {
+                    start: points[0],
+                    segments: points[1..].iter().map(|p| Segment::Line(*p)).collect(),
+                    closed: element.name == "polygon",
+                }]))
+            }
+            _ => None,
+        },
+        "path" => match element.get_attr("d") {
+            Some(AttributeValue::Path(path)) => {
+                let mut subpaths: Vec<Subpath> = vec![];
+                // the current point, in the element's own space
+                let mut current = Vector2::zero();
+                let mut subpath_start = Vector2::zero();
+                for command in path.commands.iter() {
+                    match command {
+                        PathCommand::MoveTo(to) => {
+                            current = *to;
+                            subpath_start = *to;
+                            subpaths.push(Subpath {
+                                start: transform.apply(to),
+                                segments: vec![],
+                                closed: false,
+                            });
+                        }
+                        PathCommand::LineTo(to) => {
+                            if let Some(subpath) = subpaths.last_mut() {
+                                subpath.segments.push(Segment::Line(transform.apply(to)));
+                            }
+                            current = *to;
+                        }
+                        PathCommand::ArcTo {
+                            radius,
+                            large_arc,
+                            sweep,
+                            to,
+                        } => {
+                            if let Some(subpath) = subpaths.last_mut() {
+                                subpath.segments.push(
+                                    match Arc::from_endpoints(
+                                        &current, to, *radius, *large_arc, *sweep,
+                                    ) {
+                                        Some(arc) => Segment::Arc(transform_arc(&arc, transform)),
+                                        None => Segment::Line(transform.apply(to)),
+                                    },
+                                );
+                            }
+                            current = *to;
+                        }
+                        PathCommand::Close => {
+                            if let Some(subpath) = subpaths.last_mut() {
+                                subpath.closed = true;
+                            }
+                            current = subpath_start;
+                        }
+                    }
+                }
+                Some(Shape::Path(subpaths))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn walk_element(
+    element: &SvgElement,
+    parent_transform: &Transform2D,
+    parent_paint: &Paint,
+    gradients: &HashMap<String, SvgElement>,
+    items: &mut Vec<DrawItem>,
+) {
+    match element.name.as_str() {
+        "defs" | "title" | "desc" | "metadata" | "style" | "script" | "linearGradient"
+        | "radialGradient" | "symbol" | "animate" | "set" => return,
+        _ => {}
+    }
+    match element.get_attr("display") {
+        Some(AttributeValue::Text(display)) if display == "none" => return,
+        _ => {}
+    }
+
+    let transform = match element.get_attr("transform") {
+        Some(AttributeValue::Transform(transform)) => transform.then(parent_transform),
+        _ => *parent_transform,
+    };
+    let mut paint = get_paint(element, parent_paint, &transform, gradients);
+    if element.name == "line" {
+        // lines enclose no area, so are never filled
+        paint.fill = None;
+        paint.fill_gradient = None;
+    }
+
+    if let Some(shape) = get_shape(element, &transform) {
+        items.push(DrawItem {
+            shape: shape,
+            paint: paint,
+        });
+        return;
+    }
+
+    for child in element.child_elements() {
+        walk_element(child, &transform, &paint, gradients, items);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::transform::Transform2D;
+    use crate::geom::vector::Vector2;
+    use crate::svg::element::SvgElement;
+    use crate::svg::walk::{walk_document, Shape};
+
+    #[test]
+    fn test_walk_resolves_transforms_and_paint() {
+        let document = SvgElement::new("svg")
+            .child(
+                SvgElement::new("defs")
+                    .child(SvgElement::new("circle").attr("id", "dot").attr("r", 1.0)),
+            )
+            .child(
+                SvgElement::group()
+                    .attr("transform", Transform2D::scale(2.0, 2.0))
+                    .attr("fill", "#ff0000")
+                    .attr("stroke-width", 0.5)
+                    .child(
+                        SvgElement::new("use")
+                            .attr("xlink:href", "#dot")
+                            .attr("x", 3.0)
+                            .attr("y", 0.0),
+                    ),
+            );
+
+        let items = walk_document(&document, &Transform2D::identity());
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0].shape,
+            Shape::Circle {
+                center: Vector2::new(6.0, 0.0),
+                radius: 2.0,
+            }
+        );
+        assert_eq!(items[0].paint.fill.unwrap().r, 1.0);
+        assert_eq!(items[0].paint.stroke_width, 1.0);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::serializable_app_state::{
+    get_state_from_config_string, serialize, DeserializedAppState, DocumentError,
+    SerializableAppState,
+};
+
+/// Key of the url fragment parameter holding the app state
+const STATE_KEY: &str = "d=";
+
+/// Packs the app state into a url fragment, without the leading `#`
+///
+/// The serialized state is deflated and then base64url encoded, so it can
+/// be pasted into chat and mail without being mangled.
+pub fn encode_url_fragment(app_state: &SerializableAppState) -> String {
+    let compressed = miniz_oxide::deflate::compress_to_vec(serialize(app_state).as_bytes(), 9);
+    return format!(
+        "{}{}",
+        STATE_KEY,
+        base64::encode_config(&compressed, base64::URL_SAFE_NO_PAD)
+    );
+}
+
+/// Unpacks app state from a url fragment made by `encode_url_fragment`.
+/// Returns `None` when the fragment holds no state, so other fragments
+/// don't show as errors
+pub fn decode_url_fragment(fragment: &str) -> Option<Result<DeserializedAppState, DocumentError>> {
+    let fragment = fragment.trim_start_matches('#');
+    let encoded = fragment
+        .split('&')
+        .find(|parameter| parameter.starts_with(STATE_KEY))?
+        .trim_start_matches(STATE_KEY);
+
+    let compressed = match base64::decode_config(encoded, base64::URL_SAFE_NO_PAD) {
+        Ok(compressed) => compressed,
+        Err(e) => return Some(Err(DocumentError::InvalidLink(e.to_string()))),
+    };
+    let config_bytes = match miniz_oxide::inflate::decompress_to_vec(&compressed) {
+        Ok(config_bytes) => config_bytes,
+        Err(e) => {
+            return Some(Err(DocumentError::InvalidLink(format!(
+                "could not decompress ({:?})",
+                e
+            ))))
+        }
+    };
+    return Some(match String::from_utf8(config_bytes) {
+        Ok(config_string) => get_state_from_config_string(&config_string),
+        Err(e) => Err(DocumentError::InvalidLink(e.to_string())),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::migrations::SCHEMA_VERSION;
+    use crate::serializable_app_state::{get_state_from_document_string, SerializableAppState};
+    use crate::url_state::{decode_url_fragment, encode_url_fragment};
+
+    #[test]
+    fn test_url_fragment_roundtrip() {
+        let state = get_state_from_document_string(include_str!("fixtures/v1.svg")).unwrap();
+        let fragment = encode_url_fragment(&SerializableAppState {
+            version: SCHEMA_VERSION,
+            diagram: &state.diagram,
+            style: &state.style,
+        });
+        assert!(fragment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '='));
+
+        let restored = decode_url_fragment(&format!("#{}", fragment))
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.diagram.paths[0].text, "hi");
+        assert_eq!(
+            restored.style.default_arc_style,
+            state.style.default_arc_style
+        );
+
+        assert!(decode_url_fragment("#top").is_none());
+        let truncated = &fragment[..fragment.len() / 2];
+        assert!(decode_url_fragment(truncated).unwrap().is_err());
+    }
+}